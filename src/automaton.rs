@@ -1,83 +1,333 @@
 //! Build a finite automaton from a regular expression.
 
 use std::borrow::Borrow;
-use std::borrow::Cow;
 use std::collections::HashMap;
 use std::collections::HashSet;
-use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::hash::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
+
+use itertools::Itertools;
 
 use crate::builder::ApproximatelySimilarCanonical;
+use crate::builder::Builder;
 use crate::builder::Regex;
 use crate::derivation::Symbols;
 use crate::Alphabet;
 
+mod alternating;
+mod batch;
+mod bit_parallel;
+mod boolean;
+mod cache;
+mod cheapest;
+mod classify;
+mod corpus;
+mod delta;
+mod dense;
+mod distinguish;
+mod grammar;
+mod lines;
+mod memory;
+mod minimize;
+mod monitor;
+mod nfa;
+mod parts;
+#[cfg(feature = "pattern")]
+mod pattern;
+mod prefilter;
+mod read;
+mod shared;
+mod suffix;
+mod table;
+mod thompson;
+mod transitions;
+mod trim;
+
+pub use alternating::AlternatingAutomaton;
+pub use bit_parallel::BitParallelAutomaton;
+pub use cache::DerivativeCache;
+pub use cache::SharedDerivativeCache;
+pub use classify::StateClass;
+pub use corpus::Corpus;
+pub use corpus::CorpusOptions;
+pub use delta::DeltaAutomaton;
+pub use delta::DeltaMatcher;
+pub use dense::DenseAutomaton;
+pub use dense::DenseMatcher;
+pub use dense::IndexedAutomaton;
+pub use dense::IndexedMatcher;
+pub use grammar::Production;
+pub use grammar::RightLinearGrammar;
+pub use lines::LineScanner;
+pub use lines::Mismatch;
+pub use lines::Position;
+pub use lines::Recovery;
+pub use lines::Rescan;
+pub use lines::Span;
+pub use memory::MemoryUsage;
+pub use monitor::Monitor;
+pub use monitor::Verdict;
+pub use nfa::Nfa;
+pub use parts::AutomatonPartsError;
+#[cfg(feature = "pattern")]
+pub use pattern::AutomatonSearcher;
+pub use read::ReadScanError;
+pub use read::ReadScanner;
+pub use read::Token;
+use shared::Handle;
+pub use shared::SharedAutomaton;
+pub use suffix::SuffixAutomaton;
+pub use table::AutomatonTableError;
+use transitions::Representation;
+use transitions::Transitions;
+
+/// A deterministic finite automaton, optionally with user data of type `L` attached to its states.
+/// States are identified by values of type `I`.
 #[derive(Clone)]
-pub struct FiniteAutomaton<S: Alphabet> {
-    states: Vec<State<S>>,
+pub struct FiniteAutomaton<S: Alphabet, L = (), I: StateId = usize> {
+    states: Vec<State<S, L, I>>,
 }
 
 #[derive(Clone)]
-struct State<S: Alphabet> {
-    regex: Regex<ApproximatelySimilarCanonical<S>>,
+struct State<S: Alphabet, L, I: StateId> {
+    /// The residual regular expression of the state, or `None` if it was dropped or is not
+    /// known. States that cannot reach an accepting state have the empty set.
+    regex: Option<Regex<ApproximatelySimilarCanonical<S>>>,
     accepting: bool,
-    transitions: HashMap<S, usize>,
-    default_transition: usize,
+    /// Whether only accepting states can be reached from the state, so that its residual
+    /// language contains every word.
+    universal: bool,
+    transitions: Transitions<S, I>,
+    default_transition: I,
+    label: L,
+}
+
+/// An unsigned integer type that identifies the states of a [`FiniteAutomaton`].
+///
+/// The transitions of an automaton store state identifiers, so a smaller type reduces the
+/// memory used by large automata.
+pub trait StateId: Copy + Debug + Eq + Hash + Send + Sync {
+    /// Returns the identifier for the state with the given index, if it fits in this type.
+    fn from_index(index: usize) -> Option<Self>;
+
+    /// Returns the index of the state with this identifier.
+    fn index(self) -> usize;
+}
+
+macro_rules! impl_state_id {
+    ($($ty:ty),*) => {
+        $(
+            impl StateId for $ty {
+                #[inline]
+                fn from_index(index: usize) -> Option<Self> {
+                    index.try_into().ok()
+                }
+
+                #[inline]
+                fn index(self) -> usize {
+                    self as usize
+                }
+            }
+        )*
+    };
+}
+
+impl_state_id!(u8, u16, u32, usize);
+
+/// Returns the identifier for the state with the given index.
+///
+/// Panics if the index does not fit in the identifier type.
+pub(crate) fn state_id<I: StateId>(index: usize) -> I {
+    I::from_index(index).unwrap_or_else(|| {
+        panic!(
+            "state {} does not fit in state identifier type {}",
+            index,
+            std::any::type_name::<I>()
+        )
+    })
+}
+
+/// A value with its precomputed hash. Hash maps with these keys hash the value only once,
+/// instead of on every lookup and every time the map grows, which is expensive for large
+/// regular expressions.
+#[derive(Clone, Debug)]
+pub(crate) struct Prehashed<T> {
+    hash: u64,
+    pub(crate) value: T,
+}
+
+impl<T: Hash> Prehashed<T> {
+    pub(crate) fn new(value: T) -> Self {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        Self {
+            hash: hasher.finish(),
+            value,
+        }
+    }
+}
+
+impl<T> Hash for Prehashed<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+impl<T: PartialEq> PartialEq for Prehashed<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.value == other.value
+    }
+}
+
+impl<T: Eq> Eq for Prehashed<T> {}
+
+impl<B: Builder> Regex<B> {
+    /// Build a finite automaton whose states are the derivatives of this regular expression.
+    ///
+    /// The regular expression is first rebuilt with the [`ApproximatelySimilarCanonical`]
+    /// builder, which guarantees that there are finitely many derivatives, regardless of the
+    /// builder it was built with. The states of the automaton refer to the rebuilt derivatives.
+    pub fn to_automaton(&self) -> FiniteAutomaton<B::Symbol> {
+        self.rebuild::<ApproximatelySimilarCanonical<_>>()
+            .build_automaton(false, Representation::Hashed, &mut DerivativeCache::new())
+    }
+
+    /// Build a finite automaton that identifies its states by values of type `I`, such as
+    /// `u16` or `u32`, to reduce the memory used by its transitions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the automaton has more states than can be identified by `I`.
+    pub fn to_automaton_with_state_ids<I: StateId>(&self) -> FiniteAutomaton<B::Symbol, (), I> {
+        self.rebuild::<ApproximatelySimilarCanonical<_>>()
+            .build_automaton(false, Representation::Hashed, &mut DerivativeCache::new())
+    }
+
+    /// Build a finite automaton that stores the transitions of every state as a vector sorted
+    /// by symbol, instead of a hash map. See [`FiniteAutomaton::into_sparse`].
+    pub fn to_automaton_with_sparse_transitions(&self) -> FiniteAutomaton<B::Symbol> {
+        self.rebuild::<ApproximatelySimilarCanonical<_>>()
+            .build_automaton(false, Representation::Sparse, &mut DerivativeCache::new())
+    }
+
+    /// Build a finite automaton, merging states whose residual regular expressions have the
+    /// same language, even if they are not syntactically equal.
+    ///
+    /// Every new residual is checked for equivalence against the existing states with the
+    /// same nullability, and the same symbols for which the derivative is not the empty set.
+    /// Equivalent residuals whose derivatives are empty, but not recognized as the empty set,
+    /// may therefore not be merged. This results in fewer states for regular expressions with many
+    /// intersections and complements, at the cost of the equivalence checks.
+    pub fn to_automaton_merging_equivalent(&self) -> FiniteAutomaton<B::Symbol> {
+        self.rebuild::<ApproximatelySimilarCanonical<_>>()
+            .build_automaton(true, Representation::Hashed, &mut DerivativeCache::new())
+    }
+
+    /// Returns the number of distinct residual languages of this regular expression, which is
+    /// the number of states of its minimal automaton, including a state for the empty set if
+    /// it is a residual.
+    ///
+    /// Compared to the state count of [`Regex::to_automaton`], this shows how many states are
+    /// due to residuals that the builder could not identify.
+    pub fn nerode_index(&self) -> usize {
+        self.to_automaton().minimize().state_count()
+    }
 }
 
 impl<S: Alphabet> Regex<ApproximatelySimilarCanonical<S>> {
-    // FIXME add docs
-    pub fn to_automaton(&self) -> FiniteAutomaton<S> {
+    fn build_automaton<I: StateId>(
+        &self,
+        merge_equivalent: bool,
+        representation: Representation,
+        cache: &mut DerivativeCache<S>,
+    ) -> FiniteAutomaton<S, (), I> {
         let mut symbols = HashSet::new();
         self.collect_symbols(&mut symbols);
-        let default_symbols = Symbols::Exclude(symbols.clone());
+        let default_symbols = Symbols::Exclude(symbols.iter().cloned().collect());
 
-        let mut regexes: HashMap<Self, usize> = HashMap::new();
-        let mut states = Vec::new();
+        // the classes of symbols that have distinct derivatives, used to compute the first
+        // symbols of residuals when merging equivalent states
+        let classes = Symbols::classes(symbols.clone());
 
-        let mut queue = VecDeque::new();
-        fn get_or_insert<S: Alphabet>(
-            regex: Regex<ApproximatelySimilarCanonical<S>>,
-            queue: &mut VecDeque<Regex<ApproximatelySimilarCanonical<S>>>,
-            regexes: &mut HashMap<Regex<ApproximatelySimilarCanonical<S>>, usize>,
-        ) -> usize {
-            if let Some(idx) = regexes.get(&regex) {
-                *idx
-            } else {
-                let idx = regexes.len();
-                regexes.insert(regex.clone(), idx);
-                queue.push_back(regex);
-                idx
+        // the cache identifiers of the residuals of the states, and the states they belong to
+        let mut residuals: Vec<usize> = Vec::new();
+        let mut state_ids: HashMap<usize, usize> = HashMap::new();
+        // the states by nullability and first symbols, which equivalent residuals share
+        let mut buckets: HashMap<(bool, Vec<bool>), Vec<usize>> = HashMap::new();
+        let mut get_or_insert = |residual: usize,
+                                 residuals: &mut Vec<usize>,
+                                 cache: &mut DerivativeCache<S>|
+         -> usize {
+            if let Some(idx) = state_ids.get(&residual) {
+                return *idx;
             }
-        }
+            let idx = residuals.len();
+            if merge_equivalent {
+                let key = (
+                    cache.residual(residual).is_nullable(),
+                    classes
+                        .iter()
+                        .map(|class| {
+                            let next = cache.derive(residual, class);
+                            !matches!(cache.residual(next), Regex::EmptySet)
+                        })
+                        .collect(),
+                );
+                let bucket = buckets.entry(key).or_default();
+                let regex = cache.residual(residual);
+                let equivalent = bucket
+                    .iter()
+                    .copied()
+                    .find(|other| cache.residual(residuals[*other]).is_equivalent_to(regex));
+                if let Some(other) = equivalent {
+                    state_ids.insert(residual, other);
+                    return other;
+                }
+                bucket.push(idx);
+            }
+            residuals.push(residual);
+            state_ids.insert(residual, idx);
+            idx
+        };
 
-        get_or_insert(self.clone(), &mut queue, &mut regexes);
-        while let Some(regex) = queue.pop_front() {
+        let start = cache.insert(self.clone());
+        get_or_insert(start, &mut residuals, cache);
+        let mut states = Vec::new();
+        while let Some(residual) = residuals.get(states.len()).copied() {
+            let regex = cache.residual(residual).clone();
             let accepting = regex.is_nullable();
-            let mut transitions = HashMap::default();
-            for symbol in &symbols {
-                let next = regex.derive_symbols(&Symbols::include([symbol.clone()]));
-                let next_idx = get_or_insert(next, &mut queue, &mut regexes);
-                transitions.insert(symbol.clone(), next_idx);
+            let mut transitions = Vec::with_capacity(symbols.len());
+            for symbol in symbols.iter().sorted() {
+                let next = cache.derive(residual, &Symbols::include([symbol.clone()]));
+                let next_idx = get_or_insert(next, &mut residuals, cache);
+                transitions.push((symbol.clone(), state_id(next_idx)));
             }
+            let transitions = Transitions::new(representation, transitions);
             let default_transition = {
-                let next = regex.derive_symbols(&default_symbols);
-                let next_id = get_or_insert(next, &mut queue, &mut regexes);
-                next_id
+                let next = cache.derive(residual, &default_symbols);
+                state_id(get_or_insert(next, &mut residuals, cache))
             };
             states.push(State {
-                regex,
+                regex: Some(regex),
                 accepting,
+                universal: false,
                 transitions,
                 default_transition,
+                label: (),
             });
         }
 
-        // FIXME compute states that cannot reach accepting states
-
         FiniteAutomaton { states }
+            .mark_dead_states()
+            .mark_universal_states()
     }
+}
 
-    fn collect_symbols(&self, symbols: &mut HashSet<S>) {
+impl<B: Builder> Regex<B> {
+    /// Collects all symbols that occur in this regular expression.
+    pub(crate) fn collect_symbols(&self, symbols: &mut HashSet<B::Symbol>) {
         match self {
             Regex::EmptySet => {}
             Regex::EmptyString => {}
@@ -102,48 +352,180 @@ impl<S: Alphabet> Regex<ApproximatelySimilarCanonical<S>> {
     }
 }
 
-impl<S: Alphabet> FiniteAutomaton<S> {
-    pub fn to_matcher<'a>(&'a self) -> Matcher<'a, S> {
-        Matcher {
-            fa: Cow::Borrowed(self),
-            state: 0,
+impl<S: Alphabet, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Attach user data to the states of this automaton. The labeling function is called
+    /// once for every state, with the residual regular expression of the state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the regular expressions of the states were dropped using
+    /// [`FiniteAutomaton::drop_regexes`], or are not known because the automaton was not built
+    /// from a regular expression. They can be reconstructed using
+    /// [`FiniteAutomaton::reconstruct_regexes`].
+    pub fn label_states<M>(
+        self,
+        mut f: impl FnMut(&Regex<ApproximatelySimilarCanonical<S>>) -> M,
+    ) -> FiniteAutomaton<S, M, I> {
+        FiniteAutomaton {
+            states: self
+                .states
+                .into_iter()
+                .map(|state| State {
+                    label: f(state
+                        .regex
+                        .as_ref()
+                        .expect("regular expression was dropped")),
+                    regex: state.regex,
+                    accepting: state.accepting,
+                    universal: state.universal,
+                    transitions: state.transitions,
+                    default_transition: state.default_transition,
+                })
+                .collect(),
         }
     }
 
-    pub fn into_matcher(self) -> Matcher<'static, S> {
-        Matcher {
-            fa: Cow::Owned(self),
-            state: 0,
+    /// Returns the length of the longest prefix of the given symbols that is accepted by this
+    /// automaton, or `None` if no prefix is.
+    ///
+    /// Symbols are consumed until a state with the empty set as its regular expression is
+    /// reached. Trimming the automaton first ensures that all states that cannot reach an
+    /// accepting state are recognized, so that no more symbols are consumed than necessary.
+    pub fn longest_matching_prefix<T>(&self, symbols: impl IntoIterator<Item = T>) -> Option<usize>
+    where
+        T: Borrow<S>,
+    {
+        let mut current = 0;
+        let mut longest = self.is_accepting(current).then_some(0);
+        for (idx, symbol) in symbols.into_iter().enumerate() {
+            current = self.next(current, symbol.borrow());
+            if self.is_empty_set(current) {
+                break;
+            }
+            if self.is_accepting(current) {
+                longest = Some(idx + 1);
+            }
         }
+        longest
     }
 
-    fn next(&self, current: usize, symbol: &S) -> usize {
+    /// Returns whether any substring of the given symbols is accepted by this automaton.
+    ///
+    /// This runs the automaton from every position in the input at once, without building an
+    /// automaton for the containing regular expression. It stops at the first match.
+    pub fn is_match_anywhere<T>(&self, symbols: impl IntoIterator<Item = T>) -> bool
+    where
+        T: Borrow<S>,
+    {
+        let mut active = vec![0];
+        let mut seen = vec![false; self.states.len()];
+        for symbol in symbols {
+            if active.iter().any(|state| self.is_accepting(*state)) {
+                return true;
+            }
+            let symbol = symbol.borrow();
+            let mut next_active = Vec::with_capacity(active.len() + 1);
+            for state in active.into_iter().chain(std::iter::once(0)) {
+                let next = self.next(state, symbol);
+                if !seen[next] && !self.is_empty_set(next) {
+                    seen[next] = true;
+                    next_active.push(next);
+                }
+            }
+            for state in &next_active {
+                seen[*state] = false;
+            }
+            active = next_active;
+        }
+        active.iter().any(|state| self.is_accepting(*state))
+    }
+
+    /// Returns the number of states of this automaton. States are numbered from zero, and the
+    /// start state is state zero.
+    pub fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Returns the transitions of the given state for symbols that occur in the regular expression.
+    pub(crate) fn symbol_transitions(&self, current: usize) -> impl Iterator<Item = (&S, usize)> {
+        self.states[current]
+            .transitions
+            .iter()
+            .map(|(symbol, next)| (symbol, next.index()))
+    }
+
+    /// Returns the transition of the given state for all other symbols.
+    pub(crate) fn default_transition(&self, current: usize) -> usize {
+        self.states[current].default_transition.index()
+    }
+
+    pub(crate) fn next<Q>(&self, current: usize, symbol: &Q) -> usize
+    where
+        S: Borrow<Q>,
+        Q: Hash + Ord + ?Sized,
+    {
         self.states[current]
             .transitions
             .get(symbol)
-            .cloned()
             .unwrap_or(self.states[current].default_transition)
+            .index()
     }
 
-    fn is_accepting(&self, current: usize) -> bool {
+    /// Returns whether the given state is accepting.
+    pub fn is_accepting(&self, current: usize) -> bool {
         self.states[current].accepting
     }
+
+    /// Returns whether every word is accepted from the given state.
+    pub(crate) fn is_universal(&self, current: usize) -> bool {
+        self.states[current].universal
+    }
+
+    /// Returns whether the residual regular expression of the given state is the empty set.
+    /// Such states are never dropped, so this works for automata without regular expressions.
+    pub(crate) fn is_empty_set(&self, current: usize) -> bool {
+        matches!(self.states[current].regex, Some(Regex::EmptySet))
+    }
 }
 
-pub struct Matcher<'a, S: Alphabet> {
-    fa: Cow<'a, FiniteAutomaton<S>>,
+impl<S: Alphabet, L, I: StateId> FiniteAutomaton<S, L, I> {
+    pub fn to_matcher(&self) -> Matcher<'_, S, L, I> {
+        Matcher {
+            fa: Handle::Borrowed(self),
+            state: 0,
+        }
+    }
+
+    pub fn into_matcher(self) -> Matcher<'static, S, L, I> {
+        Matcher {
+            fa: Handle::Owned(self),
+            state: 0,
+        }
+    }
+}
+
+pub struct Matcher<'a, S: Alphabet, L = (), I: StateId = usize> {
+    fa: Handle<'a, FiniteAutomaton<S, L, I>>,
     state: usize,
 }
 
-impl<'a, S: Alphabet> Matcher<'a, S> {
-    pub fn next(&mut self, symbol: &S) -> bool {
+impl<'a, S: Alphabet, L, I: StateId> Matcher<'a, S, L, I> {
+    /// Consume the given symbol and return whether the matcher is in an accepting state.
+    ///
+    /// The symbol may be any borrowed form of the alphabet, as for [`HashMap::get`]. For
+    /// example, `&str` tokens can be matched against an automaton over `String` symbols.
+    pub fn next<Q>(&mut self, symbol: &Q) -> bool
+    where
+        S: Borrow<Q>,
+        Q: Hash + Ord + ?Sized,
+    {
         self.state = self.fa.next(self.state, symbol);
         self.fa.is_accepting(self.state)
     }
 
-    pub fn next_iter<I>(&mut self, symbols: impl IntoIterator<Item = I>) -> bool
+    pub fn next_iter<T>(&mut self, symbols: impl IntoIterator<Item = T>) -> bool
     where
-        I: Borrow<S>,
+        T: Borrow<S>,
     {
         for symbol in symbols {
             self.next(symbol.borrow());
@@ -151,8 +533,173 @@ impl<'a, S: Alphabet> Matcher<'a, S> {
         self.fa.is_accepting(self.state)
     }
 
+    /// Consume the given borrowed symbols and return whether the matcher is in an accepting
+    /// state.
+    ///
+    /// Unlike [`Matcher::next_iter`], the symbols may be any borrowed form of the alphabet, as
+    /// for [`Matcher::next`]. For example, a stream of `&str` tokens can be matched against an
+    /// automaton over `String` symbols without allocating a `String` for every token.
+    pub fn next_iter_borrowed<'s, Q>(&mut self, symbols: impl IntoIterator<Item = &'s Q>) -> bool
+    where
+        S: Borrow<Q>,
+        Q: Hash + Ord + ?Sized + 's,
+    {
+        for symbol in symbols {
+            self.next(symbol);
+        }
+        self.fa.is_accepting(self.state)
+    }
+
+    /// Consume the given symbols and return whether the matcher is in an accepting state.
+    pub fn feed(&mut self, symbols: &[S]) -> bool {
+        self.next_iter(symbols)
+    }
+
+    /// Consume the given symbols, and return an iterator that yields every symbol together
+    /// with whether the matcher is in an accepting state after consuming it.
+    ///
+    /// Symbols are consumed lazily, as the returned iterator is advanced.
+    pub fn scan<T>(&mut self, symbols: T) -> Scan<'_, 'a, S, L, I, T::IntoIter>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<S>,
+    {
+        Scan {
+            matcher: self,
+            symbols: symbols.into_iter(),
+        }
+    }
+
+    /// Consume the given symbols, and return an iterator that yields a [`Transition`] for
+    /// every symbol, with the states before and after consuming it.
+    ///
+    /// This can be used for logging, coverage measurement, or side effects on state changes.
+    /// Symbols are consumed lazily, as the returned iterator is advanced.
+    pub fn trace<T>(&mut self, symbols: T) -> Trace<'_, 'a, S, L, I, T::IntoIter>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<S>,
+    {
+        Trace {
+            matcher: self,
+            symbols: symbols.into_iter(),
+        }
+    }
+
+    /// Returns the current state. The matcher starts in state zero.
+    pub fn state(&self) -> usize {
+        self.state
+    }
+
+    /// Returns the matcher to the start state, so that it can be reused for other input.
+    ///
+    /// Matchers are cheap to create: a matcher from a [`SharedAutomaton`] only holds a
+    /// reference to the automaton and its current state. Resetting a matcher avoids even that,
+    /// for example for a matcher that is kept per thread or per connection.
+    pub fn reset(&mut self) {
+        self.state = 0;
+    }
+
+    /// Returns whether the matcher accepts every continuation of the symbols consumed so far.
+    ///
+    /// Once this is the case, the matcher stays in accepting states, and consuming more input
+    /// cannot change the result.
+    pub fn will_always_accept(&self) -> bool {
+        self.fa.is_universal(self.state)
+    }
+
+    /// Returns the residual regular expression of the current state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the regular expressions of the states were dropped using
+    /// [`FiniteAutomaton::drop_regexes`], or are not known because the automaton was not built
+    /// from a regular expression. They can be reconstructed using
+    /// [`FiniteAutomaton::reconstruct_regexes`].
     pub fn regex(&self) -> &Regex<ApproximatelySimilarCanonical<S>> {
-        &self.fa.states[self.state].regex
+        self.fa.states[self.state]
+            .regex
+            .as_ref()
+            .expect("regular expression was dropped")
+    }
+
+    /// Returns the user data attached to the current state.
+    pub fn label(&self) -> &L {
+        &self.fa.states[self.state].label
+    }
+}
+
+/// Consumes symbols, as [`Matcher::next_iter`] does.
+impl<S: Alphabet, L, I: StateId, T: Borrow<S>> Extend<T> for Matcher<'_, S, L, I> {
+    fn extend<It: IntoIterator<Item = T>>(&mut self, symbols: It) {
+        self.next_iter(symbols);
+    }
+}
+
+/// Iterator returned by [`Matcher::scan`].
+pub struct Scan<'m, 'a, S: Alphabet, L, I: StateId, T> {
+    matcher: &'m mut Matcher<'a, S, L, I>,
+    symbols: T,
+}
+
+impl<S: Alphabet, L, I: StateId, T> Iterator for Scan<'_, '_, S, L, I, T>
+where
+    T: Iterator,
+    T::Item: Borrow<S>,
+{
+    type Item = (T::Item, bool);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let symbol = self.symbols.next()?;
+        let accepting = self.matcher.next(symbol.borrow());
+        Some((symbol, accepting))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.symbols.size_hint()
+    }
+}
+
+/// A transition taken by a [`Matcher`], yielded by [`Matcher::trace`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Transition<T> {
+    /// The state before the transition.
+    pub from: usize,
+    /// The consumed symbol.
+    pub symbol: T,
+    /// The state after the transition.
+    pub to: usize,
+    /// Whether the state after the transition is accepting.
+    pub accepting: bool,
+}
+
+/// Iterator returned by [`Matcher::trace`].
+pub struct Trace<'m, 'a, S: Alphabet, L, I: StateId, T> {
+    matcher: &'m mut Matcher<'a, S, L, I>,
+    symbols: T,
+}
+
+impl<S: Alphabet, L, I: StateId, T> Iterator for Trace<'_, '_, S, L, I, T>
+where
+    T: Iterator,
+    T::Item: Borrow<S>,
+{
+    type Item = Transition<T::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let symbol = self.symbols.next()?;
+        let from = self.matcher.state;
+        let accepting = self.matcher.next(symbol.borrow());
+        Some(Transition {
+            from,
+            symbol,
+            to: self.matcher.state,
+            accepting,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.symbols.size_hint()
     }
 }
 
@@ -161,9 +708,13 @@ mod tests {
     use itertools::Itertools;
 
     use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Pure;
     use crate::builder::Regex;
+    use crate::builder::StronglyCanonical;
     use crate::ops::*;
 
+    use super::Transition;
+
     #[test]
     fn test_matcher() {
         let tests: Vec<(Regex<ApproximatelySimilarCanonical<usize>>, Vec<_>, bool)> = vec![
@@ -194,6 +745,16 @@ mod tests {
             ((!().r()), vec![11], true),
             ((!11.s()), vec![42], true),
             ((!11.s()), vec![11], false),
+            (Regex::any([11, 42]), vec![42], true),
+            (Regex::any([11, 42]), vec![7], false),
+            (Regex::any([11, 42]), vec![11, 42], false),
+            (Regex::any_star([11, 42]), vec![], true),
+            (Regex::any_star([11, 42]), vec![11, 42, 11], true),
+            (Regex::any_star([11, 42]), vec![11, 7], false),
+            (Regex::complement_within(11.s(), [11, 42]), vec![], true),
+            (Regex::complement_within(11.s(), [11, 42]), vec![42], true),
+            (Regex::complement_within(11.s(), [11, 42]), vec![11], false),
+            (Regex::complement_within(11.s(), [11, 42]), vec![7], false),
         ];
         for test in tests {
             assert_eq!(
@@ -206,4 +767,295 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_labels() {
+        let r: Regex<ApproximatelySimilarCanonical<usize>> = [1.s(), 2.s()].r();
+        let fa = r.to_automaton().label_states(|r| r.to_string());
+        let mut m = fa.to_matcher();
+        assert_eq!("1 2", m.label());
+        m.next(&1);
+        assert_eq!("2", m.label());
+        m.next(&1);
+        assert_eq!("∅", m.label());
+    }
+
+    #[test]
+    fn test_matcher_borrowed_symbols() {
+        let r: Regex<ApproximatelySimilarCanonical<String>> =
+            ["foo".to_string().s(), "bar".to_string().s().c()].r();
+        let fa = r.to_automaton();
+        let mut m = fa.to_matcher();
+        assert!(m.next("foo"));
+        assert!(m.next("bar"));
+        assert!(!m.next("baz"));
+
+        let tokens = "foo bar bar".split(' ');
+        assert!(fa.to_matcher().next_iter_borrowed(tokens.clone()));
+        assert!(!fa.to_matcher().next_iter_borrowed(tokens.chain(["baz"])));
+        assert!(fa
+            .to_dense()
+            .to_matcher()
+            .next_iter_borrowed(["foo", "bar"]));
+
+        let r: Regex<ApproximatelySimilarCanonical<Vec<u8>>> = b"foo".to_vec().s();
+        let fa = r.to_automaton();
+        assert!(fa.to_matcher().next_iter_borrowed([b"foo".as_slice()]));
+    }
+
+    #[test]
+    fn test_automaton_merging_equivalent() {
+        let r: Regex<ApproximatelySimilarCanonical<usize>> =
+            !(1.s().c() + 2.s().c()) & !(2.s().c() & 1.s()).c();
+        let fa = r.to_automaton();
+        let merged = r.to_automaton_merging_equivalent();
+        assert!(merged.state_count() < fa.state_count());
+        assert_eq!(fa.clone().minimize().state_count(), merged.state_count());
+        for word in [vec![], vec![1], vec![2, 1], vec![1, 2, 1], vec![3]] {
+            assert_eq!(
+                fa.to_matcher().next_iter(&word),
+                merged.to_matcher().next_iter(&word)
+            );
+        }
+    }
+
+    #[test]
+    fn test_nerode_index() {
+        let tests: Vec<(Regex<ApproximatelySimilarCanonical<usize>>, usize)> = vec![
+            (().r(), 1),
+            (!().r(), 1),
+            ([].r(), 2),
+            (1.s(), 3),
+            ((1.s() | 2.s()).c(), 2),
+            ([1.s().c(), 1.s().c()].r(), 2),
+            (!(1.s().c() + 2.s().c()) & !(2.s().c() & 1.s()).c(), 3),
+        ];
+        for (regex, expected) in tests {
+            assert_eq!(expected, regex.nerode_index(), "{}", regex);
+            assert!(regex.nerode_index() <= regex.to_automaton().state_count());
+        }
+    }
+
+    #[test]
+    fn test_automaton_from_other_builders() {
+        let pure: Regex<Pure<usize>> = [1.s(), (2.s() | 3.s()).c()].r() & !([].r() + 1.s());
+        let strong: Regex<StronglyCanonical<usize>> = pure.rebuild();
+        let asc: Regex<ApproximatelySimilarCanonical<usize>> = pure.rebuild();
+        let expected = asc.to_automaton();
+        assert_eq!(expected.state_count(), pure.to_automaton().state_count());
+        for word in [vec![], vec![1], vec![1, 2], vec![1, 3, 2], vec![2], vec![4]] {
+            let is_match = expected.to_matcher().next_iter(&word);
+            assert_eq!(is_match, pure.to_automaton().to_matcher().next_iter(&word));
+            assert_eq!(
+                is_match,
+                strong.to_automaton().to_matcher().next_iter(&word)
+            );
+        }
+    }
+
+    #[test]
+    fn test_compact_state_ids() {
+        let r: Regex<ApproximatelySimilarCanonical<usize>> =
+            [1.s(), (2.s() | 3.s().c()), 4.s()].r() & !(2.s() | 3.s()).c();
+        let fa = r.to_automaton();
+        let compact = r.to_automaton_with_state_ids::<u16>();
+        assert_eq!(fa.state_count(), compact.state_count());
+        let minimized = compact.clone().minimize().trim();
+        for word in [
+            vec![],
+            vec![1, 4],
+            vec![1, 2, 4],
+            vec![1, 3, 3, 4],
+            vec![1, 3],
+            vec![5],
+        ] {
+            let expected = fa.to_matcher().next_iter(&word);
+            assert_eq!(expected, compact.to_matcher().next_iter(&word));
+            assert_eq!(expected, minimized.to_matcher().next_iter(&word));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit in state identifier type u8")]
+    fn test_compact_state_ids_overflow() {
+        let r: Regex<ApproximatelySimilarCanonical<usize>> =
+            (0..300).map(|_| 1.s()).reduce(Regex::concat).unwrap();
+        r.to_automaton_with_state_ids::<u8>();
+    }
+
+    #[test]
+    fn test_scan() {
+        let r: Regex<ApproximatelySimilarCanonical<usize>> = [1.s(), 2.s()].r().c();
+        let fa = r.to_automaton();
+        let mut m = fa.to_matcher();
+        let verdicts = m.scan([1, 2, 1, 2, 1]).collect_vec();
+        assert_eq!(
+            vec![(1, false), (2, true), (1, false), (2, true), (1, false)],
+            verdicts
+        );
+        let last_accepting = fa
+            .to_matcher()
+            .scan(&[1, 2, 1, 2, 1])
+            .positions(|(_, accepting)| accepting)
+            .last();
+        assert_eq!(Some(3), last_accepting);
+    }
+
+    #[test]
+    fn test_trace() {
+        let r: Regex<ApproximatelySimilarCanonical<usize>> = [1.s(), 2.s()].r().c();
+        let fa = r.to_automaton();
+        let mut m = fa.to_matcher();
+        let transitions = m.trace([1, 2, 1]).collect_vec();
+        let expected = vec![
+            Transition {
+                from: 0,
+                symbol: 1,
+                to: 1,
+                accepting: false,
+            },
+            Transition {
+                from: 1,
+                symbol: 2,
+                to: 0,
+                accepting: true,
+            },
+            Transition {
+                from: 0,
+                symbol: 1,
+                to: 1,
+                accepting: false,
+            },
+        ];
+        assert_eq!(expected, transitions);
+        assert_eq!(1, m.state());
+
+        let mut visited = vec![false; fa.state_count()];
+        for transition in fa.to_matcher().trace(&[1, 1]) {
+            visited[transition.to] = true;
+        }
+        assert_eq!(vec![false, true, true], visited);
+    }
+
+    #[test]
+    fn test_extend() {
+        let r: Regex<ApproximatelySimilarCanonical<usize>> = [1.s(), 2.s(), 3.s()].r();
+        let fa = r.to_automaton();
+        let mut m = fa.to_matcher();
+        assert!(!m.feed(&[1]));
+        m.extend([2]);
+        m.extend(&[3]);
+        assert_eq!("ε", m.regex().to_string());
+        assert!(m.feed(&[]));
+        assert!(!m.feed(&[4]));
+    }
+
+    #[test]
+    fn test_reset() {
+        let r: Regex<ApproximatelySimilarCanonical<usize>> = [1.s(), 2.s()].r();
+        let shared = r.to_automaton().into_shared();
+        let mut m = shared.matcher();
+        assert!(m.next_iter([1, 2]));
+        assert!(!m.next(&1));
+        m.reset();
+        assert_eq!(0, m.state());
+        assert!(m.next_iter([1, 2]));
+    }
+
+    #[test]
+    fn test_longest_matching_prefix() {
+        type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+        let tests: Vec<(AscRegex, Vec<_>, Option<usize>)> = vec![
+            (().r(), vec![1], None),
+            ([].r(), vec![1], Some(0)),
+            (1.s().c(), vec![1, 1, 2, 1], Some(2)),
+            ([1.s(), 2.s()].r().c(), vec![1, 2, 1, 2, 1], Some(4)),
+            ([1.s(), 2.s(), 3.s()].r() | 1.s(), vec![1, 2, 4], Some(1)),
+            ((1.s() & 2.s()) | [].r(), vec![1, 2], Some(0)),
+        ];
+        for (regex, word, expected) in tests {
+            let fa = regex.to_automaton();
+            assert_eq!(expected, fa.longest_matching_prefix(&word), "{}", regex);
+            let fa = fa.trim();
+            assert_eq!(expected, fa.longest_matching_prefix(&word), "{}", regex);
+        }
+    }
+
+    #[test]
+    fn test_dead_states() {
+        let regex: Regex<ApproximatelySimilarCanonical<usize>> =
+            [1.s(), 2.s()].r() & [1.s(), 3.s()].r();
+        assert!(!matches!(regex.derive(&1), Regex::EmptySet));
+        let fa = regex.to_automaton();
+        let mut m = fa.to_matcher();
+        assert!(!m.next(&1));
+        assert_eq!(&Regex::EmptySet, m.regex());
+        assert_eq!(None, fa.longest_matching_prefix([1, 2].iter()));
+    }
+
+    #[test]
+    fn test_will_always_accept() {
+        let tests: Vec<(
+            Regex<ApproximatelySimilarCanonical<usize>>,
+            Vec<usize>,
+            bool,
+        )> = vec![
+            (().r(), vec![], false),
+            (!().r(), vec![], true),
+            ([1.s(), !().r()].r(), vec![], false),
+            ([1.s(), !().r()].r(), vec![1], true),
+            ([1.s(), !().r()].r(), vec![2, 1], false),
+            ([1.s(), !2.s() | 2.s()].r(), vec![1], true),
+            ((1.s() | 2.s()).c(), vec![1], false),
+            (!1.s(), vec![1], false),
+            (!1.s(), vec![1, 2], true),
+        ];
+        let everything: Regex<ApproximatelySimilarCanonical<usize>> = !().r();
+        for (regex, word, expected) in tests {
+            let fas = vec![
+                regex.to_automaton(),
+                regex.to_automaton().drop_regexes(),
+                regex.to_automaton().trim().minimize().into_sparse(),
+                !!regex.to_automaton(),
+                regex.to_automaton() & everything.to_automaton(),
+            ];
+            for fa in fas {
+                let mut matcher = fa.to_matcher();
+                matcher.next_iter(&word);
+                assert_eq!(
+                    expected,
+                    matcher.will_always_accept(),
+                    "{} after {:?}",
+                    regex,
+                    word
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_match_anywhere() {
+        type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+        let tests: Vec<(AscRegex, Vec<_>, bool)> = vec![
+            ([1.s(), 2.s()].r(), vec![3, 1, 2, 3], true),
+            ([1.s(), 2.s()].r(), vec![1, 1, 2], true),
+            ([1.s(), 2.s()].r(), vec![1, 3, 2], false),
+            ([1.s(), 2.s()].r(), vec![], false),
+            ([].r(), vec![], true),
+            (!1.s() & 2.s().c(), vec![1, 1], true),
+            ([1.s(), !3.s(), 2.s()].r(), vec![4, 1, 3, 2], false),
+            ([1.s(), !3.s(), 2.s()].r(), vec![1, 4, 1, 4, 2], true),
+        ];
+        for (regex, word, expected) in tests {
+            let fa = regex.to_automaton();
+            assert_eq!(expected, fa.is_match_anywhere(&word), "{}", regex);
+            let contains = Regex::contains(regex.clone(), [1, 2, 3, 4]).to_automaton();
+            assert_eq!(
+                expected,
+                contains.to_matcher().next_iter(&word),
+                "{}",
+                regex
+            );
+        }
+    }
 }
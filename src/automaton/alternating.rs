@@ -0,0 +1,237 @@
+//! Alternating finite automata, which keep the boolean structure of regular expressions
+//! implicit.
+
+use std::borrow::Borrow;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+
+use crate::builder::ApproximatelySimilarCanonical;
+use crate::builder::Builder;
+use crate::builder::Regex;
+use crate::Alphabet;
+
+type Residual<S> = Regex<ApproximatelySimilarCanonical<S>>;
+
+/// An alternating finite automaton, returned by [`Regex::to_alternating_automaton`].
+///
+/// The states are residual regular expressions without intersection, union, or complement at
+/// the top. The transitions of a state lead to a boolean combination of states, so that
+/// intersections of many constraints do not multiply the number of states, as they do for
+/// [`Regex::to_automaton`]. States and transitions are built on the fly while matching, and
+/// are kept for later matches.
+#[derive(Clone, Debug)]
+pub struct AlternatingAutomaton<S: Alphabet> {
+    states: Vec<AlternatingState<S>>,
+    ids: HashMap<Residual<S>, usize>,
+    start: Formula,
+}
+
+#[derive(Clone, Debug)]
+struct AlternatingState<S: Alphabet> {
+    regex: Residual<S>,
+    nullable: bool,
+    transitions: HashMap<S, Formula>,
+}
+
+/// A boolean combination of states.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+enum Formula {
+    Constant(bool),
+    State(usize),
+    Not(Box<Formula>),
+    And(BTreeSet<Formula>),
+    Or(BTreeSet<Formula>),
+}
+
+impl Formula {
+    fn not(inner: Self) -> Self {
+        match inner {
+            Self::Constant(value) => Self::Constant(!value),
+            Self::Not(inner) => *inner,
+            inner => Self::Not(Box::new(inner)),
+        }
+    }
+
+    fn and(left: Self, right: Self) -> Self {
+        Self::combine(left, right, false)
+    }
+
+    fn or(left: Self, right: Self) -> Self {
+        Self::combine(left, right, true)
+    }
+
+    /// Returns the conjunction or disjunction of the operands, where `absorbing` is the value
+    /// that decides the result on its own.
+    fn combine(left: Self, right: Self, absorbing: bool) -> Self {
+        let mut operands = BTreeSet::new();
+        for operand in [left, right] {
+            match operand {
+                Self::Constant(value) if value == absorbing => return operand,
+                Self::Constant(_) => {}
+                Self::And(inner) if !absorbing => operands.extend(inner),
+                Self::Or(inner) if absorbing => operands.extend(inner),
+                operand => {
+                    operands.insert(operand);
+                }
+            }
+        }
+        match operands.len() {
+            0 => Self::Constant(!absorbing),
+            1 => operands.into_iter().next().expect("one operand"),
+            _ if absorbing => Self::Or(operands),
+            _ => Self::And(operands),
+        }
+    }
+
+    /// Replaces every state with the formula given for it.
+    fn substitute(&self, f: &mut impl FnMut(usize) -> Self) -> Self {
+        match self {
+            Self::Constant(value) => Self::Constant(*value),
+            Self::State(state) => f(*state),
+            Self::Not(inner) => Self::not(inner.substitute(f)),
+            Self::And(operands) => operands.iter().fold(Self::Constant(true), |acc, operand| {
+                Self::and(acc, operand.substitute(f))
+            }),
+            Self::Or(operands) => operands.iter().fold(Self::Constant(false), |acc, operand| {
+                Self::or(acc, operand.substitute(f))
+            }),
+        }
+    }
+
+    /// Returns the value of this formula, given the values of the states.
+    fn evaluate(&self, f: &impl Fn(usize) -> bool) -> bool {
+        match self {
+            Self::Constant(value) => *value,
+            Self::State(state) => f(*state),
+            Self::Not(inner) => !inner.evaluate(f),
+            Self::And(operands) => operands.iter().all(|operand| operand.evaluate(f)),
+            Self::Or(operands) => operands.iter().any(|operand| operand.evaluate(f)),
+        }
+    }
+}
+
+impl<B: Builder> Regex<B> {
+    /// Build an alternating finite automaton for this regular expression.
+    ///
+    /// The regular expression is first rebuilt with the [`ApproximatelySimilarCanonical`]
+    /// builder, as for [`Regex::to_automaton`].
+    pub fn to_alternating_automaton(&self) -> AlternatingAutomaton<B::Symbol> {
+        let mut automaton = AlternatingAutomaton {
+            states: Vec::new(),
+            ids: HashMap::new(),
+            start: Formula::Constant(false),
+        };
+        automaton.start = automaton.formula(&self.rebuild());
+        automaton
+    }
+}
+
+impl<S: Alphabet> AlternatingAutomaton<S> {
+    /// Returns whether the given symbols are accepted by this automaton. States and
+    /// transitions that are needed and do not exist yet are added.
+    pub fn is_match<T>(&mut self, symbols: impl IntoIterator<Item = T>) -> bool
+    where
+        T: Borrow<S>,
+    {
+        let mut current = self.start.clone();
+        for symbol in symbols {
+            if let Formula::Constant(value) = current {
+                return value;
+            }
+            current = current.substitute(&mut |state| self.transition(state, symbol.borrow()));
+        }
+        current.evaluate(&|state| self.states[state].nullable)
+    }
+
+    /// Returns the number of states that have been built so far.
+    pub fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Returns the formula for the derivative of the given state w.r.t. the given symbol.
+    fn transition(&mut self, state: usize, symbol: &S) -> Formula {
+        if let Some(formula) = self.states[state].transitions.get(symbol) {
+            return formula.clone();
+        }
+        let derivative = self.states[state].regex.derive(symbol);
+        let formula = self.formula(&derivative);
+        self.states[state]
+            .transitions
+            .insert(symbol.clone(), formula.clone());
+        formula
+    }
+
+    /// Returns the formula for the given regular expression, adding states for the operands
+    /// of its boolean structure.
+    fn formula(&mut self, regex: &Residual<S>) -> Formula {
+        match regex {
+            Regex::EmptySet => Formula::Constant(false),
+            Regex::Or(left, right) => Formula::or(self.formula(left), self.formula(right)),
+            Regex::And(left, right) => Formula::and(self.formula(left), self.formula(right)),
+            Regex::Complement(inner) => Formula::not(self.formula(inner)),
+            regex => {
+                let len = self.states.len();
+                let state = *self.ids.entry(regex.clone()).or_insert(len);
+                if state == len {
+                    self.states.push(AlternatingState {
+                        regex: regex.clone(),
+                        nullable: regex.is_nullable(),
+                        transitions: HashMap::new(),
+                    });
+                }
+                Formula::State(state)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::builder::Pure;
+    use crate::ops::*;
+
+    use super::*;
+
+    type PureRegex = Regex<Pure<usize>>;
+
+    #[test]
+    fn test_alternating_automaton() {
+        let tests: Vec<PureRegex> = vec![
+            ().r(),
+            !().r(),
+            [1.s(), 2.s().c()].r(),
+            !(1.s().c()) & (1.s() | 2.s()).c(),
+            [!([1.s(), 2.s()].r()), 1.s()].r() | ([].r() & 3.s()),
+        ];
+        let words = (1..=4)
+            .flat_map(|len| std::iter::repeat_n([1, 2, 3], len).multi_cartesian_product())
+            .chain([vec![]])
+            .collect_vec();
+        for regex in tests {
+            let mut afa = regex.to_alternating_automaton();
+            for word in &words {
+                assert_eq!(
+                    regex.is_match(word),
+                    afa.is_match(word),
+                    "{} on {:?}",
+                    regex,
+                    word
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_alternating_automaton_intersections() {
+        let contains = |symbol: usize| [!().r(), symbol.s(), !().r()].r();
+        let regex: PureRegex = Regex::and_all((1..=6).map(contains));
+        let mut afa = regex.to_alternating_automaton();
+        assert!(afa.is_match([6, 5, 4, 3, 2, 1]));
+        assert!(afa.is_match([1, 1, 2, 3, 4, 5, 6, 7]));
+        assert!(!afa.is_match([1, 2, 3, 4, 5, 5]));
+        assert!(afa.state_count() <= 12);
+        assert!(regex.to_automaton().state_count() >= 64);
+    }
+}
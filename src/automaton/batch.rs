@@ -0,0 +1,73 @@
+//! Match many inputs against a finite automaton.
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::StateId;
+use crate::Alphabet;
+
+impl<S: Alphabet, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Returns for every input whether it matches this automaton.
+    ///
+    /// A single matcher is reused for all inputs, so there is no setup cost per input.
+    pub fn match_all<W: AsRef<[S]>>(&self, inputs: impl IntoIterator<Item = W>) -> Vec<bool> {
+        let mut matcher = self.to_matcher();
+        inputs
+            .into_iter()
+            .map(|input| {
+                matcher.state = 0;
+                matcher.next_iter(input.as_ref())
+            })
+            .collect()
+    }
+
+    /// Returns for every input whether it matches this automaton, matching the inputs in
+    /// parallel using `rayon`. Every worker thread reuses a single matcher. Requires the
+    /// `rayon` feature.
+    #[cfg(feature = "rayon")]
+    pub fn match_all_parallel<W: AsRef<[S]> + Sync>(&self, inputs: &[W]) -> Vec<bool>
+    where
+        S: Sync,
+        L: Sync,
+    {
+        inputs
+            .par_iter()
+            .map_init(
+                || self.to_matcher(),
+                |matcher, input| {
+                    matcher.state = 0;
+                    matcher.next_iter(input.as_ref())
+                },
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    #[test]
+    fn test_match_all() {
+        let r: Regex<ApproximatelySimilarCanonical<usize>> = [1.s(), (2.s() | 3.s()).c()].r();
+        let fa = r.to_automaton();
+        let inputs: Vec<Vec<usize>> = (0..1000)
+            .map(|idx| (0..idx % 7).map(|len| 1 + (idx + len) % 4).collect())
+            .collect();
+        let expected = inputs
+            .iter()
+            .map(|input| fa.to_matcher().next_iter(input))
+            .collect::<Vec<_>>();
+        assert!(expected.iter().any(|matched| *matched));
+        assert!(expected.iter().any(|matched| !*matched));
+        assert_eq!(expected, fa.match_all(&inputs));
+        #[cfg(feature = "rayon")]
+        {
+            assert_eq!(expected, fa.match_all_parallel(&inputs));
+            assert_eq!(Vec::<bool>::new(), fa.match_all_parallel::<Vec<usize>>(&[]));
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Search with small finite automata, keeping the set of active states in a machine word.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::StateId;
+use crate::Alphabet;
+
+/// The maximum number of states of a [`BitParallelAutomaton`].
+const MAX_STATES: usize = u64::BITS as usize;
+
+/// A finite automaton with at most 64 states, for finding matches anywhere in the input,
+/// returned by [`FiniteAutomaton::to_bit_parallel`].
+///
+/// Like the Shift-Or algorithm for strings, the runs of the automaton from every position are
+/// tracked together as a bitset of active states in a single `u64`. A step combines the target
+/// bits of the active states for the symbol, so matching does not allocate, and states that are
+/// reached from several positions are merged for free.
+#[derive(Clone, Debug)]
+pub struct BitParallelAutomaton<S: Alphabet> {
+    /// The class of every symbol with explicit transitions. Other symbols belong to class `0`.
+    classes: HashMap<S, usize>,
+    state_count: usize,
+    /// A row per class, with for every state the bit of its target state.
+    table: Vec<u64>,
+    accepting: u64,
+    /// The states from which an accepting state can be reached.
+    live: u64,
+}
+
+impl<S: Alphabet, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Returns an equivalent automaton that tracks its active states in a single machine word,
+    /// or `None` if this automaton has more than 64 states.
+    ///
+    /// Minimizing the automaton first makes it more likely to fit.
+    pub fn to_bit_parallel(&self) -> Option<BitParallelAutomaton<S>> {
+        let state_count = self.state_count();
+        if state_count > MAX_STATES {
+            return None;
+        }
+        let bit = |state: usize| 1u64 << state;
+        let symbols = self.symbols();
+        let mut table = (0..state_count)
+            .map(|state| bit(self.default_transition(state)))
+            .collect::<Vec<_>>();
+        let mut classes = HashMap::new();
+        for (class, symbol) in symbols.into_iter().enumerate() {
+            table.extend((0..state_count).map(|state| bit(self.next(state, symbol))));
+            classes.insert(symbol.clone(), class + 1);
+        }
+        let (mut accepting, mut live) = (0, 0);
+        for (state, is_live) in self.live_states().into_iter().enumerate() {
+            if self.is_accepting(state) {
+                accepting |= bit(state);
+            }
+            if is_live {
+                live |= bit(state);
+            }
+        }
+        Some(BitParallelAutomaton {
+            classes,
+            state_count,
+            table,
+            accepting,
+            live,
+        })
+    }
+}
+
+impl<S: Alphabet> BitParallelAutomaton<S> {
+    /// Returns whether any substring of the given symbols is accepted by this automaton, as
+    /// [`FiniteAutomaton::is_match_anywhere`] does. It stops at the first match.
+    pub fn is_match_anywhere<T>(&self, symbols: impl IntoIterator<Item = T>) -> bool
+    where
+        T: Borrow<S>,
+    {
+        let start = 1 & self.live;
+        let mut active = start;
+        if active & self.accepting != 0 {
+            return true;
+        }
+        for symbol in symbols {
+            active = self.next(active | start, symbol.borrow());
+            if active & self.accepting != 0 {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Returns the number of states.
+    pub fn state_count(&self) -> usize {
+        self.state_count
+    }
+
+    /// Returns the live states reached from the given states with the given symbol.
+    #[inline]
+    fn next<Q>(&self, mut active: u64, symbol: &Q) -> u64
+    where
+        S: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let class = self.classes.get(symbol).copied().unwrap_or(0);
+        let row = &self.table[class * self.state_count..(class + 1) * self.state_count];
+        let mut next = 0;
+        while active != 0 {
+            next |= row[active.trailing_zeros() as usize];
+            active &= active - 1;
+        }
+        next & self.live
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<u8>>;
+
+    #[test]
+    fn test_bit_parallel_match_anywhere() {
+        let tests: Vec<(AscRegex, &[u8])> = vec![
+            ([b'a'.s(), b'b'.s()].r(), b"xxaxxabx"),
+            ([b'a'.s(), b'b'.s()].r(), b"xxaxxaxb"),
+            ([b'a'.s(), b'a'.s(), b'b'.s()].r(), b"aaab"),
+            ([b'a'.s(), b'a'.s(), b'b'.s()].r(), b"abaab"),
+            ([b'a'.s(), b'a'.s(), b'b'.s()].r(), b"ababa"),
+            ([(b'a'.s() | b'b'.s()).c(), b'c'.s()].r(), b"xxxxbc"),
+            ([b'a'.s(), !b'b'.s()].r(), b"xaxax"),
+            ([b'a'.s(), !b'b'.s()].r(), b"xxxxxx"),
+            (!b'a'.s() & [b'x'.s(), !().r()].r(), b"a"),
+            (!b'a'.s() & [b'x'.s(), !().r()].r(), b"ax"),
+            ([].r(), b""),
+            (().r(), b"abc"),
+        ];
+        for (regex, haystack) in tests {
+            let fa = regex.to_automaton();
+            let bit_parallel = fa.to_bit_parallel().expect("small automaton");
+            assert_eq!(fa.state_count(), bit_parallel.state_count());
+            assert_eq!(
+                fa.is_match_anywhere(haystack),
+                bit_parallel.is_match_anywhere(haystack),
+                "{} on {:?}",
+                regex,
+                String::from_utf8_lossy(haystack)
+            );
+        }
+    }
+
+    #[test]
+    fn test_bit_parallel_too_large() {
+        let regex: AscRegex = Regex::concat_all(std::iter::repeat_n(b'a'.s(), 64));
+        let fa = regex.to_automaton();
+        assert!(fa.state_count() > 64);
+        assert!(fa.to_bit_parallel().is_none());
+        let regex: AscRegex = Regex::concat_all(std::iter::repeat_n(b'a'.s(), 62));
+        assert!(regex.to_automaton().to_bit_parallel().is_some());
+    }
+}
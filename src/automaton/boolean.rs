@@ -0,0 +1,309 @@
+//! Boolean operations on finite automata, such as union, intersection, and complement.
+
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::automaton::state_id;
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::State;
+use crate::automaton::StateId;
+use crate::automaton::Transitions;
+use crate::builder::ApproximatelySimilarCanonical;
+use crate::builder::Builder;
+use crate::builder::Regex;
+use crate::Alphabet;
+
+type Asc<S> = ApproximatelySimilarCanonical<S>;
+type Residual<S> = Regex<Asc<S>>;
+
+impl<S: Alphabet, I: StateId> FiniteAutomaton<S, (), I> {
+    /// Returns the product of this automaton and the other automaton, whose states are the
+    /// pairs of states reachable from the pair of start states. The accepting flags, residual
+    /// regular expressions, and dead states of the pairs are combined using the given
+    /// functions.
+    fn product(
+        &self,
+        other: &Self,
+        accepting: fn(bool, bool) -> bool,
+        regex: fn(Residual<S>, Residual<S>) -> Residual<S>,
+        empty_set: fn(bool, bool) -> bool,
+    ) -> Self {
+        let symbols = self
+            .symbols()
+            .into_iter()
+            .merge(other.symbols())
+            .dedup()
+            .collect_vec();
+
+        let mut ids = HashMap::from([((0, 0), 0)]);
+        let mut pairs = vec![(0, 0)];
+        let mut get_or_insert = |pair, pairs: &mut Vec<(usize, usize)>| -> I {
+            state_id(*ids.entry(pair).or_insert_with(|| {
+                pairs.push(pair);
+                pairs.len() - 1
+            }))
+        };
+
+        let mut states = Vec::new();
+        while let Some((left, right)) = pairs.get(states.len()).copied() {
+            let transitions = symbols
+                .iter()
+                .map(|symbol| {
+                    let next = (self.next(left, *symbol), other.next(right, *symbol));
+                    ((*symbol).clone(), get_or_insert(next, &mut pairs))
+                })
+                .collect();
+            let default_transition = get_or_insert(
+                (
+                    self.default_transition(left),
+                    other.default_transition(right),
+                ),
+                &mut pairs,
+            );
+            let regex = match (&self.states[left].regex, &other.states[right].regex) {
+                (Some(left), Some(right)) => Some(regex(left.clone(), right.clone())),
+                _ if empty_set(self.is_empty_set(left), other.is_empty_set(right)) => {
+                    Some(Regex::EmptySet)
+                }
+                _ => None,
+            };
+            states.push(State {
+                regex,
+                accepting: accepting(self.is_accepting(left), other.is_accepting(right)),
+                universal: false,
+                transitions: Transitions::Hashed(transitions),
+                default_transition,
+                label: (),
+            });
+        }
+        FiniteAutomaton { states }.mark_universal_states()
+    }
+
+    /// Returns an automaton that accepts the words accepted by this automaton but not by the
+    /// other automaton.
+    ///
+    /// Together with [`FiniteAutomaton::cheapest_word`], this finds words that are accepted by
+    /// one compiled automaton and not by another.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the product has more states than can be identified by `I`.
+    pub fn difference(&self, other: &Self) -> Self {
+        self.product(
+            other,
+            |left, right| left && !right,
+            |left, right| Asc::and(left, Asc::complement(right)),
+            |left, _| left,
+        )
+    }
+
+    /// Returns an automaton that accepts the words accepted by exactly one of this automaton
+    /// and the other automaton. It accepts no words if and only if the automata are
+    /// equivalent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the product has more states than can be identified by `I`.
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self.product(
+            other,
+            |left, right| left != right,
+            |left, right| {
+                Asc::or(
+                    Asc::and(left.clone(), Asc::complement(right.clone())),
+                    Asc::and(Asc::complement(left), right),
+                )
+            },
+            |left, right| left && right,
+        )
+    }
+}
+
+/// The union of two automata, which accepts the words accepted by either of them.
+///
+/// # Panics
+///
+/// Panics if the product has more states than can be identified by `I`.
+impl<S: Alphabet, I: StateId> std::ops::BitOr for FiniteAutomaton<S, (), I> {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.product(
+            &rhs,
+            |left, right| left || right,
+            Asc::or,
+            |left, right| left && right,
+        )
+    }
+}
+
+/// The intersection of two automata, which accepts the words accepted by both of them.
+///
+/// # Panics
+///
+/// Panics if the product has more states than can be identified by `I`.
+impl<S: Alphabet, I: StateId> std::ops::BitAnd for FiniteAutomaton<S, (), I> {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.product(
+            &rhs,
+            |left, right| left && right,
+            Asc::and,
+            |left, right| left || right,
+        )
+    }
+}
+
+/// The complement of an automaton, which accepts the words it does not accept.
+impl<S: Alphabet, I: StateId> std::ops::Not for FiniteAutomaton<S, (), I> {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        FiniteAutomaton {
+            states: self
+                .states
+                .into_iter()
+                .map(|state| State {
+                    regex: state.regex.map(Asc::complement),
+                    accepting: !state.accepting,
+                    ..state
+                })
+                .collect(),
+        }
+        .mark_universal_states()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ops::*;
+
+    use super::*;
+
+    type AscRegex = Regex<Asc<usize>>;
+    type Word = Option<Vec<usize>>;
+
+    #[test]
+    fn test_boolean_operators() {
+        let tests: Vec<(AscRegex, AscRegex)> = vec![
+            (().r(), [].r()),
+            (1.s(), 2.s()),
+            ([1.s(), 2.s().c()].r(), [1.s().c(), 2.s()].r()),
+            ((1.s() | 2.s()).c(), !3.s()),
+            (!().r(), [1.s(), 1.s()].r()),
+        ];
+        let words = vec![
+            vec![],
+            vec![1],
+            vec![2],
+            vec![3],
+            vec![1, 2],
+            vec![1, 1],
+            vec![1, 1, 2],
+            vec![2, 2],
+            vec![4, 1],
+        ];
+        for (left, right) in tests {
+            let fas = vec![
+                (
+                    left.clone() | right.clone(),
+                    left.to_automaton() | right.to_automaton(),
+                ),
+                (
+                    left.clone() & right.clone(),
+                    left.to_automaton() & right.to_automaton(),
+                ),
+                (!left.clone(), !left.to_automaton()),
+                (
+                    left.clone() & !right.clone(),
+                    left.to_automaton().drop_regexes() & !right.to_automaton().drop_regexes(),
+                ),
+            ];
+            for (regex, fa) in fas {
+                for word in &words {
+                    assert_eq!(
+                        regex.is_match(word),
+                        fa.to_matcher().next_iter(word),
+                        "{} on {:?}",
+                        regex,
+                        word
+                    );
+                }
+                assert_eq!(
+                    regex.to_automaton().minimize().state_count(),
+                    fa.minimize().state_count(),
+                    "{}",
+                    regex
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_difference() {
+        let tests: Vec<(AscRegex, AscRegex, Word, Word)> = vec![
+            (().r(), ().r(), None, None),
+            (1.s(), 1.s(), None, None),
+            (1.s().c(), [].r(), Some(vec![1]), Some(vec![1])),
+            ([].r(), 1.s().c(), None, Some(vec![1])),
+            (
+                [1.s(), 2.s().c()].r(),
+                [1.s().c(), 2.s()].r(),
+                Some(vec![1]),
+                Some(vec![1]),
+            ),
+            (
+                (1.s() | 2.s()).c(),
+                [1.s().c(), 2.s().c()].r(),
+                Some(vec![2, 1]),
+                Some(vec![2, 1]),
+            ),
+        ];
+        for (left, right, difference, symmetric_difference) in tests {
+            let left_fa = left.to_automaton();
+            let right_fa = right.to_automaton();
+            let fa = left_fa.difference(&right_fa);
+            assert_eq!(difference, fa.cheapest_word(|_| 1), "{} - {}", left, right);
+            assert_eq!(
+                (left.clone() & !right.clone())
+                    .to_automaton()
+                    .minimize()
+                    .state_count(),
+                fa.minimize().state_count(),
+                "{} - {}",
+                left,
+                right
+            );
+            let fa = left_fa.symmetric_difference(&right_fa);
+            assert_eq!(
+                symmetric_difference,
+                fa.cheapest_word(|_| 1),
+                "{} ^ {}",
+                left,
+                right
+            );
+            let fa = left_fa
+                .drop_regexes()
+                .symmetric_difference(&right_fa.drop_regexes());
+            assert_eq!(
+                symmetric_difference,
+                fa.cheapest_word(|_| 1),
+                "{} ^ {}",
+                left,
+                right
+            );
+        }
+    }
+
+    #[test]
+    fn test_boolean_operators_regexes() {
+        let left: AscRegex = [1.s(), 2.s()].r();
+        let right: AscRegex = 1.s().c();
+        let fa = left.to_automaton() | right.to_automaton();
+        assert_eq!(&(left.clone() | right.clone()), fa.to_matcher().regex());
+        let fa = !left.to_automaton();
+        assert_eq!(&!left, fa.to_matcher().regex());
+    }
+}
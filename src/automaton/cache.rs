@@ -0,0 +1,302 @@
+//! Share derivatives between the automata of several regular expressions.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+
+use crate::automaton::transitions::Representation;
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::Prehashed;
+use crate::builder::ApproximatelySimilarCanonical;
+use crate::builder::Builder;
+use crate::builder::Regex;
+use crate::derivation::Symbols;
+use crate::Alphabet;
+
+type Residual<S> = Regex<ApproximatelySimilarCanonical<S>>;
+
+/// A cache of residual regular expressions and their derivatives, which can be shared between
+/// the automata built with [`Regex::to_automaton_with_cache`].
+///
+/// Related regular expressions, such as the patterns of a lexer, often have residuals in
+/// common. Every residual is kept once in the cache, and its derivatives are computed once,
+/// for all automata built with the cache.
+#[derive(Clone, Debug)]
+pub struct DerivativeCache<S: Alphabet> {
+    ids: HashMap<Prehashed<Residual<S>>, usize>,
+    residuals: Vec<Residual<S>>,
+    derivatives: HashMap<(usize, Symbols<S>), usize>,
+}
+
+impl<S: Alphabet> Default for DerivativeCache<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Alphabet> DerivativeCache<S> {
+    /// Returns an empty cache.
+    pub fn new() -> Self {
+        Self {
+            ids: HashMap::new(),
+            residuals: Vec::new(),
+            derivatives: HashMap::new(),
+        }
+    }
+
+    /// Returns the number of residuals in the cache.
+    pub fn residual_count(&self) -> usize {
+        self.residuals.len()
+    }
+
+    /// Removes all residuals that are not reachable from the given regular expressions by
+    /// derivatives in the cache, and their derivatives.
+    ///
+    /// Without purging, a long-lived cache keeps the residuals of every automaton built with
+    /// it. Purging with the regular expressions that are still in use keeps the residuals
+    /// that can be shared with their automata, and frees the others.
+    pub fn purge<'a, B>(&mut self, keep: impl IntoIterator<Item = &'a Regex<B>>)
+    where
+        B: Builder<Symbol = S> + 'a,
+    {
+        let mut successors = vec![Vec::new(); self.residuals.len()];
+        for ((from, _), to) in &self.derivatives {
+            successors[*from].push(*to);
+        }
+        let mut reachable = vec![false; self.residuals.len()];
+        let mut stack = keep
+            .into_iter()
+            .filter_map(|regex| {
+                self.ids
+                    .get(&Prehashed::new(
+                        regex.rebuild::<ApproximatelySimilarCanonical<_>>(),
+                    ))
+                    .copied()
+            })
+            .collect::<Vec<_>>();
+        while let Some(id) = stack.pop() {
+            if !reachable[id] {
+                reachable[id] = true;
+                stack.extend(successors[id].iter().copied());
+            }
+        }
+
+        // the purged cache is built separately, and replaces this cache only when it is
+        // complete, so that a panic leaves this cache consistent
+        let mut purged = Self::new();
+        let mut renumbered = vec![None; self.residuals.len()];
+        for (id, residual) in self.residuals.iter().enumerate() {
+            if reachable[id] {
+                renumbered[id] = Some(purged.insert(residual.clone()));
+            }
+        }
+        purged.derivatives = self
+            .derivatives
+            .iter()
+            .filter_map(|((from, symbols), to)| {
+                Some(((renumbered[*from]?, symbols.clone()), renumbered[*to]?))
+            })
+            .collect();
+        *self = purged;
+    }
+
+    /// Returns the identifier of the given residual, adding it if it is not in the cache.
+    pub(crate) fn insert(&mut self, regex: Residual<S>) -> usize {
+        let regex = Prehashed::new(regex);
+        if let Some(id) = self.ids.get(&regex) {
+            return *id;
+        }
+        let id = self.residuals.len();
+        self.residuals.push(regex.value.clone());
+        self.ids.insert(regex, id);
+        id
+    }
+
+    /// Returns the residual with the given identifier.
+    pub(crate) fn residual(&self, id: usize) -> &Residual<S> {
+        &self.residuals[id]
+    }
+
+    /// Returns the identifier of the derivative of the given residual w.r.t. the given symbols.
+    pub(crate) fn derive(&mut self, id: usize, symbols: &Symbols<S>) -> usize {
+        let key = (id, symbols.clone());
+        if let Some(next) = self.derivatives.get(&key) {
+            return *next;
+        }
+        let next = self.insert(self.residuals[id].derive_symbols(symbols));
+        self.derivatives.insert(key, next);
+        next
+    }
+}
+
+/// A reference-counted handle to a [`DerivativeCache`] that can be used from several threads.
+/// Cloning the handle does not clone the cache.
+///
+/// A [`DerivativeCache`] that is owned by a single thread needs no locking. A shared cache
+/// is locked for the construction of every automaton, so automata are built one at a time,
+/// but every thread benefits from the residuals found by the others.
+pub struct SharedDerivativeCache<S: Alphabet> {
+    cache: Arc<Mutex<DerivativeCache<S>>>,
+}
+
+impl<S: Alphabet> Clone for SharedDerivativeCache<S> {
+    fn clone(&self) -> Self {
+        Self {
+            cache: self.cache.clone(),
+        }
+    }
+}
+
+impl<S: Alphabet> DerivativeCache<S> {
+    /// Returns a shared handle to this cache.
+    pub fn into_shared(self) -> SharedDerivativeCache<S> {
+        SharedDerivativeCache {
+            cache: Arc::new(Mutex::new(self)),
+        }
+    }
+}
+
+impl<S: Alphabet> From<DerivativeCache<S>> for SharedDerivativeCache<S> {
+    fn from(value: DerivativeCache<S>) -> Self {
+        value.into_shared()
+    }
+}
+
+impl<S: Alphabet> Default for SharedDerivativeCache<S> {
+    fn default() -> Self {
+        DerivativeCache::new().into_shared()
+    }
+}
+
+impl<S: Alphabet> SharedDerivativeCache<S> {
+    /// Locks the cache, blocking until no other thread uses it. The cache stays usable if
+    /// a thread panicked while holding the lock, because entries are only ever added whole,
+    /// and [`DerivativeCache::purge`] replaces the cache only after the purged cache is built.
+    pub fn lock(&self) -> MutexGuard<'_, DerivativeCache<S>> {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<B: Builder> Regex<B> {
+    /// Build a finite automaton as with [`Regex::to_automaton`], using and extending the given
+    /// cache of derivatives.
+    ///
+    /// Building the automata of several regular expressions with the same cache avoids
+    /// computing and storing their common residuals and derivatives more than once.
+    pub fn to_automaton_with_cache(
+        &self,
+        cache: &mut DerivativeCache<B::Symbol>,
+    ) -> FiniteAutomaton<B::Symbol> {
+        self.rebuild::<ApproximatelySimilarCanonical<_>>()
+            .build_automaton(false, Representation::Hashed, cache)
+    }
+
+    /// Build a finite automaton as with [`Regex::to_automaton_with_cache`], holding the lock of
+    /// the given shared cache while the automaton is built.
+    pub fn to_automaton_with_shared_cache(
+        &self,
+        cache: &SharedDerivativeCache<B::Symbol>,
+    ) -> FiniteAutomaton<B::Symbol> {
+        let regex = self.rebuild::<ApproximatelySimilarCanonical<_>>();
+        regex.build_automaton(false, Representation::Hashed, &mut cache.lock())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use crate::builder::Pure;
+    use crate::ops::*;
+
+    use super::*;
+
+    type PureRegex = Regex<Pure<usize>>;
+
+    #[test]
+    fn test_to_automaton_with_cache() {
+        let tests: Vec<PureRegex> = vec![
+            [1.s(), (1.s() | 2.s()).c()].r(),
+            [1.s(), (1.s() | 2.s()).c(), 3.s()].r(),
+            [2.s(), (1.s() | 2.s()).c()].r(),
+            [1.s(), (1.s() | 2.s()).c()].r(),
+        ];
+        let words = vec![
+            vec![],
+            vec![1],
+            vec![1, 2, 1],
+            vec![1, 2, 3],
+            vec![2, 2],
+            vec![3],
+        ];
+        let mut cache = DerivativeCache::new();
+        let mut state_count = 0;
+        for regex in &tests {
+            let expected = regex.to_automaton();
+            let fa = regex.to_automaton_with_cache(&mut cache);
+            assert_eq!(expected.state_count(), fa.state_count(), "{}", regex);
+            for word in &words {
+                assert_eq!(
+                    expected.to_matcher().next_iter(word),
+                    fa.to_matcher().next_iter(word),
+                    "{} on {:?}",
+                    regex,
+                    word
+                );
+            }
+            state_count += fa.state_count();
+        }
+        assert!(cache.residual_count() < state_count);
+
+        let residual_count = cache.residual_count();
+        tests[0].to_automaton_with_cache(&mut cache);
+        assert_eq!(residual_count, cache.residual_count());
+    }
+
+    #[test]
+    fn test_purge() {
+        let kept: PureRegex = [1.s(), 2.s().c()].r();
+        let dropped: PureRegex = [3.s(), 4.s(), 5.s()].r();
+        let mut cache = DerivativeCache::new();
+        let kept_count = kept.to_automaton_with_cache(&mut cache).state_count();
+        dropped.to_automaton_with_cache(&mut cache);
+        assert!(cache.residual_count() > kept_count);
+
+        cache.purge([&kept]);
+        assert_eq!(kept_count, cache.residual_count());
+        let fa = kept.to_automaton_with_cache(&mut cache);
+        assert_eq!(kept_count, cache.residual_count());
+        assert!(fa.to_matcher().next_iter([1, 2, 2]));
+        assert!(!fa.to_matcher().next_iter([2]));
+
+        cache.purge(std::iter::empty::<&PureRegex>());
+        assert_eq!(0, cache.residual_count());
+    }
+
+    #[test]
+    fn test_to_automaton_with_shared_cache() {
+        let tests: Vec<(PureRegex, Vec<usize>)> = vec![
+            ([1.s(), (1.s() | 2.s()).c()].r(), vec![1, 2, 1]),
+            ([2.s(), (1.s() | 2.s()).c()].r(), vec![2, 2]),
+            ([3.s(), (1.s() | 2.s()).c()].r(), vec![3, 1]),
+        ];
+        let shared = SharedDerivativeCache::default();
+        thread::scope(|scope| {
+            for (regex, word) in &tests {
+                let cache = shared.clone();
+                scope.spawn(move || {
+                    let fa = regex.to_automaton_with_shared_cache(&cache);
+                    assert!(fa.to_matcher().next_iter(word), "{} on {:?}", regex, word);
+                });
+            }
+        });
+
+        let mut local = DerivativeCache::new();
+        for (regex, _) in &tests {
+            regex.to_automaton_with_cache(&mut local);
+        }
+        assert_eq!(local.residual_count(), shared.lock().residual_count());
+    }
+}
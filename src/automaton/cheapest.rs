@@ -0,0 +1,106 @@
+//! Find accepted words of minimal cost in finite automata.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::StateId;
+use crate::Alphabet;
+
+impl<S: Alphabet, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Returns an accepted word with the lowest total cost, where the cost of a word is the sum
+    /// of the costs of its symbols, or `None` if no word is accepted.
+    ///
+    /// Only symbols that have explicit transitions are used, because the automaton does not
+    /// know any other symbols. Among words with the same cost, one is chosen deterministically.
+    /// Costs are added with saturation.
+    pub fn cheapest_word(&self, cost: impl Fn(&S) -> u64) -> Option<Vec<S>> {
+        let symbols = self.symbols();
+        let costs = symbols
+            .iter()
+            .map(|symbol| cost(symbol))
+            .collect::<Vec<_>>();
+
+        let mut distances: Vec<Option<u64>> = vec![None; self.states.len()];
+        let mut predecessors: Vec<Option<(usize, usize)>> = vec![None; self.states.len()];
+        let mut queue = BinaryHeap::from([Reverse((0, 0))]);
+        distances[0] = Some(0);
+        while let Some(Reverse((distance, current))) = queue.pop() {
+            if distances[current] != Some(distance) {
+                continue;
+            }
+            if self.is_accepting(current) {
+                let mut word = Vec::new();
+                let mut state = current;
+                while let Some((previous, symbol)) = predecessors[state] {
+                    word.push(symbols[symbol].clone());
+                    state = previous;
+                }
+                word.reverse();
+                return Some(word);
+            }
+            for (idx, symbol) in symbols.iter().enumerate() {
+                let next = self.next(current, *symbol);
+                let next_distance = distance.saturating_add(costs[idx]);
+                if distances[next].is_none_or(|other| next_distance < other) {
+                    distances[next] = Some(next_distance);
+                    predecessors[next] = Some((current, idx));
+                    queue.push(Reverse((next_distance, next)));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+
+    #[test]
+    fn test_cheapest_word() {
+        let tests: Vec<(AscRegex, Option<Vec<usize>>)> = vec![
+            (().r(), None),
+            ([].r(), Some(vec![])),
+            (1.s() | 2.s(), Some(vec![1])),
+            (
+                [9.s(), 9.s()].r() | [1.s(), 1.s(), 1.s()].r(),
+                Some(vec![1, 1, 1]),
+            ),
+            ([1.s().c(), 5.s()].r() | [3.s(), 3.s()].r(), Some(vec![5])),
+            (
+                [2.s(), 2.s(), 2.s()].r() | [4.s(), 4.s()].r(),
+                Some(vec![2, 2, 2]),
+            ),
+            (1.s() & 2.s(), None),
+        ];
+        for (regex, expected) in tests {
+            let fa = regex.to_automaton();
+            assert_eq!(
+                expected,
+                fa.cheapest_word(|symbol| *symbol as u64),
+                "{}",
+                regex
+            );
+            let fa = fa.trim().minimize();
+            assert_eq!(
+                expected,
+                fa.cheapest_word(|symbol| *symbol as u64),
+                "{}",
+                regex
+            );
+        }
+    }
+
+    #[test]
+    fn test_cheapest_word_zero_cost() {
+        let regex: AscRegex = [1.s().c(), 2.s()].r();
+        let fa = regex.to_automaton();
+        assert_eq!(Some(vec![2]), fa.cheapest_word(|_| 0));
+        assert_eq!(Some(vec![2]), fa.cheapest_word(|_| u64::MAX));
+    }
+}
@@ -0,0 +1,109 @@
+//! Classify the states of finite automata.
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::StateId;
+use crate::Alphabet;
+
+/// The classification of a state, returned by [`FiniteAutomaton::classify_states`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct StateClass {
+    /// Whether the state can be reached from the start state.
+    pub reachable: bool,
+    /// Whether an accepting state can be reached from the state.
+    pub live: bool,
+    /// Whether no accepting state can be reached from the state. This is the opposite of
+    /// `live`.
+    pub dead: bool,
+    /// Whether the state is accepting.
+    pub accepting: bool,
+    /// Whether only accepting states can be reached from the state, so that every word is
+    /// accepted from it.
+    pub universal: bool,
+}
+
+impl<S: Alphabet, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Returns the classification of every state of this automaton, indexed by state.
+    pub fn classify_states(&self) -> Vec<StateClass> {
+        let reachable = self.reachable_states();
+        let live = self.live_states();
+        (0..self.state_count())
+            .map(|state| StateClass {
+                reachable: reachable[state],
+                live: live[state],
+                dead: !live[state],
+                accepting: self.is_accepting(state),
+                universal: self.is_universal(state),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    use super::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+
+    #[test]
+    fn test_classify_states() {
+        let regex: AscRegex = [1.s(), !().r()].r() | [2.s(), 3.s()].r();
+        let fa = regex.to_automaton();
+        let classes = fa.classify_states();
+        assert_eq!(fa.state_count(), classes.len());
+        assert!(classes.iter().all(|class| class.reachable));
+        assert!(classes.iter().all(|class| class.live != class.dead));
+        assert!(classes
+            .iter()
+            .all(|class| !class.universal || class.accepting));
+
+        let state = |word: &[usize]| word.iter().fold(0, |state, symbol| fa.next(state, symbol));
+        let class = |accepting, universal, live| StateClass {
+            reachable: true,
+            live,
+            dead: !live,
+            accepting,
+            universal,
+        };
+        let tests = vec![
+            (vec![], class(false, false, true)),
+            (vec![1], class(true, true, true)),
+            (vec![2], class(false, false, true)),
+            (vec![2, 3], class(true, false, true)),
+            (vec![3], class(false, false, false)),
+        ];
+        for (word, expected) in tests {
+            assert_eq!(expected, classes[state(&word)], "{:?}", word);
+        }
+    }
+
+    #[test]
+    fn test_classify_unreachable_states() {
+        let table = "
+            state accepting 1 default
+            0     false     0 0
+            1     true      1 1
+        ";
+        let fa = FiniteAutomaton::<usize>::from_table_str(table).expect("valid table");
+        let expected = vec![
+            StateClass {
+                reachable: true,
+                live: false,
+                dead: true,
+                accepting: false,
+                universal: false,
+            },
+            StateClass {
+                reachable: false,
+                live: true,
+                dead: false,
+                accepting: true,
+                universal: true,
+            },
+        ];
+        assert_eq!(expected, fa.classify_states());
+    }
+}
@@ -0,0 +1,304 @@
+//! Generate seed inputs for fuzzing from finite automata.
+
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::StateId;
+use crate::Alphabet;
+
+/// Limits on the words generated by [`FiniteAutomaton::corpus`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CorpusOptions {
+    accepted: usize,
+    near_misses: usize,
+    max_len: usize,
+}
+
+impl Default for CorpusOptions {
+    fn default() -> Self {
+        Self {
+            accepted: 32,
+            near_misses: 32,
+            max_len: 32,
+        }
+    }
+}
+
+impl CorpusOptions {
+    /// Limit the number of accepted words.
+    pub fn with_accepted(self, accepted: usize) -> Self {
+        Self { accepted, ..self }
+    }
+
+    /// Limit the number of near-miss words.
+    pub fn with_near_misses(self, near_misses: usize) -> Self {
+        Self {
+            near_misses,
+            ..self
+        }
+    }
+
+    /// Limit the length of accepted words.
+    pub fn with_max_len(self, max_len: usize) -> Self {
+        Self { max_len, ..self }
+    }
+}
+
+/// Seed inputs for fuzzing, generated by [`FiniteAutomaton::corpus`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Corpus<S> {
+    /// Accepted words, which together take every transition between states from which an
+    /// accepting state can be reached, as far as the limits allow.
+    pub accepted: Vec<Vec<S>>,
+    /// Rejected words that are a single insertion, deletion, or substitution away from one of
+    /// the accepted words.
+    pub near_misses: Vec<Vec<S>>,
+    /// Accepted words of the shortest and longest accepted lengths, and the rejected words
+    /// obtained by removing the last symbol of the shortest one or appending a symbol to the
+    /// longest one.
+    pub boundary: Vec<Vec<S>>,
+}
+
+impl<S: Alphabet, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Returns a corpus of accepted, near-miss, and boundary-length words for this automaton,
+    /// for use as seed inputs when fuzzing the systems whose input it describes.
+    ///
+    /// Words only contain symbols that have explicit transitions, because the automaton does
+    /// not know any other symbols. The corpus is deterministic.
+    pub fn corpus(&self, options: CorpusOptions) -> Corpus<S> {
+        let symbols = self.symbols();
+        let accepts = self.accepts_in(&symbols, options.max_len);
+        let prefixes = self.shortest_prefixes(&symbols);
+
+        let mut seen = HashSet::new();
+        let mut accepted = Vec::new();
+        let mut push_accepted = |word: Vec<S>| {
+            if accepted.len() < options.accepted && seen.insert(word.clone()) {
+                accepted.push(word);
+            }
+        };
+        if let Some(word) = self.shortest_suffix(&symbols, &accepts, 0, Vec::new()) {
+            push_accepted(word);
+        }
+        for (state, prefix) in prefixes.iter().enumerate() {
+            let Some(prefix) = prefix else {
+                continue;
+            };
+            for symbol in &symbols {
+                let mut word = prefix.clone();
+                word.push((*symbol).clone());
+                let next = self.next(state, *symbol);
+                if let Some(word) = self.shortest_suffix(&symbols, &accepts, next, word) {
+                    push_accepted(word);
+                }
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut near_misses = Vec::new();
+        'words: for word in &accepted {
+            for edit in edits(word, &symbols) {
+                if near_misses.len() == options.near_misses {
+                    break 'words;
+                }
+                if !self.is_accepted(&edit) && seen.insert(edit.clone()) {
+                    near_misses.push(edit);
+                }
+            }
+        }
+
+        let mut boundary = Vec::new();
+        let lengths = (0..=options.max_len).filter(|len| accepts[*len][0]);
+        if let (Some(min), Some(max)) = (lengths.clone().min(), lengths.max()) {
+            let shortest = self.word_of_len(&symbols, &accepts, 0, min, Vec::new());
+            if let Some((_, rest)) = shortest.split_last() {
+                boundary.push(rest.to_vec());
+            }
+            boundary.push(shortest);
+            if max > min {
+                boundary.push(self.word_of_len(&symbols, &accepts, 0, max, Vec::new()));
+            }
+            let longest = boundary.last().expect("longest word").clone();
+            let longer = symbols
+                .iter()
+                .map(|symbol| [longest.as_slice(), &[(*symbol).clone()]].concat())
+                .find(|word| !self.is_accepted(word));
+            boundary.extend(longer);
+        }
+
+        Corpus {
+            accepted,
+            near_misses,
+            boundary,
+        }
+    }
+
+    /// Returns whether the given word leads from the start state to an accepting state.
+    fn is_accepted(&self, word: &[S]) -> bool {
+        self.is_accepting(
+            word.iter()
+                .fold(0, |state, symbol| self.next(state, symbol)),
+        )
+    }
+
+    /// Returns for every length up to the given maximum, and every state, whether a word of
+    /// exactly that length over the given symbols leads from the state to an accepting state.
+    fn accepts_in(&self, symbols: &[&S], max_len: usize) -> Vec<Vec<bool>> {
+        let mut accepts = vec![self.states.iter().map(|state| state.accepting).collect()];
+        for len in 1..=max_len {
+            let shorter: &Vec<bool> = &accepts[len - 1];
+            let current = (0..self.states.len())
+                .map(|state| {
+                    symbols
+                        .iter()
+                        .any(|symbol| shorter[self.next(state, *symbol)])
+                })
+                .collect();
+            accepts.push(current);
+        }
+        accepts
+    }
+
+    /// Returns for every state a shortest word over the given symbols that leads from the
+    /// start state to it, or `None` if there is none.
+    fn shortest_prefixes(&self, symbols: &[&S]) -> Vec<Option<Vec<S>>> {
+        let mut prefixes = vec![None; self.states.len()];
+        prefixes[0] = Some(Vec::new());
+        let mut queue = VecDeque::from([0]);
+        while let Some(state) = queue.pop_front() {
+            for symbol in symbols {
+                let next = self.next(state, *symbol);
+                if prefixes[next].is_none() {
+                    let mut prefix = prefixes[state].clone().expect("visited state");
+                    prefix.push((*symbol).clone());
+                    prefixes[next] = Some(prefix);
+                    queue.push_back(next);
+                }
+            }
+        }
+        prefixes
+    }
+
+    /// Extends the given word with a shortest word that leads from the given state to an
+    /// accepting state, if the result is not longer than the maximum length.
+    fn shortest_suffix(
+        &self,
+        symbols: &[&S],
+        accepts: &[Vec<bool>],
+        state: usize,
+        word: Vec<S>,
+    ) -> Option<Vec<S>> {
+        let max_len = (accepts.len() - 1).checked_sub(word.len())?;
+        let len = (0..=max_len).find(|len| accepts[*len][state])?;
+        Some(self.word_of_len(symbols, accepts, state, len, word))
+    }
+
+    /// Extends the given word with the first word of the given length, in the order of the
+    /// given symbols, that leads from the given state to an accepting state.
+    fn word_of_len(
+        &self,
+        symbols: &[&S],
+        accepts: &[Vec<bool>],
+        state: usize,
+        len: usize,
+        mut word: Vec<S>,
+    ) -> Vec<S> {
+        let mut current = state;
+        for remaining in (0..len).rev() {
+            let (symbol, next) = symbols
+                .iter()
+                .map(|symbol| (*symbol, self.next(current, *symbol)))
+                .find(|(_, next)| accepts[remaining][*next])
+                .expect("accepting state is reachable");
+            word.push(symbol.clone());
+            current = next;
+        }
+        word
+    }
+}
+
+/// Returns the words that are a single deletion, substitution, or insertion away from the
+/// given word.
+fn edits<'a, S: Alphabet>(word: &'a [S], symbols: &'a [&S]) -> impl Iterator<Item = Vec<S>> + 'a {
+    let deletions = (0..word.len()).map(|idx| [&word[..idx], &word[idx + 1..]].concat());
+    let substitutions = (0..word.len()).flat_map(move |idx| {
+        symbols
+            .iter()
+            .filter(move |symbol| ***symbol != word[idx])
+            .map(move |symbol| {
+                let mut edit = word.to_vec();
+                edit[idx] = (*symbol).clone();
+                edit
+            })
+    });
+    let insertions = (0..=word.len()).flat_map(move |idx| {
+        symbols.iter().map(move |symbol| {
+            let mut edit = word.to_vec();
+            edit.insert(idx, (*symbol).clone());
+            edit
+        })
+    });
+    deletions.chain(substitutions).chain(insertions)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    use super::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+
+    #[test]
+    fn test_corpus() {
+        let regex: AscRegex = [1.s(), (2.s() | 3.s()).c(), 4.s()].r();
+        let fa = regex.to_automaton();
+        let corpus = fa.corpus(CorpusOptions::default());
+        assert_eq!(
+            vec![vec![1, 4], vec![1, 2, 4], vec![1, 3, 4]],
+            corpus.accepted
+        );
+        assert!(corpus.near_misses.contains(&vec![1]));
+        assert!(corpus.near_misses.contains(&vec![1, 4, 4]));
+        assert!(corpus.near_misses.len() <= 32);
+        for word in &corpus.near_misses {
+            assert!(!fa.to_matcher().next_iter(word));
+        }
+        let longest = [vec![1], vec![2; 30], vec![4]].concat();
+        assert_eq!(
+            vec![
+                vec![1],
+                vec![1, 4],
+                longest.clone(),
+                [longest, vec![1]].concat(),
+            ],
+            corpus.boundary
+        );
+    }
+
+    #[test]
+    fn test_corpus_limits() {
+        let regex: AscRegex = [1.s(), (2.s() | 3.s()).c(), 4.s()].r();
+        let fa = regex.to_automaton();
+        let options = CorpusOptions::default()
+            .with_accepted(2)
+            .with_near_misses(3)
+            .with_max_len(2);
+        let corpus = fa.corpus(options);
+        assert_eq!(vec![vec![1, 4]], corpus.accepted);
+        assert_eq!(3, corpus.near_misses.len());
+        assert_eq!(vec![vec![1], vec![1, 4], vec![1, 4, 1]], corpus.boundary);
+    }
+
+    #[test]
+    fn test_corpus_empty_language() {
+        let regex: AscRegex = 1.s() & 2.s();
+        let corpus = regex.to_automaton().corpus(CorpusOptions::default());
+        assert!(corpus.accepted.is_empty());
+        assert!(corpus.near_misses.is_empty());
+        assert!(corpus.boundary.is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+//! Finite automata whose states store only the transitions that differ from a base state.
+
+use std::borrow::Borrow;
+
+use itertools::Itertools;
+
+use crate::automaton::state_id;
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::StateId;
+use crate::Alphabet;
+
+/// A deterministic finite automaton in which states can store only the transitions that differ
+/// from those of a base state, as in the table compression of lexer generators.
+///
+/// This is much smaller than [`FiniteAutomaton`] and [`DenseAutomaton`](crate::DenseAutomaton)
+/// for automata in which most states have most of their transitions in common, such as the
+/// automata of keyword sets. A transition is found by a binary search in the transitions of
+/// the state, followed by one in the transitions of its base state.
+#[derive(Clone, Debug)]
+pub struct DeltaAutomaton<S: Alphabet, I: StateId = usize> {
+    states: Vec<DeltaState<S, I>>,
+}
+
+#[derive(Clone, Debug)]
+struct DeltaState<S, I> {
+    /// The state that has the transitions for the symbols that have no transition here. Base
+    /// states have no base themselves, and have a transition for every symbol of the automaton.
+    base: Option<I>,
+    /// Transitions sorted by symbol.
+    transitions: Vec<(S, I)>,
+    /// The transition for symbols that do not occur in the automaton.
+    default_transition: I,
+    accepting: bool,
+}
+
+impl<S: Alphabet, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Returns an equivalent automaton in which states store only the transitions that differ
+    /// from a base state.
+    ///
+    /// States are visited in order. A state stores its differences from the earlier base state
+    /// it differs least from, if these are fewer than its own transitions, and otherwise
+    /// becomes a base state.
+    pub fn to_delta(&self) -> DeltaAutomaton<S, I> {
+        let symbols = self.symbols();
+        let rows = (0..self.state_count())
+            .map(|state| {
+                symbols
+                    .iter()
+                    .map(|symbol| self.next(state, *symbol))
+                    .collect_vec()
+            })
+            .collect_vec();
+        let differences = |row: &[usize], base: &[usize]| {
+            row.iter()
+                .zip(base)
+                .filter(|(next, base_next)| next != base_next)
+                .count()
+        };
+
+        let mut bases: Vec<usize> = Vec::new();
+        let mut states = Vec::with_capacity(rows.len());
+        for (state, row) in rows.iter().enumerate() {
+            let base = bases
+                .iter()
+                .map(|base| (*base, differences(row, &rows[*base])))
+                .min_by_key(|(_, count)| *count)
+                .filter(|(_, count)| *count < row.len());
+            let transitions = symbols
+                .iter()
+                .zip(row)
+                .enumerate()
+                .filter(|(idx, _)| base.is_none_or(|(base, _)| rows[base][*idx] != row[*idx]))
+                .map(|(_, (symbol, next))| ((*symbol).clone(), state_id(*next)))
+                .collect_vec();
+            if base.is_none() {
+                bases.push(state);
+            }
+            states.push(DeltaState {
+                base: base.map(|(base, _)| state_id(base)),
+                transitions,
+                default_transition: state_id(self.default_transition(state)),
+                accepting: self.is_accepting(state),
+            });
+        }
+        DeltaAutomaton { states }
+    }
+}
+
+impl<S: Alphabet, I: StateId> DeltaAutomaton<S, I> {
+    pub fn to_matcher(&self) -> DeltaMatcher<'_, S, I> {
+        DeltaMatcher { fa: self, state: 0 }
+    }
+
+    /// Returns the number of states.
+    pub fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Returns the number of transitions that are stored, which does not include the default
+    /// transitions.
+    pub fn transition_count(&self) -> usize {
+        self.states
+            .iter()
+            .map(|state| state.transitions.len())
+            .sum()
+    }
+
+    fn next<Q>(&self, current: usize, symbol: &Q) -> usize
+    where
+        S: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let state = &self.states[current];
+        let find = |transitions: &[(S, I)]| {
+            transitions
+                .binary_search_by(|(other, _)| other.borrow().cmp(symbol))
+                .ok()
+                .map(|idx| transitions[idx].1)
+        };
+        find(&state.transitions)
+            .or_else(|| {
+                let base = state.base?;
+                find(&self.states[base.index()].transitions)
+            })
+            .unwrap_or(state.default_transition)
+            .index()
+    }
+}
+
+pub struct DeltaMatcher<'a, S: Alphabet, I: StateId = usize> {
+    fa: &'a DeltaAutomaton<S, I>,
+    state: usize,
+}
+
+impl<S: Alphabet, I: StateId> DeltaMatcher<'_, S, I> {
+    /// Consume the given symbol and return whether the matcher is in an accepting state.
+    pub fn next<Q>(&mut self, symbol: &Q) -> bool
+    where
+        S: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.state = self.fa.next(self.state, symbol);
+        self.fa.states[self.state].accepting
+    }
+
+    pub fn next_iter<T>(&mut self, symbols: impl IntoIterator<Item = T>) -> bool
+    where
+        T: Borrow<S>,
+    {
+        for symbol in symbols {
+            self.state = self.fa.next(self.state, symbol.borrow());
+        }
+        self.fa.states[self.state].accepting
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+
+    #[test]
+    fn test_delta_matcher() {
+        let tests: Vec<AscRegex> = vec![
+            ().r(),
+            !().r(),
+            [1.s(), 2.s()].r(),
+            (1.s() | 3.s() | 5.s()).c(),
+            !3.s() & (2.s() | 3.s() | 4.s()),
+            [!().r(), 1.s(), 2.s(), 3.s()].r(),
+        ];
+        let words = (1..=4)
+            .flat_map(|len| std::iter::repeat_n([1, 2, 3, 4], len).multi_cartesian_product())
+            .chain([vec![]])
+            .collect_vec();
+        for regex in tests {
+            let fa = regex.to_automaton();
+            let delta = fa.to_delta();
+            assert_eq!(fa.state_count(), delta.state_count());
+            for word in &words {
+                assert_eq!(
+                    fa.to_matcher().next_iter(word),
+                    delta.to_matcher().next_iter(word),
+                    "{} on {:?}",
+                    regex,
+                    word
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_delta_transition_count() {
+        // every state goes back to the start on most symbols
+        let regex: AscRegex = [!().r(), Regex::concat_all((1..=8).map(|symbol| symbol.s()))].r();
+        let fa = regex.to_automaton();
+        let delta = fa.to_delta();
+        assert_eq!(9, fa.state_count());
+        // the start state has all eight, the others only the one to the next state
+        assert_eq!(8 + 7, delta.transition_count());
+    }
+}
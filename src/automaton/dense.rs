@@ -0,0 +1,384 @@
+//! Finite automata with a flat transition table.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use itertools::Itertools;
+
+#[cfg(feature = "simd")]
+use crate::automaton::prefilter::find_byte;
+use crate::automaton::state_id;
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::StateId;
+use crate::raw::RawDenseAutomaton;
+use crate::Alphabet;
+
+/// A deterministic finite automaton with all transitions in a single contiguous table.
+///
+/// Symbols are compressed into classes of symbols that have the same transitions in every
+/// state. The table has a row per state and a column per class, so a transition is found by
+/// looking up the class of the symbol, followed by a single array load. Symbols that do not
+/// occur in the regular expression belong to class `0`.
+#[derive(Clone, Debug)]
+pub struct DenseAutomaton<S: Alphabet, I: StateId = usize> {
+    classes: HashMap<S, usize>,
+    class_count: usize,
+    /// A bitset with a bit per state, which is set for accepting states.
+    accepting: Vec<u64>,
+    table: Vec<I>,
+}
+
+impl<S: Alphabet, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Returns an equivalent automaton with a flat transition table.
+    pub fn to_dense(&self) -> DenseAutomaton<S, I> {
+        let state_count = self.state_count();
+        let default_column = (0..state_count)
+            .map(|state| self.default_transition(state))
+            .collect_vec();
+
+        let mut columns = vec![default_column.clone()];
+        let mut column_ids = HashMap::from([(default_column, 0)]);
+        let mut classes = HashMap::new();
+        for symbol in self.symbols() {
+            let column = (0..state_count)
+                .map(|state| self.next(state, symbol))
+                .collect_vec();
+            let class = *column_ids.entry(column.clone()).or_insert_with(|| {
+                columns.push(column);
+                columns.len() - 1
+            });
+            if class != 0 {
+                classes.insert(symbol.clone(), class);
+            }
+        }
+
+        let class_count = columns.len();
+        let mut table = Vec::with_capacity(state_count * class_count);
+        for state in 0..state_count {
+            table.extend(columns.iter().map(|column| state_id::<I>(column[state])));
+        }
+        let mut accepting = vec![0; state_count.div_ceil(64)];
+        for state in (0..state_count).filter(|state| self.is_accepting(*state)) {
+            accepting[state / 64] |= 1 << (state % 64);
+        }
+        DenseAutomaton {
+            classes,
+            class_count,
+            accepting,
+            table,
+        }
+    }
+}
+
+impl<S: Alphabet, I: StateId> DenseAutomaton<S, I> {
+    pub fn to_matcher(&self) -> DenseMatcher<'_, S, I> {
+        DenseMatcher { fa: self, state: 0 }
+    }
+
+    /// Returns the number of symbol classes, which is the number of columns of the table.
+    pub fn class_count(&self) -> usize {
+        self.class_count
+    }
+
+    /// Returns a view of the tables of this automaton, for matching loops outside this crate.
+    pub fn raw(&self) -> RawDenseAutomaton<'_, S, I> {
+        RawDenseAutomaton::new(
+            &self.classes,
+            self.class_count,
+            &self.accepting,
+            &self.table,
+        )
+    }
+
+    #[inline]
+    fn is_accepting(&self, state: usize) -> bool {
+        self.accepting[state / 64] & (1 << (state % 64)) != 0
+    }
+
+    #[inline]
+    fn next<Q>(&self, current: usize, symbol: &Q) -> usize
+    where
+        S: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let class = self.classes.get(symbol).copied().unwrap_or(0);
+        self.table[current * self.class_count + class].index()
+    }
+}
+
+pub struct DenseMatcher<'a, S: Alphabet, I: StateId = usize> {
+    fa: &'a DenseAutomaton<S, I>,
+    state: usize,
+}
+
+impl<S: Alphabet, I: StateId> DenseMatcher<'_, S, I> {
+    /// Consume the given symbol and return whether the matcher is in an accepting state.
+    pub fn next<Q>(&mut self, symbol: &Q) -> bool
+    where
+        S: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.state = self.fa.next(self.state, symbol);
+        self.fa.is_accepting(self.state)
+    }
+
+    pub fn next_iter<T>(&mut self, symbols: impl IntoIterator<Item = T>) -> bool
+    where
+        T: Borrow<S>,
+    {
+        for symbol in symbols {
+            self.state = self.fa.next(self.state, symbol.borrow());
+        }
+        self.fa.is_accepting(self.state)
+    }
+
+    /// Consume the given borrowed symbols and return whether the matcher is in an accepting
+    /// state, as [`Matcher::next_iter_borrowed`](crate::Matcher::next_iter_borrowed) does.
+    pub fn next_iter_borrowed<'s, Q>(&mut self, symbols: impl IntoIterator<Item = &'s Q>) -> bool
+    where
+        S: Borrow<Q>,
+        Q: Hash + Eq + ?Sized + 's,
+    {
+        for symbol in symbols {
+            self.state = self.fa.next(self.state, symbol);
+        }
+        self.fa.is_accepting(self.state)
+    }
+}
+
+/// The maximum number of bytes leaving a state for which [`IndexedMatcher::feed_bytes`] skips
+/// ahead.
+#[cfg(feature = "simd")]
+const MAX_ESCAPES: usize = 3;
+
+/// A [`DenseAutomaton`] for alphabets whose symbols are small integers, such as bytes, returned
+/// by [`DenseAutomaton::to_indexed`].
+///
+/// The class of a symbol is found by indexing an array with the symbol, instead of a hash map
+/// lookup, so matching does no hashing, cloning, or allocation.
+#[derive(Clone, Debug)]
+pub struct IndexedAutomaton<S, I: StateId = usize> {
+    /// The class of every symbol, indexed by the symbol. Larger symbols belong to class `0`.
+    classes: Vec<usize>,
+    class_count: usize,
+    accepting: Vec<u64>,
+    table: Vec<I>,
+    /// The bytes that lead out of every state, if there are at most three of them, and all
+    /// other symbols lead back to the state.
+    #[cfg(feature = "simd")]
+    escapes: Vec<Option<Vec<u8>>>,
+    _symbols: PhantomData<fn(S)>,
+}
+
+impl<S: Alphabet + Copy + Into<usize>, I: StateId> DenseAutomaton<S, I> {
+    /// Returns an equivalent automaton that finds the classes of symbols by indexing. The class
+    /// array has an entry for every symbol up to the largest symbol in the regular expression.
+    pub fn to_indexed(&self) -> IndexedAutomaton<S, I> {
+        let len = self
+            .classes
+            .keys()
+            .map(|symbol| (*symbol).into() + 1)
+            .max()
+            .unwrap_or(0);
+        let mut classes = vec![0; len];
+        for (symbol, class) in &self.classes {
+            classes[(*symbol).into()] = *class;
+        }
+        IndexedAutomaton {
+            #[cfg(feature = "simd")]
+            escapes: self.escapes(&classes),
+            classes,
+            class_count: self.class_count,
+            accepting: self.accepting.clone(),
+            table: self.table.clone(),
+            _symbols: PhantomData,
+        }
+    }
+
+    /// Returns the bytes that lead out of every state, if there are at most three of them,
+    /// they are all bytes, and all other symbols lead back to the state.
+    #[cfg(feature = "simd")]
+    fn escapes(&self, classes: &[usize]) -> Vec<Option<Vec<u8>>> {
+        let state_count = self.table.len() / self.class_count;
+        (0..state_count)
+            .map(|state| {
+                let row = &self.table[state * self.class_count..(state + 1) * self.class_count];
+                if row[0].index() != state {
+                    return None;
+                }
+                let escapes = (0..classes.len())
+                    .filter(|symbol| row[classes[*symbol]].index() != state)
+                    .map(|symbol| u8::try_from(symbol).ok())
+                    .collect::<Option<Vec<_>>>()?;
+                (escapes.len() <= MAX_ESCAPES).then_some(escapes)
+            })
+            .collect()
+    }
+}
+
+impl<S: Copy + Into<usize>, I: StateId> IndexedAutomaton<S, I> {
+    pub fn to_matcher(&self) -> IndexedMatcher<'_, S, I> {
+        IndexedMatcher { fa: self, state: 0 }
+    }
+
+    #[inline]
+    fn is_accepting(&self, state: usize) -> bool {
+        self.accepting[state / 64] & (1 << (state % 64)) != 0
+    }
+
+    #[inline]
+    fn next(&self, current: usize, symbol: S) -> usize {
+        let class = self.classes.get(symbol.into()).copied().unwrap_or(0);
+        self.table[current * self.class_count + class].index()
+    }
+}
+
+pub struct IndexedMatcher<'a, S, I: StateId = usize> {
+    fa: &'a IndexedAutomaton<S, I>,
+    state: usize,
+}
+
+impl<S: Copy + Into<usize>, I: StateId> IndexedMatcher<'_, S, I> {
+    /// Consume the given symbol and return whether the matcher is in an accepting state.
+    #[inline]
+    pub fn next(&mut self, symbol: S) -> bool {
+        self.state = self.fa.next(self.state, symbol);
+        self.fa.is_accepting(self.state)
+    }
+
+    /// Consume the given symbols and return whether the matcher is in an accepting state.
+    pub fn next_iter(&mut self, symbols: impl IntoIterator<Item = S>) -> bool {
+        for symbol in symbols {
+            self.state = self.fa.next(self.state, symbol);
+        }
+        self.fa.is_accepting(self.state)
+    }
+
+    /// Consume the given slice of symbols and return whether the matcher is in an accepting
+    /// state.
+    pub fn feed(&mut self, symbols: &[S]) -> bool {
+        self.next_iter(symbols.iter().copied())
+    }
+}
+
+#[cfg(feature = "simd")]
+impl<I: StateId> IndexedMatcher<'_, u8, I> {
+    /// Consume the given bytes and return whether the matcher is in an accepting state, as
+    /// [`IndexedMatcher::feed`] does.
+    ///
+    /// In states that are only left by at most three bytes, `memchr` skips ahead to the next
+    /// of those bytes, using vector instructions where available. This is fast for inputs with
+    /// long runs that do not change the state, such as logs scanned for a rare marker.
+    pub fn feed_bytes(&mut self, bytes: &[u8]) -> bool {
+        let mut position = 0;
+        while position < bytes.len() {
+            if let Some(escapes) = &self.fa.escapes[self.state] {
+                match find_byte(escapes, &bytes[position..]) {
+                    Some(offset) => position += offset,
+                    None => break,
+                }
+            }
+            self.state = self.fa.next(self.state, bytes[position]);
+            position += 1;
+        }
+        self.fa.is_accepting(self.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+
+    #[test]
+    fn test_dense_matcher() {
+        let tests: Vec<(AscRegex, usize, Vec<usize>, bool)> = vec![
+            (().r(), 1, vec![], false),
+            (!().r(), 1, vec![1, 2], true),
+            ([1.s(), 2.s()].r(), 3, vec![1, 2], true),
+            ([1.s(), 2.s()].r(), 3, vec![1, 3], false),
+            ((1.s() | 3.s() | 5.s()).c(), 2, vec![5, 3, 1], true),
+            ((1.s() | 3.s() | 5.s()).c(), 2, vec![5, 4], false),
+            (!3.s() & (2.s() | 3.s() | 4.s()), 3, vec![4], true),
+            (!3.s() & (2.s() | 3.s() | 4.s()), 3, vec![3], false),
+        ];
+        for (regex, class_count, word, expected) in tests {
+            let fa = regex.to_automaton();
+            let dense = fa.to_dense();
+            assert_eq!(class_count, dense.class_count(), "classes of {}", regex);
+            assert_eq!(expected, dense.to_matcher().next_iter(&word), "{}", regex);
+            let dense = regex.to_automaton_with_state_ids::<u8>().to_dense();
+            assert_eq!(expected, dense.to_matcher().next_iter(&word), "{}", regex);
+        }
+    }
+
+    #[test]
+    fn test_indexed_matcher() {
+        type ByteRegex = Regex<ApproximatelySimilarCanonical<u8>>;
+        let tests: Vec<(ByteRegex, Vec<u8>)> = vec![
+            (().r(), vec![]),
+            (!().r(), vec![1, 200]),
+            ([b'a'.s(), b'b'.s()].r(), vec![b'a', b'b']),
+            ([b'a'.s(), b'b'.s()].r(), vec![b'a', b'c']),
+            ((b'a'.s() | b'c'.s()).c(), vec![b'c', b'a']),
+            ((b'a'.s() | b'c'.s()).c(), vec![b'c', 255]),
+            (!b'c'.s() & (b'b'.s() | b'c'.s() | b'd'.s()), vec![b'd']),
+            (!b'c'.s() & (b'b'.s() | b'c'.s() | b'd'.s()), vec![b'c']),
+        ];
+        for (regex, word) in tests {
+            let dense = regex.to_automaton().to_dense();
+            let indexed = dense.to_indexed();
+            let expected = dense.to_matcher().next_iter(&word);
+            assert_eq!(expected, regex.is_match(&word), "{} on {:?}", regex, word);
+            assert_eq!(
+                expected,
+                indexed.to_matcher().feed(&word),
+                "{} on {:?}",
+                regex,
+                word
+            );
+            let mut matcher = indexed.to_matcher();
+            let last = word.iter().map(|symbol| matcher.next(*symbol)).last();
+            assert_eq!(
+                expected,
+                last.unwrap_or(expected),
+                "{} on {:?}",
+                regex,
+                word
+            );
+        }
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn test_feed_bytes() {
+        type ByteRegex = Regex<ApproximatelySimilarCanonical<u8>>;
+        let anything = || !().r();
+        let tests: Vec<(ByteRegex, &[u8])> = vec![
+            ([anything(), b'!'.s(), anything()].r(), b"......!...."),
+            ([anything(), b'!'.s(), anything()].r(), b".........."),
+            ([anything(), b'!'.s(), b'?'.s()].r(), b"..!..!?"),
+            ([anything(), b'!'.s(), b'?'.s()].r(), b"..!?..!"),
+            ([b'['.s(), (!b']'.s()).c(), b']'.s()].r(), b"[......]"),
+            ([b'['.s(), (!b']'.s()).c(), b']'.s()].r(), b"[...]..]"),
+            (anything(), b"......"),
+            (().r(), b"......"),
+            ([].r(), b""),
+        ];
+        for (regex, bytes) in tests {
+            let indexed = regex.to_automaton().to_dense().to_indexed();
+            assert_eq!(
+                regex.is_match(bytes),
+                indexed.to_matcher().feed_bytes(bytes),
+                "{} on {:?}",
+                regex,
+                String::from_utf8_lossy(bytes)
+            );
+        }
+    }
+}
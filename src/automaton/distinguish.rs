@@ -0,0 +1,82 @@
+//! Find words that distinguish states of finite automata.
+
+use std::collections::HashSet;
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::StateId;
+use crate::Alphabet;
+
+impl<S: Alphabet, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Returns a shortest word that is accepted from exactly one of the given states, or
+    /// `None` if no such word is found. Labels are not taken into account.
+    ///
+    /// Only symbols that have explicit transitions are used, as for
+    /// [`FiniteAutomaton::cheapest_word`], so states that only differ in their default
+    /// transitions are not distinguished. Among words of the same length, one is chosen
+    /// deterministically.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either state does not exist.
+    pub fn distinguishing_word(&self, left: usize, right: usize) -> Option<Vec<S>> {
+        assert!(left < self.states.len() && right < self.states.len());
+        let symbols = self.symbols();
+
+        let mut visited = HashSet::from([(left, right)]);
+        let mut pairs = vec![(left, right)];
+        let mut predecessors: Vec<Option<(usize, usize)>> = vec![None];
+        let mut current = 0;
+        while let Some((left, right)) = pairs.get(current).copied() {
+            if self.is_accepting(left) != self.is_accepting(right) {
+                let mut word = Vec::new();
+                let mut idx = current;
+                while let Some((previous, symbol)) = predecessors[idx] {
+                    word.push(symbols[symbol].clone());
+                    idx = previous;
+                }
+                word.reverse();
+                return Some(word);
+            }
+            for (idx, symbol) in symbols.iter().enumerate() {
+                let next = (self.next(left, *symbol), self.next(right, *symbol));
+                if next.0 != next.1 && visited.insert(next) {
+                    pairs.push(next);
+                    predecessors.push(Some((current, idx)));
+                }
+            }
+            current += 1;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+    type Word = Option<Vec<usize>>;
+
+    #[test]
+    fn test_distinguishing_word() {
+        let regex: AscRegex = [1.s(), 2.s().c(), 3.s()].r() | [2.s(), 2.s(), 3.s()].r();
+        let fa = regex.to_automaton();
+        let state = |word: &[usize]| word.iter().fold(0, |state, symbol| fa.next(state, symbol));
+        let tests: Vec<(Vec<usize>, Vec<usize>, Word)> = vec![
+            (vec![], vec![], None),
+            (vec![1], vec![1, 2], None),
+            (vec![1], vec![2], Some(vec![3])),
+            (vec![1], vec![2, 2], Some(vec![2, 3])),
+            (vec![1, 3], vec![3], Some(vec![])),
+            (vec![], vec![1], Some(vec![3])),
+        ];
+        for (left, right, expected) in tests {
+            let (left, right) = (state(&left), state(&right));
+            let word = fa.distinguishing_word(left, right);
+            assert_eq!(expected, word, "{} and {}", left, right);
+            assert_eq!(word, fa.distinguishing_word(right, left));
+        }
+    }
+}
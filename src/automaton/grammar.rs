@@ -0,0 +1,132 @@
+//! Convert finite automata to right-linear grammars.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt::Display;
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::StateId;
+use crate::derivation::Symbols;
+use crate::Alphabet;
+
+/// A production of a [`RightLinearGrammar`]. Nonterminals are identified by the states of the
+/// automaton the grammar was created from.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Production<S: Alphabet> {
+    /// The nonterminal `from` produces a symbol from the set, followed by the nonterminal
+    /// `to`.
+    Step {
+        from: usize,
+        symbols: Symbols<S>,
+        to: usize,
+    },
+    /// The nonterminal `from` produces the empty string.
+    Empty { from: usize },
+}
+
+/// A right-linear grammar, returned by [`FiniteAutomaton::to_right_linear_grammar`].
+///
+/// The start nonterminal is `0`. Displaying the grammar writes one production per line, with
+/// nonterminals written as `N` followed by their number.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RightLinearGrammar<S: Alphabet> {
+    /// The productions, ordered by the nonterminal they produce from.
+    pub productions: Vec<Production<S>>,
+}
+
+impl<S: Alphabet, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Returns a right-linear grammar that generates the language of this automaton.
+    ///
+    /// Every state is a nonterminal, which produces the empty string if the state is
+    /// accepting, and a symbol followed by the target nonterminal for every transition.
+    /// Transitions with the same target are combined into a single production, and the
+    /// default transition produces the symbols without explicit transitions to other states.
+    /// Only states that are reachable and from which an accepting state can be reached are
+    /// included, so the grammar has no productions if the language is empty.
+    pub fn to_right_linear_grammar(&self) -> RightLinearGrammar<S> {
+        let reachable = self.reachable_states();
+        let live = self.live_states();
+        let mut productions = Vec::new();
+        for from in 0..self.state_count() {
+            if !reachable[from] || !live[from] {
+                continue;
+            }
+            if self.is_accepting(from) {
+                productions.push(Production::Empty { from });
+            }
+            let default = self.default_transition(from);
+            let mut targets = BTreeMap::<usize, BTreeSet<S>>::new();
+            let mut explicit = BTreeSet::new();
+            for (symbol, to) in self.symbol_transitions(from) {
+                if to != default {
+                    targets.entry(to).or_default().insert(symbol.clone());
+                    explicit.insert(symbol.clone());
+                }
+            }
+            productions.extend(
+                targets
+                    .into_iter()
+                    .map(|(to, symbols)| (to, Symbols::Include(symbols.into())))
+                    .chain(std::iter::once((
+                        default,
+                        Symbols::Exclude(explicit.into()),
+                    )))
+                    .filter(|(to, _)| live[*to])
+                    .map(|(to, symbols)| Production::Step { from, symbols, to }),
+            );
+        }
+        RightLinearGrammar { productions }
+    }
+}
+
+impl<S: Alphabet + Display> Display for Production<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Step { from, symbols, to } => write!(f, "N{} → {} N{}", from, symbols, to),
+            Self::Empty { from } => write!(f, "N{} → ε", from),
+        }
+    }
+}
+
+impl<S: Alphabet + Display> Display for RightLinearGrammar<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for production in &self.productions {
+            writeln!(f, "{}", production)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+
+    #[test]
+    fn test_to_right_linear_grammar() {
+        let tests: Vec<(AscRegex, &str)> = vec![
+            (().r(), ""),
+            ([].r(), "N0 → ε\n"),
+            (
+                [1.s() | 2.s(), 3.s().c()].r(),
+                "N0 → {1, 2} N1\nN1 → ε\nN1 → {3} N1\n",
+            ),
+            (
+                [!().r(), 1.s()].r(),
+                "N0 → {1} N1\nN0 → Σ∖{1} N0\nN1 → ε\nN1 → {1} N1\nN1 → Σ∖{1} N0\n",
+            ),
+        ];
+        for (regex, expected) in tests {
+            let fa = regex.to_automaton();
+            assert_eq!(
+                expected,
+                fa.to_right_linear_grammar().to_string(),
+                "{}",
+                regex
+            );
+        }
+    }
+}
@@ -0,0 +1,566 @@
+//! Scan text with finite automata, tracking lines and columns.
+
+use std::ops::Range;
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::StateId;
+
+/// A position in text, returned as part of a [`Span`] or [`Mismatch`] by a [`LineScanner`].
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Position {
+    /// The byte offset, starting at zero.
+    pub offset: usize,
+    /// The line, starting at one. Lines are separated by `'\n'`.
+    pub line: usize,
+    /// The column in characters, starting at one.
+    pub column: usize,
+}
+
+impl Position {
+    fn start() -> Self {
+        Self {
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    /// Returns the position after the given character.
+    fn advance(self, c: char) -> Self {
+        let offset = self.offset + c.len_utf8();
+        if c == '\n' {
+            Self {
+                offset,
+                line: self.line + 1,
+                column: 1,
+            }
+        } else {
+            Self {
+                offset,
+                column: self.column + 1,
+                ..self
+            }
+        }
+    }
+}
+
+/// The span of a match, yielded by a [`LineScanner`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Span {
+    /// The position of the first character of the match.
+    pub start: Position,
+    /// The position after the last character of the match.
+    pub end: Position,
+}
+
+impl Span {
+    /// Returns the byte range of the match, for slicing the scanned text.
+    pub fn range(&self) -> Range<usize> {
+        self.start.offset..self.end.offset
+    }
+}
+
+/// A position where no match starts, yielded by a [`LineScanner`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Mismatch {
+    /// The position where the match was expected.
+    pub position: Position,
+    /// The character at that position.
+    pub found: char,
+    /// The position after the characters that were skipped to recover from the mismatch. It
+    /// is the same as `position` if the scanner stopped.
+    pub end: Position,
+}
+
+impl Mismatch {
+    /// Returns the span of the characters that were skipped to recover from the mismatch, to
+    /// use as an error token.
+    pub fn span(&self) -> Span {
+        Span {
+            start: self.position,
+            end: self.end,
+        }
+    }
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unexpected {:?} at line {}, column {}",
+            self.found, self.position.line, self.position.column
+        )
+    }
+}
+
+impl std::error::Error for Mismatch {}
+
+/// How a [`LineScanner`] recovers when no match starts at the current position, set with
+/// [`LineScanner::with_recovery`].
+#[derive(Clone, Copy, Debug, Default)]
+pub enum Recovery {
+    /// Stop scanning.
+    #[default]
+    Stop,
+    /// Skip the unexpected character.
+    SkipChar,
+    /// Skip characters until a match starts, so that consecutive unexpected characters are
+    /// reported as a single mismatch.
+    SkipToMatch,
+    /// Skip the unexpected character, and the characters after it until one for which the
+    /// given function returns true, such as [`char::is_whitespace`].
+    SkipToSentinel(fn(char) -> bool),
+}
+
+/// Splits text into consecutive matches of an automaton, as a lexer does, returned by
+/// [`FiniteAutomaton::line_scanner`].
+///
+/// Every step consumes the longest non-empty match at the current position, and yields its
+/// span. If no non-empty match starts at the current position, a [`Mismatch`] is yielded, and
+/// the scanner stops, unless another [`Recovery`] is set.
+pub struct LineScanner<'a, 'b, L, I: StateId = usize> {
+    automaton: &'b FiniteAutomaton<char, L, I>,
+    text: &'a str,
+    position: Position,
+    recovery: Recovery,
+    failed: bool,
+}
+
+/// The result of scanning text again after an edit, returned by [`FiniteAutomaton::rescan`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rescan {
+    /// The range of the old spans that are replaced. The old spans after this range are
+    /// re-synchronized: they are the same in the edited text, at shifted positions.
+    pub replaced: Range<usize>,
+    /// The spans that replace the old spans in the range.
+    pub spans: Vec<Span>,
+    /// The position in the old text where scanning re-synchronized.
+    old: Position,
+    /// The position in the edited text where scanning re-synchronized.
+    new: Position,
+}
+
+impl Rescan {
+    /// Returns the given re-synchronized old span, at its position in the edited text.
+    pub fn shift(&self, span: Span) -> Span {
+        Span {
+            start: self.shift_position(span.start),
+            end: self.shift_position(span.end),
+        }
+    }
+
+    fn shift_position(&self, position: Position) -> Position {
+        Position {
+            offset: position.offset - self.old.offset + self.new.offset,
+            line: position.line - self.old.line + self.new.line,
+            column: if position.line == self.old.line {
+                position.column - self.old.column + self.new.column
+            } else {
+                position.column
+            },
+        }
+    }
+}
+
+impl<L, I: StateId> FiniteAutomaton<char, L, I> {
+    /// Returns a scanner that splits the given text into matches of this automaton, with
+    /// lines and columns for every match and mismatch.
+    pub fn line_scanner<'a>(&self, text: &'a str) -> LineScanner<'a, '_, L, I> {
+        self.line_scanner_at(text, Position::start())
+    }
+
+    /// Returns a scanner that resumes scanning the given text at the given checkpoint, which
+    /// is a position between matches, as returned by [`LineScanner::position`] for the same
+    /// text.
+    pub fn line_scanner_at<'a>(
+        &self,
+        text: &'a str,
+        checkpoint: Position,
+    ) -> LineScanner<'a, '_, L, I> {
+        LineScanner {
+            automaton: self,
+            text,
+            position: checkpoint,
+            recovery: Recovery::Stop,
+            failed: false,
+        }
+    }
+
+    /// Scans the given edited text again, reusing the spans from scanning the text before the
+    /// edit. The edit replaced the given byte range of the old text with the given number of
+    /// bytes. The spans must be all spans of the old text, without mismatches.
+    ///
+    /// Scanning starts at the earliest span whose scan may have looked at the edited range,
+    /// and stops as soon as a new span ends at the end of an old span after the edit. Returns
+    /// a mismatch if one is found before that.
+    pub fn rescan(
+        &self,
+        text: &str,
+        spans: &[Span],
+        edit: Range<usize>,
+        inserted: usize,
+    ) -> Result<Rescan, Mismatch> {
+        // the scan of any earlier span may have looked ahead past the ends of the spans
+        // between it and the edit, so all of them are checked
+        let first = spans.partition_point(|span| span.end.offset < edit.start);
+        let first = (0..first)
+            .find(|idx| self.scan_reaches(text, spans[*idx].start.offset, edit.start))
+            .unwrap_or(first);
+        let start = first
+            .checked_sub(1)
+            .map_or(Position::start(), |idx| spans[idx].end);
+
+        let edit_end = edit.start + inserted;
+        let mut new_spans = Vec::new();
+        for span in self.line_scanner_at(text, start) {
+            let span = span?;
+            new_spans.push(span);
+            if span.end.offset < edit_end {
+                continue;
+            }
+            let old_offset = span.end.offset + edit.len() - inserted;
+            let last = first + spans[first..].partition_point(|old| old.end.offset < old_offset);
+            if let Some(old) = spans.get(last).filter(|old| old.end.offset == old_offset) {
+                return Ok(Rescan {
+                    replaced: first..last + 1,
+                    spans: new_spans,
+                    old: old.end,
+                    new: span.end,
+                });
+            }
+        }
+        Ok(Rescan {
+            replaced: first..spans.len(),
+            new: new_spans.last().map_or(start, |span| span.end),
+            old: spans.last().map_or(Position::start(), |span| span.end),
+            spans: new_spans,
+        })
+    }
+
+    /// Returns whether scanning for a match at the given byte offset looks at the text at or
+    /// after the given limit.
+    fn scan_reaches(&self, text: &str, from: usize, limit: usize) -> bool {
+        let mut state = 0;
+        for (offset, c) in text[from..].char_indices() {
+            if from + offset >= limit {
+                return true;
+            }
+            state = self.next(state, &c);
+            if self.is_empty_set(state) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl<'a, L, I: StateId> LineScanner<'a, '_, L, I> {
+    /// Sets how this scanner recovers when no match starts at the current position. Every
+    /// mismatch is still yielded, with the skipped characters as its span.
+    pub fn with_recovery(mut self, recovery: Recovery) -> Self {
+        self.recovery = recovery;
+        self
+    }
+
+    /// Returns the position of the next character to consume.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Returns the text that has not been consumed yet.
+    pub fn rest(&self) -> &'a str {
+        &self.text[self.position.offset..]
+    }
+
+    /// Consumes the given number of characters, to recover from a mismatch. Consuming
+    /// resumes scanning after a mismatch.
+    pub fn skip_chars(&mut self, count: usize) {
+        for c in self.rest().chars().take(count) {
+            self.position = self.position.advance(c);
+        }
+        self.failed = false;
+    }
+
+    /// Returns the length in characters of the longest non-empty match at the start of the
+    /// given text.
+    fn match_len(&self, text: &str) -> Option<usize> {
+        self.automaton
+            .longest_matching_prefix(text.chars())
+            .filter(|len| *len > 0)
+    }
+}
+
+impl<L, I: StateId> Iterator for LineScanner<'_, '_, L, I> {
+    type Item = Result<Span, Mismatch>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let rest = self.rest();
+        let found = rest.chars().next()?;
+        let start = self.position;
+        match self.match_len(rest) {
+            Some(len) => {
+                self.position = rest.chars().take(len).fold(start, Position::advance);
+                Some(Ok(Span {
+                    start,
+                    end: self.position,
+                }))
+            }
+            None => {
+                let skipped = rest.chars().skip(1);
+                self.position = match self.recovery {
+                    Recovery::Stop => {
+                        self.failed = true;
+                        start
+                    }
+                    Recovery::SkipChar => start.advance(found),
+                    Recovery::SkipToMatch => {
+                        let mut end = start.advance(found);
+                        for c in skipped {
+                            if self.match_len(&self.text[end.offset..]).is_some() {
+                                break;
+                            }
+                            end = end.advance(c);
+                        }
+                        end
+                    }
+                    Recovery::SkipToSentinel(sentinel) => skipped
+                        .take_while(|c| !sentinel(*c))
+                        .fold(start.advance(found), Position::advance),
+                };
+                Some(Err(Mismatch {
+                    position: start,
+                    found,
+                    end: self.position,
+                }))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    use super::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<char>>;
+
+    fn position(offset: usize, line: usize, column: usize) -> Position {
+        Position {
+            offset,
+            line,
+            column,
+        }
+    }
+
+    #[test]
+    fn test_line_scanner() {
+        let word = ('a'.s() | 'b'.s() | 'é'.s()).c();
+        let space = (' '.s() | '\n'.s()).c();
+        let regex: AscRegex = [word.clone(), space].r() & ![].r();
+        let fa = regex.to_automaton();
+        let text = "ab é\nba\n\nb";
+        let spans = fa
+            .line_scanner(text)
+            .collect::<Result<Vec<_>, _>>()
+            .expect("no mismatch");
+        let expected = vec![
+            Span {
+                start: position(0, 1, 1),
+                end: position(3, 1, 4),
+            },
+            Span {
+                start: position(3, 1, 4),
+                end: position(6, 2, 1),
+            },
+            Span {
+                start: position(6, 2, 1),
+                end: position(10, 4, 1),
+            },
+            Span {
+                start: position(10, 4, 1),
+                end: position(11, 4, 2),
+            },
+        ];
+        assert_eq!(expected, spans);
+        assert_eq!("é\n", &text[spans[1].range()]);
+    }
+
+    #[test]
+    fn test_rescan() {
+        let word = ('a'.s() | 'b'.s()).c();
+        let space = (' '.s() | '\n'.s()).c();
+        let regex: AscRegex = [word, space].r() & ![].r();
+        let fa = regex.to_automaton().trim();
+        let text = "ab ba\nb aa\nbb a";
+        let spans = fa
+            .line_scanner(text)
+            .collect::<Result<Vec<_>, _>>()
+            .expect("no mismatch");
+        let tests = vec![
+            (3..5, "b", 0..2),
+            (3..5, "b\nab ", 0..2),
+            (0..0, "a", 0..1),
+            (2..3, "", 0..2),
+            (9..9, "\n\n", 3..4),
+            (14..15, "b a", 4..6),
+            (15..15, "b", 5..6),
+        ];
+        for (edit, replacement, replaced) in tests {
+            let edited = [&text[..edit.start], replacement, &text[edit.end..]].concat();
+            let rescan = fa
+                .rescan(&edited, &spans, edit.clone(), replacement.len())
+                .expect("no mismatch");
+            assert_eq!(replaced, rescan.replaced, "{:?}", edited);
+            let patched = spans[..rescan.replaced.start]
+                .iter()
+                .copied()
+                .chain(rescan.spans.iter().copied())
+                .chain(
+                    spans[rescan.replaced.end..]
+                        .iter()
+                        .map(|span| rescan.shift(*span)),
+                )
+                .collect::<Vec<_>>();
+            let expected = fa
+                .line_scanner(&edited)
+                .collect::<Result<Vec<_>, _>>()
+                .expect("no mismatch");
+            assert_eq!(expected, patched, "{:?}", edited);
+        }
+    }
+
+    #[test]
+    fn test_rescan_lookahead() {
+        let regex: AscRegex =
+            ['a'.s(), 'b'.s()].r() | ['a'.s(), 'b'.s(), 'c'.s(), 'd'.s()].r() | 'c'.s();
+        let fa = regex.to_automaton().trim();
+        let spans = fa
+            .line_scanner("abc")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("no mismatch");
+        assert_eq!(2, spans.len());
+        let rescan = fa.rescan("abcd", &spans, 3..3, 1).expect("no mismatch");
+        assert_eq!(0..2, rescan.replaced);
+        assert_eq!(
+            vec![Span {
+                start: position(0, 1, 1),
+                end: position(4, 1, 5),
+            }],
+            rescan.spans
+        );
+        let mismatch = Mismatch {
+            position: position(2, 1, 3),
+            found: 'x',
+            end: position(2, 1, 3),
+        };
+        assert_eq!(Err(mismatch), fa.rescan("abxc", &spans, 2..2, 1));
+
+        // the scan of the first span looks past the spans after it
+        let regex: AscRegex = 'x'.s() | 'y'.s() | 'z'.s() | ['x'.s(), 'y'.s().c(), 'z'.s()].r();
+        let fa = regex.to_automaton().trim();
+        let spans = fa
+            .line_scanner("xyyy")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("no mismatch");
+        assert_eq!(4, spans.len());
+        let rescan = fa.rescan("xyyyz", &spans, 4..4, 1).expect("no mismatch");
+        assert_eq!(0..4, rescan.replaced);
+        assert_eq!(
+            vec![Span {
+                start: position(0, 1, 1),
+                end: position(5, 1, 6),
+            }],
+            rescan.spans
+        );
+    }
+
+    #[test]
+    fn test_line_scanner_mismatch() {
+        let regex: AscRegex = ('a'.s() | '\n'.s()).c() & ![].r();
+        let fa = regex.to_automaton();
+        let mut scanner = fa.line_scanner("a\naxa");
+        assert!(matches!(scanner.next(), Some(Ok(_))));
+        let mismatch = Mismatch {
+            position: position(3, 2, 2),
+            found: 'x',
+            end: position(3, 2, 2),
+        };
+        assert_eq!(Some(Err(mismatch)), scanner.next());
+        assert_eq!(None, scanner.next());
+        assert_eq!("unexpected 'x' at line 2, column 2", mismatch.to_string());
+
+        scanner.skip_chars(1);
+        assert_eq!(position(4, 2, 3), scanner.position());
+        assert_eq!(
+            Some(Ok(Span {
+                start: position(4, 2, 3),
+                end: position(5, 2, 4),
+            })),
+            scanner.next()
+        );
+        assert_eq!(None, scanner.next());
+    }
+
+    #[test]
+    fn test_line_scanner_recovery() {
+        let word = ('a'.s() | 'b'.s()).c();
+        let regex: AscRegex = [word, ' '.s().c()].r() & ![].r();
+        let fa = regex.to_automaton().trim();
+        let text = "ab x?y ba xyb";
+        let tests = vec![
+            (Recovery::Stop, vec![Ok("ab "), Err(("", 'x'))]),
+            (
+                Recovery::SkipChar,
+                vec![
+                    Ok("ab "),
+                    Err(("x", 'x')),
+                    Err(("?", '?')),
+                    Err(("y", 'y')),
+                    Ok(" "),
+                    Ok("ba "),
+                    Err(("x", 'x')),
+                    Err(("y", 'y')),
+                    Ok("b"),
+                ],
+            ),
+            (
+                Recovery::SkipToMatch,
+                vec![
+                    Ok("ab "),
+                    Err(("x?y", 'x')),
+                    Ok(" "),
+                    Ok("ba "),
+                    Err(("xy", 'x')),
+                    Ok("b"),
+                ],
+            ),
+            (
+                Recovery::SkipToSentinel(char::is_whitespace),
+                vec![
+                    Ok("ab "),
+                    Err(("x?y", 'x')),
+                    Ok(" "),
+                    Ok("ba "),
+                    Err(("xyb", 'x')),
+                ],
+            ),
+        ];
+        for (recovery, expected) in tests {
+            let tokens = fa
+                .line_scanner(text)
+                .with_recovery(recovery)
+                .map(|token| match token {
+                    Ok(span) => Ok(&text[span.range()]),
+                    Err(mismatch) => Err((&text[mismatch.span().range()], mismatch.found)),
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(expected, tokens, "{:?}", recovery);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Report and reduce the memory used by finite automata.
+
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::State;
+use crate::automaton::StateId;
+use crate::builder::ApproximatelySimilarCanonical;
+use crate::builder::Builder;
+use crate::builder::Regex;
+use crate::product::eliminate_states;
+use crate::Alphabet;
+
+type Asc<S> = ApproximatelySimilarCanonical<S>;
+
+/// An estimate of the memory used by a [`FiniteAutomaton`], in bytes.
+///
+/// Memory owned by symbols and labels, such as the contents of `String` symbols, is not
+/// included.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MemoryUsage {
+    /// The memory used by the states themselves, including their labels.
+    pub states: usize,
+    /// The memory used by the transitions of the states.
+    pub transitions: usize,
+    /// The memory used by the nodes of the residual regular expressions of the states.
+    pub regexes: usize,
+}
+
+impl MemoryUsage {
+    /// Returns the total memory used by the automaton.
+    pub fn total(&self) -> usize {
+        self.states + self.transitions + self.regexes
+    }
+}
+
+impl<S: Alphabet, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Returns an estimate of the memory used by this automaton.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let node_size = size_of::<Regex<ApproximatelySimilarCanonical<S>>>();
+        MemoryUsage {
+            states: self.states.capacity() * size_of::<State<S, L, I>>(),
+            transitions: self
+                .states
+                .iter()
+                .map(|state| state.transitions.heap_size())
+                .sum(),
+            regexes: self
+                .states
+                .iter()
+                .filter_map(|state| state.regex.as_ref())
+                .map(|regex| (regex.size() - 1) * node_size)
+                .sum(),
+        }
+    }
+
+    /// Returns this automaton without the residual regular expressions of its states, which
+    /// can use more memory than the transitions for large automata.
+    ///
+    /// Matching is not affected, but [`Matcher::regex`](crate::Matcher::regex) and
+    /// [`FiniteAutomaton::label_states`] cannot be used afterwards. States with the empty set
+    /// as their regular expression keep it, so that they are still recognized as dead states.
+    pub fn drop_regexes(mut self) -> Self {
+        for state in &mut self.states {
+            if !matches!(state.regex, Some(Regex::EmptySet)) {
+                state.regex = None;
+            }
+        }
+        self
+    }
+
+    /// Returns this automaton with the residual regular expressions of its states that are
+    /// not known reconstructed from the transitions. These are the regular expressions that
+    /// were dropped using [`FiniteAutomaton::drop_regexes`], and those of automata that were
+    /// not built from a regular expression, such as by [`FiniteAutomaton::from_table_str`].
+    ///
+    /// The regular expressions are reconstructed by state elimination, which results in
+    /// regular expressions with the same languages, but not necessarily the same structure.
+    /// This is expensive for large automata.
+    pub fn reconstruct_regexes(mut self) -> Self {
+        if self.states.iter().all(|state| state.regex.is_some()) {
+            return self;
+        }
+        let symbols = self.symbols().into_iter().cloned().collect_vec();
+        let accepting = (0..self.state_count())
+            .map(|state| self.is_accepting(state))
+            .collect_vec();
+        let other_symbol = other_symbol(&symbols);
+        let mut edges: HashMap<(usize, usize), Regex<Asc<S>>> = HashMap::new();
+        for from in 0..self.state_count() {
+            let labels = symbols
+                .iter()
+                .map(|symbol| (Asc::symbol(symbol.clone()), self.next(from, symbol)))
+                .chain(std::iter::once((
+                    other_symbol.clone(),
+                    self.default_transition(from),
+                )));
+            for (label, to) in labels {
+                let edge = edges.remove(&(from, to)).unwrap_or_else(Asc::empty_set);
+                edges.insert((from, to), Asc::or(edge, label));
+            }
+        }
+
+        for (idx, state) in self.states.iter_mut().enumerate() {
+            if state.regex.is_none() {
+                state.regex = Some(eliminate_states(idx, accepting.clone(), edges.clone()));
+            }
+        }
+        self
+    }
+}
+
+/// Returns a regular expression matching any single symbol except the given symbols.
+fn other_symbol<S: Alphabet>(symbols: &[S]) -> Regex<Asc<S>> {
+    Asc::and(
+        Regex::single_symbol(),
+        Asc::complement(Regex::any(symbols.iter().cloned())),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+
+    #[test]
+    fn test_memory_usage() {
+        let regex: AscRegex = [1.s(), (2.s() | 3.s()).c(), 4.s()].r() & !(2.s() | 3.s()).c();
+        let fa = regex.to_automaton();
+        let usage = fa.memory_usage();
+        assert!(usage.states > 0);
+        assert!(usage.transitions > 0);
+        assert!(usage.regexes > 0);
+        assert_eq!(
+            usage.states + usage.transitions + usage.regexes,
+            usage.total()
+        );
+
+        let dropped = fa.clone().drop_regexes();
+        assert_eq!(
+            usage.states + usage.transitions,
+            dropped.memory_usage().total()
+        );
+        for word in [vec![], vec![1, 4], vec![1, 2, 3, 4], vec![2], vec![1, 5, 4]] {
+            assert_eq!(
+                fa.to_matcher().next_iter(&word),
+                dropped.to_matcher().next_iter(&word)
+            );
+            assert_eq!(
+                fa.longest_matching_prefix(&word),
+                dropped.longest_matching_prefix(&word)
+            );
+        }
+    }
+
+    #[test]
+    fn test_reconstruct_regexes() {
+        let regex: AscRegex = [1.s(), (2.s() | 3.s()).c(), 4.s()].r() & !(2.s() | 3.s()).c();
+        let fa = regex.to_automaton();
+        let reconstructed = fa.clone().drop_regexes().reconstruct_regexes();
+        for state in 0..fa.state_count() {
+            let expected = fa.states[state].regex.as_ref().unwrap();
+            let actual = reconstructed.states[state].regex.as_ref().unwrap();
+            assert!(
+                expected.is_equivalent_to(actual),
+                "{} and {}",
+                expected,
+                actual
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "regular expression was dropped")]
+    fn test_dropped_regex() {
+        let regex: AscRegex = [1.s(), 2.s()].r();
+        regex.to_automaton().drop_regexes().to_matcher().regex();
+    }
+}
@@ -0,0 +1,222 @@
+//! Minimize finite automata and bring them into canonical form.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use itertools::Itertools;
+
+use crate::automaton::state_id;
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::State;
+use crate::automaton::StateId;
+use crate::automaton::Transitions;
+use crate::Alphabet;
+
+impl<S: Alphabet, L: Eq + Hash, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Returns an equivalent automaton with the minimal number of states, by merging states
+    /// with the same residual language and the same label.
+    ///
+    /// Merged states keep the regular expression and label of the state with the lowest
+    /// index. The start state remains the first state.
+    pub fn minimize(self) -> Self {
+        let symbols = self.symbols();
+        let blocks = self.equivalence_classes(&symbols);
+        let block_count = blocks.iter().max().map_or(0, |max| max + 1);
+
+        let mut rows = vec![None; block_count];
+        for (idx, block) in blocks.iter().enumerate() {
+            if rows[*block].is_some() {
+                continue;
+            }
+            let default_transition = blocks[self.default_transition(idx)];
+            let transitions = Transitions::new(
+                self.states[idx].transitions.representation(),
+                symbols
+                    .iter()
+                    .map(|symbol| (symbol, blocks[self.next(idx, *symbol)]))
+                    .filter(|(_, next)| *next != default_transition)
+                    .map(|(symbol, next)| ((*symbol).clone(), state_id(next))),
+            );
+            rows[*block] = Some((transitions, state_id(default_transition)));
+        }
+
+        let mut states = Vec::with_capacity(block_count);
+        for (idx, state) in self.states.into_iter().enumerate() {
+            if blocks[idx] < states.len() {
+                continue;
+            }
+            let (transitions, default_transition) =
+                rows[blocks[idx]].take().expect("row for every block");
+            states.push(State {
+                regex: state.regex,
+                accepting: state.accepting,
+                universal: state.universal,
+                transitions,
+                default_transition,
+                label: state.label,
+            });
+        }
+
+        FiniteAutomaton { states }
+    }
+
+    /// Returns the minimal automaton, with states numbered in breadth-first order from the
+    /// start state, visiting transitions in symbol order followed by the default transition.
+    ///
+    /// Automata for the same language, with the same labels, have the same canonical form,
+    /// except for the regular expressions stored in the states.
+    pub fn canonicalize(self) -> Self {
+        self.minimize().renumber_breadth_first()
+    }
+
+    /// Returns the index of the equivalence class of every state. Classes are numbered in order
+    /// of their first state.
+    pub(crate) fn equivalence_classes(&self, symbols: &[&S]) -> Vec<usize> {
+        let mut ids = HashMap::new();
+        let mut blocks = self
+            .states
+            .iter()
+            .map(|state| {
+                let len = ids.len();
+                *ids.entry((state.accepting, &state.label)).or_insert(len)
+            })
+            .collect_vec();
+        let mut block_count = ids.len();
+        loop {
+            let mut ids = HashMap::new();
+            let refined = (0..self.states.len())
+                .map(|idx| {
+                    let signature = symbols
+                        .iter()
+                        .map(|symbol| blocks[self.next(idx, *symbol)])
+                        .chain(std::iter::once(blocks[self.default_transition(idx)]))
+                        .collect_vec();
+                    let len = ids.len();
+                    *ids.entry((blocks[idx], signature)).or_insert(len)
+                })
+                .collect_vec();
+            blocks = refined;
+            if ids.len() == block_count {
+                return blocks;
+            }
+            block_count = ids.len();
+        }
+    }
+}
+
+impl<S: Alphabet, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Returns all symbols that have explicit transitions, in order.
+    pub(crate) fn symbols(&self) -> Vec<&S> {
+        self.states
+            .iter()
+            .flat_map(|state| state.transitions.iter().map(|(symbol, _)| symbol))
+            .sorted()
+            .dedup()
+            .collect()
+    }
+
+    fn renumber_breadth_first(self) -> Self {
+        let mut mapping = vec![None; self.states.len()];
+        let mut order = Vec::new();
+        let mut queue = VecDeque::from([0]);
+        mapping[0] = Some(0);
+        while let Some(idx) = queue.pop_front() {
+            order.push(idx);
+            let state = &self.states[idx];
+            let successors = state
+                .transitions
+                .iter()
+                .sorted_by(|(left, _), (right, _)| left.cmp(right))
+                .map(|(_, next)| next.index())
+                .chain(std::iter::once(state.default_transition.index()));
+            for next in successors {
+                if mapping[next].is_none() {
+                    mapping[next] = Some(order.len() + queue.len());
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let mut states = self.states.into_iter().map(Some).collect_vec();
+        let states = order
+            .into_iter()
+            .map(|idx| {
+                let state = states[idx].take().expect("state is visited once");
+                State {
+                    regex: state.regex,
+                    accepting: state.accepting,
+                    universal: state.universal,
+                    transitions: Transitions::new(
+                        state.transitions.representation(),
+                        state.transitions.into_iter().map(|(symbol, next)| {
+                            (
+                                symbol,
+                                state_id(mapping[next.index()].expect("state is reachable")),
+                            )
+                        }),
+                    ),
+                    default_transition: state_id(
+                        mapping[state.default_transition.index()].expect("state is reachable"),
+                    ),
+                    label: state.label,
+                }
+            })
+            .collect();
+
+        FiniteAutomaton { states }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<u8>>;
+
+    #[test]
+    fn test_minimize() {
+        let tests: Vec<(AscRegex, usize)> = vec![
+            (().r(), 1),
+            (!().r(), 1),
+            ((1.s() | 2.s()).c(), 2),
+            ([1.s().c(), 1.s().c()].r(), 2),
+            ((1.s() & 2.s()) | [3.s(), 4.s()].r(), 4),
+        ];
+        for (regex, state_count) in tests {
+            let fa = regex.to_automaton().minimize();
+            assert_eq!(state_count, fa.state_count(), "states of {}", regex);
+        }
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let tests: Vec<(AscRegex, AscRegex)> = vec![
+            ((1.s() | 2.s()).c(), (1.s().c() + 2.s().c()).c()),
+            (1.s() | (2.s() & 3.s()), 1.s()),
+            (
+                [1.s(), 2.s()].r() | [1.s(), 3.s()].r(),
+                [1.s(), (3.s() | 2.s())].r(),
+            ),
+            (!!1.s().c(), [1.s().c(), 1.s().c()].r()),
+        ];
+        for (left, right) in tests {
+            assert_eq!(
+                left.to_automaton()
+                    .canonicalize()
+                    .to_static_bytes()
+                    .expect("fits"),
+                right
+                    .to_automaton()
+                    .canonicalize()
+                    .to_static_bytes()
+                    .expect("fits"),
+                "canonical forms of {} and {}",
+                left,
+                right
+            );
+        }
+    }
+}
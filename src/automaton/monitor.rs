@@ -0,0 +1,123 @@
+//! Monitor input with three-valued verdicts, as in runtime verification.
+
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::Matcher;
+use crate::automaton::StateId;
+use crate::Alphabet;
+
+/// The verdict of a [`Monitor`] on the input consumed so far.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Verdict {
+    /// Every continuation of the input is accepted.
+    Accepted,
+    /// No continuation of the input is accepted.
+    Rejected,
+    /// Some continuations of the input are accepted, and some are not.
+    Inconclusive,
+}
+
+/// A matcher that reports a [`Verdict`] after consuming input, returned by
+/// [`FiniteAutomaton::to_monitor`].
+pub struct Monitor<'a, S: Alphabet, L = (), I: StateId = usize> {
+    matcher: Matcher<'a, S, L, I>,
+    live: Vec<bool>,
+}
+
+impl<S: Alphabet, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Returns a monitor for this automaton. The states from which an accepting state can be
+    /// reached are computed once, so that every verdict takes constant time.
+    pub fn to_monitor(&self) -> Monitor<'_, S, L, I> {
+        Monitor {
+            matcher: self.to_matcher(),
+            live: self.live_states(),
+        }
+    }
+}
+
+impl<S: Alphabet, L, I: StateId> Monitor<'_, S, L, I> {
+    /// Consume the given symbol and return the verdict on the input consumed so far.
+    pub fn next<Q>(&mut self, symbol: &Q) -> Verdict
+    where
+        S: Borrow<Q>,
+        Q: Hash + Ord + ?Sized,
+    {
+        self.matcher.next(symbol);
+        self.verdict()
+    }
+
+    /// Consume the given symbols and return the verdict on the input consumed so far.
+    ///
+    /// Symbols are not consumed beyond the first one after which the verdict is final.
+    pub fn next_iter<T>(&mut self, symbols: impl IntoIterator<Item = T>) -> Verdict
+    where
+        T: Borrow<S>,
+    {
+        for symbol in symbols {
+            if self.verdict() != Verdict::Inconclusive {
+                break;
+            }
+            self.matcher.next(symbol.borrow());
+        }
+        self.verdict()
+    }
+
+    /// Returns the verdict on the input consumed so far. Once the verdict is
+    /// [`Verdict::Accepted`] or [`Verdict::Rejected`], consuming more input does not change
+    /// it.
+    pub fn verdict(&self) -> Verdict {
+        let state = self.matcher.state();
+        if self.matcher.will_always_accept() {
+            Verdict::Accepted
+        } else if !self.live[state] {
+            Verdict::Rejected
+        } else {
+            Verdict::Inconclusive
+        }
+    }
+
+    /// Returns whether the input consumed so far is accepted, which may be the case for an
+    /// inconclusive verdict.
+    pub fn is_accepting(&self) -> bool {
+        self.matcher.fa.is_accepting(self.matcher.state())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    use super::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+
+    #[test]
+    fn test_monitor() {
+        let regex: AscRegex = [1.s(), 2.s(), !().r()].r() | 2.s().c();
+        let fa = regex.to_automaton();
+        let tests = vec![
+            (vec![], Verdict::Inconclusive, true),
+            (vec![1], Verdict::Inconclusive, false),
+            (vec![1, 2], Verdict::Accepted, true),
+            (vec![1, 2, 3], Verdict::Accepted, true),
+            (vec![1, 3], Verdict::Rejected, false),
+            (vec![1, 3, 2], Verdict::Rejected, false),
+            (vec![2, 2], Verdict::Inconclusive, true),
+            (vec![3], Verdict::Rejected, false),
+        ];
+        for (word, verdict, accepting) in tests {
+            let mut monitor = fa.to_monitor();
+            assert_eq!(verdict, monitor.next_iter(&word), "{:?}", word);
+            let mut monitor = fa.to_monitor();
+            for symbol in &word {
+                monitor.next(symbol);
+            }
+            assert_eq!(verdict, monitor.verdict(), "{:?}", word);
+            assert_eq!(accepting, monitor.is_accepting(), "{:?}", word);
+        }
+    }
+}
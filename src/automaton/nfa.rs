@@ -0,0 +1,254 @@
+//! Nondeterministic finite automata, and their conversion to deterministic automata.
+
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::State;
+use crate::automaton::Transitions;
+use crate::Alphabet;
+
+/// A nondeterministic finite automaton, with a set of target states for every symbol, and
+/// optional epsilon transitions that are taken without consuming a symbol.
+///
+/// The start state is state zero, which is created by [`Nfa::new`]. Use [`Nfa::determinize`]
+/// to convert it into a [`FiniteAutomaton`] for matching.
+#[derive(Clone, Debug)]
+pub struct Nfa<S: Alphabet> {
+    states: Vec<NfaState<S>>,
+}
+
+#[derive(Clone, Debug)]
+struct NfaState<S: Alphabet> {
+    accepting: bool,
+    transitions: BTreeMap<S, BTreeSet<usize>>,
+    epsilon_transitions: BTreeSet<usize>,
+}
+
+impl<S: Alphabet> NfaState<S> {
+    fn new(accepting: bool) -> Self {
+        Self {
+            accepting,
+            transitions: BTreeMap::new(),
+            epsilon_transitions: BTreeSet::new(),
+        }
+    }
+}
+
+impl<S: Alphabet> Default for Nfa<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Alphabet> Nfa<S> {
+    /// Returns an automaton with a non-accepting start state, and no transitions.
+    pub fn new() -> Self {
+        Self {
+            states: vec![NfaState::new(false)],
+        }
+    }
+
+    /// Adds a state, and returns its index.
+    pub fn add_state(&mut self, accepting: bool) -> usize {
+        self.states.push(NfaState::new(accepting));
+        self.states.len() - 1
+    }
+
+    /// Sets whether the given state is accepting.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state does not exist.
+    pub fn set_accepting(&mut self, state: usize, accepting: bool) {
+        self.states[state].accepting = accepting;
+    }
+
+    /// Adds a transition for the given symbol.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either state does not exist.
+    pub fn add_transition(&mut self, from: usize, symbol: S, to: usize) {
+        assert!(to < self.states.len());
+        self.states[from]
+            .transitions
+            .entry(symbol)
+            .or_default()
+            .insert(to);
+    }
+
+    /// Adds an epsilon transition, which is taken without consuming a symbol.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either state does not exist.
+    pub fn add_epsilon_transition(&mut self, from: usize, to: usize) {
+        assert!(to < self.states.len());
+        self.states[from].epsilon_transitions.insert(to);
+    }
+
+    /// Returns the number of states.
+    pub fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Returns whether the given symbols are accepted by this automaton, by tracking the set
+    /// of states it can be in.
+    pub fn is_match<T>(&self, symbols: impl IntoIterator<Item = T>) -> bool
+    where
+        T: Borrow<S>,
+    {
+        let mut current = self.epsilon_closure([0]);
+        for symbol in symbols {
+            current = self.epsilon_closure(current.iter().flat_map(|state| {
+                self.states[*state]
+                    .transitions
+                    .get(symbol.borrow())
+                    .into_iter()
+                    .flatten()
+                    .copied()
+            }));
+        }
+        current.iter().any(|state| self.states[*state].accepting)
+    }
+
+    /// Returns an equivalent deterministic automaton, using the subset construction. Only the
+    /// sets of states that are reachable from the start state become states.
+    ///
+    /// The regular expressions of the states are not known, except for the empty set of the
+    /// states that cannot reach an accepting state, and can be reconstructed using
+    /// [`FiniteAutomaton::reconstruct_regexes`].
+    pub fn determinize(&self) -> FiniteAutomaton<S> {
+        let symbols = self
+            .states
+            .iter()
+            .flat_map(|state| state.transitions.keys())
+            .collect::<BTreeSet<_>>();
+
+        let start = self.epsilon_closure([0]);
+        let mut ids = HashMap::from([(start.clone(), 0)]);
+        let mut subsets = vec![start];
+        let mut get_or_insert = |subset: BTreeSet<usize>, subsets: &mut Vec<_>| {
+            *ids.entry(subset.clone()).or_insert_with(|| {
+                subsets.push(subset);
+                subsets.len() - 1
+            })
+        };
+
+        let mut states = Vec::new();
+        while let Some(subset) = subsets.get(states.len()).cloned() {
+            let accepting = subset.iter().any(|state| self.states[*state].accepting);
+            let transitions = symbols
+                .iter()
+                .filter_map(|symbol| {
+                    let next = self.epsilon_closure(subset.iter().flat_map(|state| {
+                        self.states[*state]
+                            .transitions
+                            .get(*symbol)
+                            .into_iter()
+                            .flatten()
+                            .copied()
+                    }));
+                    (!next.is_empty()).then(|| {
+                        let next = get_or_insert(next, &mut subsets);
+                        ((*symbol).clone(), next)
+                    })
+                })
+                .collect();
+            states.push(State {
+                regex: None,
+                accepting,
+                universal: false,
+                transitions: Transitions::Hashed(transitions),
+                default_transition: get_or_insert(BTreeSet::new(), &mut subsets),
+                label: (),
+            });
+        }
+        FiniteAutomaton { states }
+            .mark_dead_states()
+            .mark_universal_states()
+    }
+
+    /// Returns the given states, and the states reachable from them by epsilon transitions.
+    fn epsilon_closure(&self, states: impl IntoIterator<Item = usize>) -> BTreeSet<usize> {
+        let mut closure = BTreeSet::new();
+        let mut stack = states.into_iter().collect::<Vec<_>>();
+        while let Some(state) = stack.pop() {
+            if closure.insert(state) {
+                stack.extend(self.states[state].epsilon_transitions.iter().copied());
+            }
+        }
+        closure
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    use super::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+
+    #[test]
+    fn test_determinize() {
+        // (1 | 2)* 1 2*, with an epsilon cycle
+        let mut nfa = Nfa::new();
+        let last = nfa.add_state(true);
+        let after = nfa.add_state(false);
+        let back = nfa.add_state(false);
+        nfa.add_transition(0, 1, 0);
+        nfa.add_transition(0, 2, 0);
+        nfa.add_transition(0, 1, last);
+        nfa.add_epsilon_transition(last, after);
+        nfa.add_transition(after, 2, back);
+        nfa.add_epsilon_transition(back, after);
+        nfa.add_epsilon_transition(after, back);
+        nfa.set_accepting(back, true);
+        assert_eq!(4, nfa.state_count());
+
+        let expected: AscRegex = [(1.s() | 2.s()).c(), 1.s(), 2.s().c()].r();
+        let fa = nfa.determinize();
+        assert_eq!(0, fa.memory_usage().regexes);
+        assert!(fa
+            .clone()
+            .reconstruct_regexes()
+            .to_matcher()
+            .regex()
+            .is_equivalent_to(&expected));
+        let words = vec![
+            vec![],
+            vec![1],
+            vec![2],
+            vec![1, 2],
+            vec![1, 2, 2],
+            vec![2, 1, 1],
+            vec![2, 1, 2, 1],
+            vec![1, 3],
+        ];
+        for word in words {
+            assert_eq!(
+                expected.is_match(&word),
+                fa.to_matcher().next_iter(&word),
+                "{:?}",
+                word
+            );
+        }
+        assert_eq!(
+            expected.to_automaton().minimize().state_count(),
+            fa.minimize().state_count()
+        );
+    }
+
+    #[test]
+    fn test_determinize_empty() {
+        let fa = Nfa::<usize>::new().determinize();
+        assert!(!fa.to_matcher().next_iter(Vec::<usize>::new()));
+        assert_eq!(&AscRegex::EmptySet, fa.to_matcher().regex());
+    }
+}
@@ -0,0 +1,176 @@
+//! Construct finite automata from explicit transitions.
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::automaton::FiniteAutomaton;
+use crate::Alphabet;
+
+/// Errors that can occur when constructing an automaton from its parts.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AutomatonPartsError {
+    /// The automaton has no states.
+    NoStates,
+    /// The given state is used, but does not exist.
+    InvalidState(usize),
+    /// The given state has more than one transition for the same symbol.
+    Nondeterministic(usize),
+}
+
+impl std::fmt::Display for AutomatonPartsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoStates => write!(f, "no states"),
+            Self::InvalidState(state) => write!(f, "invalid state {}", state),
+            Self::Nondeterministic(state) => {
+                write!(
+                    f,
+                    "state {} has more than one transition for a symbol",
+                    state
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for AutomatonPartsError {}
+
+impl<S: Alphabet> FiniteAutomaton<S> {
+    /// Constructs an automaton from the given number of states, transitions given as source
+    /// state, symbol, and target state, accepting states, and start state. This allows
+    /// automata produced by other tools to be minimized, combined, and converted to regular
+    /// expressions.
+    ///
+    /// The start state becomes state zero, and the other states keep their order. Symbols
+    /// without a transition lead to a state that accepts nothing, which is added as the last
+    /// state. The regular expressions of the states are not known, except for the empty set of
+    /// the states that accept nothing, and can be reconstructed using
+    /// [`FiniteAutomaton::reconstruct_regexes`].
+    pub fn from_parts(
+        state_count: usize,
+        transitions: impl IntoIterator<Item = (usize, S, usize)>,
+        accepting: impl IntoIterator<Item = usize>,
+        start: usize,
+    ) -> Result<Self, AutomatonPartsError> {
+        if state_count == 0 {
+            return Err(AutomatonPartsError::NoStates);
+        }
+        let check = |state: usize| {
+            if state < state_count {
+                Ok(state)
+            } else {
+                Err(AutomatonPartsError::InvalidState(state))
+            }
+        };
+        check(start)?;
+        let renumber = |state: usize| match state.cmp(&start) {
+            std::cmp::Ordering::Less => state + 1,
+            std::cmp::Ordering::Equal => 0,
+            std::cmp::Ordering::Greater => state,
+        };
+
+        let mut targets = HashMap::new();
+        let mut symbols = BTreeSet::new();
+        for (from, symbol, to) in transitions {
+            let (from, to) = (renumber(check(from)?), renumber(check(to)?));
+            if targets
+                .insert((from, symbol.clone()), to)
+                .is_some_and(|other| other != to)
+            {
+                return Err(AutomatonPartsError::Nondeterministic(from));
+            }
+            symbols.insert(symbol);
+        }
+        let mut rows = vec![(false, Vec::new()); state_count];
+        for state in accepting {
+            rows[renumber(check(state)?)].0 = true;
+        }
+
+        let dead = state_count;
+        rows.push((false, Vec::new()));
+        for (from, (_, row)) in rows.iter_mut().enumerate() {
+            *row = symbols
+                .iter()
+                .map(|symbol| *targets.get(&(from, symbol.clone())).unwrap_or(&dead))
+                .chain(std::iter::once(dead))
+                .collect_vec();
+        }
+        Ok(Self::from_rows(symbols.into_iter().collect(), rows))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    use super::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+
+    #[test]
+    fn test_from_parts() {
+        // 2 --1--> 0 --2--> 1 --2--> 3 --2--> 1, accepting 0 and 3
+        let transitions = vec![(2, 1, 0), (0, 2, 1), (1, 2, 3), (3, 2, 1)];
+        let fa = FiniteAutomaton::from_parts(4, transitions, [0, 3], 2).expect("valid parts");
+        let expected: AscRegex = [1.s(), [2.s(), 2.s()].r().c()].r();
+        assert_eq!(5, fa.state_count());
+        assert!(fa
+            .clone()
+            .reconstruct_regexes()
+            .to_matcher()
+            .regex()
+            .is_equivalent_to(&expected));
+        for word in [
+            vec![],
+            vec![1],
+            vec![1, 2],
+            vec![1, 2, 2],
+            vec![2, 2],
+            vec![1, 3],
+        ] {
+            assert_eq!(
+                expected.is_match(&word),
+                fa.to_matcher().next_iter(&word),
+                "{:?}",
+                word
+            );
+        }
+        assert_eq!(4, fa.minimize().state_count());
+    }
+
+    #[test]
+    fn test_from_parts_errors() {
+        let tests = vec![
+            (0, vec![], vec![], 0, AutomatonPartsError::NoStates),
+            (1, vec![], vec![], 1, AutomatonPartsError::InvalidState(1)),
+            (
+                1,
+                vec![(0, 1, 2)],
+                vec![],
+                0,
+                AutomatonPartsError::InvalidState(2),
+            ),
+            (1, vec![], vec![3], 0, AutomatonPartsError::InvalidState(3)),
+            (
+                2,
+                vec![(0, 1, 0), (1, 1, 0), (1, 1, 1)],
+                vec![],
+                1,
+                AutomatonPartsError::Nondeterministic(0),
+            ),
+        ];
+        for (state_count, transitions, accepting, start, expected) in tests {
+            let result =
+                FiniteAutomaton::<usize>::from_parts(state_count, transitions, accepting, start);
+            assert_eq!(Some(expected), result.err());
+        }
+        assert_eq!(
+            "state 0 has more than one transition for a symbol",
+            AutomatonPartsError::Nondeterministic(0).to_string()
+        );
+    }
+}
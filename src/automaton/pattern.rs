@@ -0,0 +1,108 @@
+//! Search strings with automata using the standard string pattern API.
+
+use std::str::pattern::Pattern;
+use std::str::pattern::SearchStep;
+use std::str::pattern::Searcher;
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::StateId;
+
+/// Searches a string for the leftmost longest non-overlapping matches of an automaton.
+///
+/// An empty match is reported at most once at every position, so searching with an automaton
+/// that accepts the empty string yields an empty match between every pair of characters.
+pub struct AutomatonSearcher<'a, 'b, L, I: StateId> {
+    automaton: &'b FiniteAutomaton<char, L, I>,
+    haystack: &'a str,
+    position: usize,
+    allow_empty: bool,
+}
+
+impl<'b, L, I: StateId> Pattern for &'b FiniteAutomaton<char, L, I> {
+    type Searcher<'a> = AutomatonSearcher<'a, 'b, L, I>;
+
+    fn into_searcher(self, haystack: &str) -> Self::Searcher<'_> {
+        AutomatonSearcher {
+            automaton: self,
+            haystack,
+            position: 0,
+            allow_empty: true,
+        }
+    }
+}
+
+unsafe impl<'a, L, I: StateId> Searcher<'a> for AutomatonSearcher<'a, '_, L, I> {
+    fn haystack(&self) -> &'a str {
+        self.haystack
+    }
+
+    fn next(&mut self) -> SearchStep {
+        let start = self.position;
+        let rest = &self.haystack[start..];
+        match self.automaton.longest_matching_prefix(rest.chars()) {
+            Some(0) if self.allow_empty => {
+                self.allow_empty = false;
+                SearchStep::Match(start, start)
+            }
+            Some(len) if len > 0 => {
+                let end = rest
+                    .char_indices()
+                    .nth(len)
+                    .map_or(self.haystack.len(), |(offset, _)| start + offset);
+                self.position = end;
+                self.allow_empty = true;
+                SearchStep::Match(start, end)
+            }
+            _ => match rest.chars().next() {
+                Some(c) => {
+                    self.position += c.len_utf8();
+                    self.allow_empty = true;
+                    SearchStep::Reject(start, self.position)
+                }
+                None => SearchStep::Done,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<char>>;
+
+    #[test]
+    fn test_find() {
+        let regex: AscRegex = ['a'.s(), 'b'.s().c()].r();
+        let fa = regex.to_automaton();
+        assert_eq!(Some(2), "xyabbza".find(&fa));
+        assert_eq!(Some(4), "xyäabbza".find(&fa));
+        assert_eq!(None, "xyz".find(&fa));
+        assert!("xabz".contains(&fa));
+        assert!("abbz".starts_with(&fa));
+        assert!(!"xabz".starts_with(&fa));
+    }
+
+    #[test]
+    fn test_match_indices() {
+        let regex: AscRegex = ['a'.s(), 'b'.s().c()].r();
+        let fa = regex.to_automaton();
+        assert_eq!(
+            vec![(1, "abb"), (4, "a"), (7, "ab")],
+            "xabbaäab".match_indices(&fa).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_split() {
+        let separator: AscRegex = ' '.s() | ','.s();
+        let fa = [separator.clone(), separator.clone().c()]
+            .r()
+            .to_automaton();
+        assert_eq!(vec!["a", "ä", "c"], "a, ä c".split(&fa).collect::<Vec<_>>());
+        let fa = separator.c().to_automaton();
+        assert_eq!(vec!["", "a", "ä", ""], "aä".split(&fa).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,125 @@
+//! Skip ahead in byte inputs to positions where a match can start.
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::StateId;
+use crate::builder::Regex;
+
+/// The maximum number of distinct start bytes for which a prefilter is used.
+const MAX_START_BYTES: usize = 3;
+
+impl<L, I: StateId> FiniteAutomaton<u8, L, I> {
+    /// Returns whether any substring of the given bytes is accepted by this automaton.
+    ///
+    /// This behaves like [`FiniteAutomaton::is_match_anywhere`], but if matches can only start
+    /// with one of at most three bytes, it uses `memchr` to skip over input in which no match is
+    /// in progress. If all matches contain a [required factor](Regex::required_factor), inputs
+    /// that do not contain it are rejected without running the automaton.
+    pub fn is_match_anywhere_bytes(&self, haystack: &[u8]) -> bool {
+        if let Some(factor) = self.states[0]
+            .regex
+            .as_ref()
+            .and_then(Regex::required_factor)
+        {
+            if memchr::memmem::find(haystack, &factor).is_none() {
+                return false;
+            }
+        }
+        let Some(start_bytes) = self.start_bytes() else {
+            return self.is_match_anywhere(haystack);
+        };
+        let mut active = Vec::new();
+        let mut seen = vec![false; self.states.len()];
+        let mut position = 0;
+        while position < haystack.len() {
+            if active.is_empty() {
+                match find_byte(&start_bytes, &haystack[position..]) {
+                    Some(offset) => position += offset,
+                    None => return false,
+                }
+            }
+            let symbol = haystack[position];
+            let mut next_active = Vec::with_capacity(active.len() + 1);
+            for state in active.into_iter().chain(std::iter::once(0)) {
+                let next = self.next(state, &symbol);
+                if !seen[next] && !self.is_empty_set(next) {
+                    seen[next] = true;
+                    next_active.push(next);
+                }
+            }
+            for state in &next_active {
+                seen[*state] = false;
+            }
+            if next_active.iter().any(|state| self.is_accepting(*state)) {
+                return true;
+            }
+            active = next_active;
+            position += 1;
+        }
+        false
+    }
+
+    /// Returns the bytes that lead out of the start state to a state that is not the empty set,
+    /// if the start state does not accept and there are at most [`MAX_START_BYTES`] of them.
+    fn start_bytes(&self) -> Option<Vec<u8>> {
+        if self.is_accepting(0) {
+            return None;
+        }
+        let is_live = |state: usize| !self.is_empty_set(state);
+        if is_live(self.default_transition(0)) {
+            return None;
+        }
+        let start_bytes = self
+            .symbol_transitions(0)
+            .filter(|(_, next)| is_live(*next))
+            .map(|(symbol, _)| *symbol)
+            .collect::<Vec<_>>();
+        (start_bytes.len() <= MAX_START_BYTES).then_some(start_bytes)
+    }
+}
+
+/// Returns the position of the first occurrence of one of at most three bytes.
+pub(crate) fn find_byte(bytes: &[u8], haystack: &[u8]) -> Option<usize> {
+    match *bytes {
+        [] => None,
+        [first] => memchr::memchr(first, haystack),
+        [first, second] => memchr::memchr2(first, second, haystack),
+        [first, second, third] => memchr::memchr3(first, second, third, haystack),
+        _ => unreachable!("at most three bytes"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<u8>>;
+
+    #[test]
+    fn test_match_anywhere_bytes() {
+        let tests: Vec<(AscRegex, &[u8], bool)> = vec![
+            ([b'a'.s(), b'b'.s()].r(), b"xxaxxabx", true),
+            ([b'a'.s(), b'b'.s()].r(), b"xxaxxaxb", false),
+            ([b'x'.s().c(), b'a'.s(), b'b'.s()].r(), b"xxxxab", true),
+            ([b'x'.s().c(), b'a'.s(), b'b'.s()].r(), b"xxxxba", false),
+            ([b'a'.s(), b'b'.s()].r(), b"aab", true),
+            ([b'a'.s(), b'b'.s()].r(), b"", false),
+            ([(b'a'.s() | b'b'.s()), b'c'.s()].r(), b"xxxxbc", true),
+            ([(b'a'.s() | b'b'.s()), b'c'.s()].r(), b"xxxxcb", false),
+            ((b'a'.s() | b'b'.s() | b'c'.s() | b'd'.s()), b"xxxd", true),
+            ([b'a'.s(), !b'b'.s()].r(), b"xaxax", true),
+            ([b'a'.s(), !b'b'.s()].r(), b"xxxxxx", false),
+            ([].r(), b"", true),
+            (().r(), b"abc", false),
+            (!b'a'.s(), b"a", true),
+        ];
+        for (regex, haystack, expected) in tests {
+            let fa = regex.to_automaton();
+            assert_eq!(expected, fa.is_match_anywhere_bytes(haystack), "{}", regex);
+            assert_eq!(expected, fa.is_match_anywhere(haystack), "{}", regex);
+            let fa = fa.trim();
+            assert_eq!(expected, fa.is_match_anywhere_bytes(haystack), "{}", regex);
+        }
+    }
+}
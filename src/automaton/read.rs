@@ -0,0 +1,260 @@
+//! Scan byte streams with finite automata.
+
+use std::io::ErrorKind;
+use std::io::Read;
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::StateId;
+
+/// The number of bytes that is read from the underlying reader at once.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// A match yielded by a [`ReadScanner`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Token {
+    /// The byte offset of the match in the stream.
+    pub offset: usize,
+    /// The matched bytes.
+    pub bytes: Vec<u8>,
+}
+
+/// Errors that can occur when scanning a byte stream with a [`ReadScanner`].
+#[derive(Debug)]
+pub enum ReadScanError {
+    /// Reading from the underlying reader failed.
+    Io(std::io::Error),
+    /// No match starts at the given byte offset, where the given byte was found.
+    Mismatch { offset: usize, found: u8 },
+}
+
+impl std::fmt::Display for ReadScanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "read failed: {}", err),
+            Self::Mismatch { offset, found } => {
+                write!(f, "unexpected byte {:#04x} at offset {}", found, offset)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReadScanError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Mismatch { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ReadScanError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Splits a byte stream into consecutive matches of an automaton, as a lexer does, returned
+/// by [`FiniteAutomaton::read_scanner`].
+///
+/// Every step consumes the longest non-empty match at the current offset, and yields it as a
+/// [`Token`]. Input is read in chunks, and matches may span chunks. If no non-empty match
+/// starts at the current offset, or reading fails, an error is yielded, and the scanner stops.
+pub struct ReadScanner<'a, R, L, I: StateId = usize> {
+    automaton: &'a FiniteAutomaton<u8, L, I>,
+    reader: R,
+    buffer: Vec<u8>,
+    // the position in the buffer of the next byte to consume
+    start: usize,
+    offset: usize,
+    eof: bool,
+    failed: bool,
+}
+
+impl<L, I: StateId> FiniteAutomaton<u8, L, I> {
+    /// Returns a scanner that splits the bytes from the given reader into matches of this
+    /// automaton.
+    ///
+    /// Bytes are read until a state with the empty set as its regular expression is reached,
+    /// as for [`FiniteAutomaton::longest_matching_prefix`]. Trimming the automaton first
+    /// ensures that no more bytes are buffered than necessary.
+    pub fn read_scanner<R: Read>(&self, reader: R) -> ReadScanner<'_, R, L, I> {
+        ReadScanner {
+            automaton: self,
+            reader,
+            buffer: Vec::new(),
+            start: 0,
+            offset: 0,
+            eof: false,
+            failed: false,
+        }
+    }
+}
+
+impl<R: Read, L, I: StateId> ReadScanner<'_, R, L, I> {
+    /// Returns the byte offset of the next byte to consume.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the next token, or `None` at the end of the stream.
+    fn next_token(&mut self) -> Result<Option<Token>, ReadScanError> {
+        let mut state = 0;
+        let mut len = 0;
+        let mut longest = None;
+        'scan: loop {
+            while self.start + len < self.buffer.len() {
+                state = self.automaton.next(state, &self.buffer[self.start + len]);
+                len += 1;
+                if self.automaton.is_empty_set(state) {
+                    break 'scan;
+                }
+                if self.automaton.is_accepting(state) {
+                    longest = Some(len);
+                }
+            }
+            if self.eof {
+                break;
+            }
+            self.fill()?;
+        }
+
+        match longest {
+            Some(len) => {
+                let token = Token {
+                    offset: self.offset,
+                    bytes: self.buffer[self.start..self.start + len].to_vec(),
+                };
+                self.start += len;
+                self.offset += len;
+                Ok(Some(token))
+            }
+            None => match self.buffer.get(self.start) {
+                Some(found) => Err(ReadScanError::Mismatch {
+                    offset: self.offset,
+                    found: *found,
+                }),
+                None => Ok(None),
+            },
+        }
+    }
+
+    /// Appends the next chunk from the reader to the buffer, after removing the consumed
+    /// bytes from it.
+    fn fill(&mut self) -> Result<(), std::io::Error> {
+        self.buffer.drain(..self.start);
+        self.start = 0;
+        let len = self.buffer.len();
+        self.buffer.resize(len + CHUNK_SIZE, 0);
+        let result = loop {
+            match self.reader.read(&mut self.buffer[len..]) {
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                result => break result,
+            }
+        };
+        let read = *result.as_ref().unwrap_or(&0);
+        self.buffer.truncate(len + read);
+        self.eof = read == 0;
+        result.map(|_| ())
+    }
+}
+
+impl<R: Read, L, I: StateId> Iterator for ReadScanner<'_, R, L, I> {
+    type Item = Result<Token, ReadScanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        match self.next_token() {
+            Ok(token) => token.map(Ok),
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    use super::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<u8>>;
+
+    /// A reader that returns a single byte at a time.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let Some((first, rest)) = self.0.split_first() else {
+                return Ok(0);
+            };
+            buf[0] = *first;
+            self.0 = rest;
+            Ok(1)
+        }
+    }
+
+    fn token(offset: usize, bytes: &[u8]) -> Token {
+        Token {
+            offset,
+            bytes: bytes.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_read_scanner() {
+        let word = (b'a'.s() | b'b'.s()).c();
+        let regex: AscRegex = [word, b' '.s().c()].r() & ![].r();
+        let fa = regex.to_automaton().trim();
+        let input = b"ab ba  b";
+        let expected = vec![token(0, b"ab "), token(3, b"ba  "), token(7, b"b")];
+        let tokens = fa
+            .read_scanner(input.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .expect("no error");
+        assert_eq!(expected, tokens);
+        let tokens = fa
+            .read_scanner(Trickle(input))
+            .collect::<Result<Vec<_>, _>>()
+            .expect("no error");
+        assert_eq!(expected, tokens);
+        assert_eq!(0, fa.read_scanner(b"".as_slice()).count());
+    }
+
+    #[test]
+    fn test_read_scanner_long_token() {
+        let regex: AscRegex = b'a'.s().c() & ![].r();
+        let fa = regex.to_automaton();
+        let input = vec![b'a'; 3 * CHUNK_SIZE + 1];
+        let tokens = fa
+            .read_scanner(input.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .expect("no error");
+        assert_eq!(vec![token(0, &input)], tokens);
+    }
+
+    #[test]
+    fn test_read_scanner_mismatch() {
+        let regex: AscRegex = b'a'.s();
+        let fa = regex.to_automaton();
+        let mut scanner = fa.read_scanner(Trickle(b"aax"));
+        assert_eq!(Some(token(0, b"a")), scanner.next().transpose().unwrap());
+        assert_eq!(Some(token(1, b"a")), scanner.next().transpose().unwrap());
+        assert_eq!(2, scanner.offset());
+        let err = scanner.next().expect("error").expect_err("mismatch");
+        assert!(matches!(
+            err,
+            ReadScanError::Mismatch {
+                offset: 2,
+                found: b'x'
+            }
+        ));
+        assert_eq!("unexpected byte 0x78 at offset 2", err.to_string());
+        assert!(scanner.next().is_none());
+    }
+}
@@ -0,0 +1,108 @@
+//! Share finite automata between matchers and threads.
+
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::Matcher;
+use crate::automaton::StateId;
+use crate::Alphabet;
+
+/// A reference-counted handle to a finite automaton, that hands out independent matchers
+/// over the same automaton. Cloning the handle does not clone the automaton.
+///
+/// Matchers created by the handle are `Send` if the automaton is `Send` and `Sync`, so they
+/// can be used concurrently from different threads.
+pub struct SharedAutomaton<S: Alphabet, L = (), I: StateId = usize> {
+    fa: Arc<FiniteAutomaton<S, L, I>>,
+}
+
+impl<S: Alphabet, L, I: StateId> Clone for SharedAutomaton<S, L, I> {
+    fn clone(&self) -> Self {
+        Self {
+            fa: self.fa.clone(),
+        }
+    }
+}
+
+impl<S: Alphabet, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Returns a shared handle to this automaton.
+    pub fn into_shared(self) -> SharedAutomaton<S, L, I> {
+        SharedAutomaton { fa: Arc::new(self) }
+    }
+}
+
+impl<S: Alphabet, L, I: StateId> From<FiniteAutomaton<S, L, I>> for SharedAutomaton<S, L, I> {
+    fn from(value: FiniteAutomaton<S, L, I>) -> Self {
+        value.into_shared()
+    }
+}
+
+impl<S: Alphabet, L, I: StateId> SharedAutomaton<S, L, I> {
+    /// Returns a new matcher, starting in the start state of the automaton.
+    pub fn matcher(&self) -> Matcher<'static, S, L, I> {
+        Matcher {
+            fa: Handle::Shared(self.fa.clone()),
+            state: 0,
+        }
+    }
+}
+
+impl<S: Alphabet, L, I: StateId> Deref for SharedAutomaton<S, L, I> {
+    type Target = FiniteAutomaton<S, L, I>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.fa
+    }
+}
+
+/// The automaton of a matcher, which may be borrowed, owned, or shared.
+pub(crate) enum Handle<'a, T> {
+    Borrowed(&'a T),
+    Owned(T),
+    Shared(Arc<T>),
+}
+
+impl<T> Deref for Handle<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Handle::Borrowed(value) => value,
+            Handle::Owned(value) => value,
+            Handle::Shared(value) => value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    #[test]
+    fn test_shared_matchers() {
+        let r: Regex<ApproximatelySimilarCanonical<usize>> = [1.s(), (2.s() | 3.s()).c()].r();
+        let shared = r.to_automaton().into_shared();
+        let tests: Vec<(Vec<usize>, bool)> = vec![
+            (vec![1], true),
+            (vec![1, 2, 3, 2], true),
+            (vec![2], false),
+            (vec![1, 4], false),
+        ];
+        thread::scope(|scope| {
+            for (word, expected) in &tests {
+                let mut matcher = shared.matcher();
+                scope.spawn(move || assert_eq!(*expected, matcher.next_iter(word)));
+            }
+        });
+        let mut left = shared.matcher();
+        let mut right = shared.clone().matcher();
+        assert!(left.next(&1));
+        assert!(!right.next(&2));
+        assert!(left.next(&2));
+    }
+}
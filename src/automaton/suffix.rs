@@ -0,0 +1,114 @@
+//! Match suffixes of inputs using automata for reversed regular expressions.
+
+use std::borrow::Borrow;
+
+use crate::automaton::FiniteAutomaton;
+use crate::builder::ApproximatelySimilarCanonical;
+use crate::builder::Builder;
+use crate::builder::Regex;
+use crate::Alphabet;
+
+impl<B: Builder> Regex<B> {
+    /// Returns a regular expression for the reversed strings of the language of this regular
+    /// expression.
+    pub fn reverse(&self) -> Self {
+        match self {
+            Regex::EmptySet => B::empty_set(),
+            Regex::EmptyString => B::empty_string(),
+            Regex::Symbol(value) => B::symbol(value.clone()),
+            Regex::Concat(left, right) => B::concat(right.reverse(), left.reverse()),
+            Regex::Closure(inner) => B::closure(inner.reverse()),
+            Regex::Or(left, right) => B::or(left.reverse(), right.reverse()),
+            Regex::And(left, right) => B::and(left.reverse(), right.reverse()),
+            Regex::Complement(inner) => B::complement(inner.reverse()),
+        }
+    }
+}
+
+/// A finite automaton for the reversed language of a regular expression, which matches inputs
+/// from the end.
+#[derive(Clone)]
+pub struct SuffixAutomaton<S: Alphabet> {
+    fa: FiniteAutomaton<S>,
+}
+
+impl<S: Alphabet> Regex<ApproximatelySimilarCanonical<S>> {
+    /// Build an automaton that checks whether inputs end with a string in the language of this
+    /// regular expression.
+    pub fn to_suffix_automaton(&self) -> SuffixAutomaton<S> {
+        SuffixAutomaton {
+            fa: self.reverse().to_automaton().trim(),
+        }
+    }
+}
+
+impl<S: Alphabet> SuffixAutomaton<S> {
+    /// Returns whether the given symbols end with a string in the language of the regular
+    /// expression.
+    ///
+    /// The symbols are consumed from the end, until no longer suffix can be in the language,
+    /// so the input does not have to be buffered or reversed.
+    pub fn is_suffix_match<T>(
+        &self,
+        symbols: impl IntoIterator<Item = T, IntoIter: DoubleEndedIterator>,
+    ) -> bool
+    where
+        T: Borrow<S>,
+    {
+        self.longest_matching_suffix(symbols).is_some()
+    }
+
+    /// Returns the length of the longest suffix of the given symbols that is in the language of
+    /// the regular expression, or `None` if no suffix is.
+    pub fn longest_matching_suffix<T>(
+        &self,
+        symbols: impl IntoIterator<Item = T, IntoIter: DoubleEndedIterator>,
+    ) -> Option<usize>
+    where
+        T: Borrow<S>,
+    {
+        self.fa.longest_matching_prefix(symbols.into_iter().rev())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Pure;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    type PureRegex = Regex<Pure<usize>>;
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+
+    #[test]
+    fn test_reverse() {
+        let tests: Vec<(PureRegex, PureRegex)> = vec![
+            (1.s(), 1.s()),
+            ([1.s(), 2.s()].r(), [2.s(), 1.s()].r()),
+            ([1.s(), 2.s()].r().c(), [2.s(), 1.s()].r().c()),
+            (![1.s(), 2.s()].r() & 3.s(), ![2.s(), 1.s()].r() & 3.s()),
+        ];
+        for (regex, expected) in tests {
+            assert_eq!(expected, regex.reverse());
+        }
+    }
+
+    #[test]
+    fn test_suffix_match() {
+        let tests: Vec<(AscRegex, Vec<usize>, Option<usize>)> = vec![
+            ([1.s(), 2.s()].r(), vec![3, 1, 2], Some(2)),
+            ([1.s(), 2.s()].r(), vec![1, 2, 3], None),
+            ([1.s(), 2.s()].r().c(), vec![3, 1, 2, 1, 2], Some(4)),
+            ([1.s(), 2.s()].r().c(), vec![3], Some(0)),
+            (!2.s() & [1.s(), !().r()].r(), vec![1, 2], Some(2)),
+            (!2.s() & [1.s(), !().r()].r(), vec![2, 2], None),
+            (!2.s() & [1.s(), !().r()].r(), vec![2, 1, 2, 2], Some(3)),
+        ];
+        for (regex, word, expected) in tests {
+            let fa = regex.to_suffix_automaton();
+            assert_eq!(expected, fa.longest_matching_suffix(&word), "{}", regex);
+            assert_eq!(expected.is_some(), fa.is_suffix_match(&word), "{}", regex);
+        }
+    }
+}
@@ -0,0 +1,351 @@
+//! A plain-text table format for finite automata.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use itertools::Itertools;
+
+use crate::automaton::state_id;
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::State;
+use crate::automaton::StateId;
+use crate::automaton::Transitions;
+use crate::Alphabet;
+
+const STATE_COLUMN: &str = "state";
+const ACCEPTING_COLUMN: &str = "accepting";
+const DEFAULT_COLUMN: &str = "default";
+
+/// Errors that can occur when reading an automaton from a table.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AutomatonTableError {
+    /// The table does not start with the expected header.
+    InvalidHeader,
+    /// A symbol in the header cannot be parsed, or occurs more than once. When writing a
+    /// table, a symbol is displayed as an empty string, with whitespace, or the same as
+    /// another symbol.
+    InvalidSymbol,
+    /// The table has no states.
+    NoStates,
+    /// The row on the given line does not match the header, or does not have the expected
+    /// state number.
+    InvalidRow(usize),
+    /// A transition on the given line refers to a state that does not exist.
+    InvalidTransition(usize),
+}
+
+impl std::fmt::Display for AutomatonTableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "invalid header"),
+            Self::InvalidSymbol => write!(f, "invalid symbol"),
+            Self::NoStates => write!(f, "no states"),
+            Self::InvalidRow(line) => write!(f, "invalid row on line {}", line),
+            Self::InvalidTransition(line) => write!(f, "invalid transition on line {}", line),
+        }
+    }
+}
+
+impl std::error::Error for AutomatonTableError {}
+
+impl<S: Alphabet + Display, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Returns this automaton as a plain-text table, which can be read back using
+    /// [`FiniteAutomaton::from_table_str`].
+    ///
+    /// The table has a header row with the symbols that have explicit transitions, and a row
+    /// for every state with its number, whether it is accepting, its target state for every
+    /// symbol, and its target state for all other symbols. Columns are separated by spaces and
+    /// aligned, so the symbols must be displayed without whitespace, or
+    /// [`AutomatonTableError::InvalidSymbol`] is returned. Labels are not included.
+    pub fn to_table_string(&self) -> Result<String, AutomatonTableError> {
+        let symbols = (0..self.state_count())
+            .flat_map(|state| self.symbol_transitions(state).map(|(symbol, _)| symbol))
+            .sorted()
+            .dedup()
+            .collect_vec();
+        let names = symbols
+            .iter()
+            .map(|symbol| symbol.to_string())
+            .collect_vec();
+        if names
+            .iter()
+            .any(|name| name.is_empty() || name.contains(char::is_whitespace))
+            || names.iter().duplicates().next().is_some()
+        {
+            return Err(AutomatonTableError::InvalidSymbol);
+        }
+
+        let mut rows = Vec::with_capacity(self.state_count() + 1);
+        rows.push(
+            std::iter::once(STATE_COLUMN.to_string())
+                .chain(std::iter::once(ACCEPTING_COLUMN.to_string()))
+                .chain(names)
+                .chain(std::iter::once(DEFAULT_COLUMN.to_string()))
+                .collect_vec(),
+        );
+        for state in 0..self.state_count() {
+            rows.push(
+                std::iter::once(state.to_string())
+                    .chain(std::iter::once(self.is_accepting(state).to_string()))
+                    .chain(
+                        symbols
+                            .iter()
+                            .map(|symbol| self.next(state, *symbol).to_string()),
+                    )
+                    .chain(std::iter::once(self.default_transition(state).to_string()))
+                    .collect_vec(),
+            );
+        }
+
+        let widths = (0..symbols.len() + 3)
+            .map(|column| rows.iter().map(|row| row[column].len()).max().unwrap_or(0))
+            .collect_vec();
+        let mut table = String::new();
+        for row in rows {
+            let line = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                .join(" ");
+            table.push_str(line.trim_end());
+            table.push('\n');
+        }
+        Ok(table)
+    }
+}
+
+impl<S: Alphabet + FromStr> FiniteAutomaton<S> {
+    /// Reads an automaton from a plain-text table, in the format produced by
+    /// [`FiniteAutomaton::to_table_string`]. Empty lines are ignored.
+    ///
+    /// The table does not contain the regular expressions of the states, so they are not
+    /// known, except for the empty set of the states that cannot reach an accepting state.
+    /// They can be reconstructed using [`FiniteAutomaton::reconstruct_regexes`].
+    pub fn from_table_str(table: &str) -> Result<Self, AutomatonTableError> {
+        let mut lines = table
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| (idx + 1, line.split_whitespace().collect_vec()))
+            .filter(|(_, cells)| !cells.is_empty());
+
+        let (_, header) = lines.next().ok_or(AutomatonTableError::InvalidHeader)?;
+        if header.len() < 3
+            || header[0] != STATE_COLUMN
+            || header[1] != ACCEPTING_COLUMN
+            || header[header.len() - 1] != DEFAULT_COLUMN
+        {
+            return Err(AutomatonTableError::InvalidHeader);
+        }
+        let symbols = header[2..header.len() - 1]
+            .iter()
+            .map(|symbol| symbol.parse::<S>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| AutomatonTableError::InvalidSymbol)?;
+        if symbols.iter().duplicates().next().is_some() {
+            return Err(AutomatonTableError::InvalidSymbol);
+        }
+
+        let mut rows = Vec::new();
+        for (line, cells) in lines {
+            if cells.len() != header.len() || cells[0] != rows.len().to_string() {
+                return Err(AutomatonTableError::InvalidRow(line));
+            }
+            let accepting = cells[1]
+                .parse::<bool>()
+                .map_err(|_| AutomatonTableError::InvalidRow(line))?;
+            let targets = cells[2..]
+                .iter()
+                .map(|target| target.parse::<usize>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| AutomatonTableError::InvalidTransition(line))?;
+            rows.push((line, accepting, targets));
+        }
+        if rows.is_empty() {
+            return Err(AutomatonTableError::NoStates);
+        }
+        for (line, _, targets) in &rows {
+            if targets.iter().any(|target| *target >= rows.len()) {
+                return Err(AutomatonTableError::InvalidTransition(*line));
+            }
+        }
+
+        let rows = rows
+            .into_iter()
+            .map(|(_, accepting, targets)| (accepting, targets))
+            .collect_vec();
+        Ok(Self::from_rows(symbols, rows))
+    }
+}
+
+impl<S: Alphabet> FiniteAutomaton<S> {
+    /// Returns an automaton with a state for every row, given by whether it is accepting and
+    /// its target state for every symbol, followed by its target state for all other symbols.
+    /// The regular expressions of the states are not known, except for the states that cannot
+    /// reach an accepting state, which get the empty set.
+    pub(crate) fn from_rows(symbols: Vec<S>, rows: Vec<(bool, Vec<usize>)>) -> Self {
+        let states = rows
+            .into_iter()
+            .map(|(accepting, targets)| State {
+                regex: None,
+                accepting,
+                universal: false,
+                transitions: Transitions::Hashed(
+                    symbols
+                        .iter()
+                        .cloned()
+                        .zip(targets.iter().map(|target| state_id(*target)))
+                        .collect(),
+                ),
+                default_transition: state_id(targets[symbols.len()]),
+                label: (),
+            })
+            .collect();
+        FiniteAutomaton { states }
+            .mark_dead_states()
+            .mark_universal_states()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    use super::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+
+    #[test]
+    fn test_to_table_string() {
+        let regex: AscRegex = [1.s(), (2.s() | 10.s()).c()].r();
+        let fa = regex.to_automaton().trim();
+        let expected = "\
+state accepting 1 2 10 default
+0     false     1 2 2  2
+1     true      2 1 1  2
+2     false     2 2 2  2
+";
+        assert_eq!(Ok(expected.to_string()), fa.to_table_string());
+    }
+
+    #[test]
+    fn test_table_round_trip() {
+        let tests: Vec<AscRegex> = vec![
+            ().r(),
+            [].r(),
+            [1.s(), (2.s() | 10.s()).c()].r(),
+            !(1.s() | 2.s()),
+            [1.s().c(), !2.s()].r() & !3.s().c(),
+        ];
+        let words = vec![
+            vec![],
+            vec![1],
+            vec![2],
+            vec![4],
+            vec![1, 2],
+            vec![1, 10, 2],
+            vec![1, 4],
+            vec![3, 3],
+        ];
+        for regex in tests {
+            let fa = regex.to_automaton();
+            let table = fa.to_table_string().expect("symbols without whitespace");
+            let loaded = FiniteAutomaton::<usize>::from_table_str(&table).expect("valid table");
+            assert_eq!(Ok(table), loaded.to_table_string(), "{}", regex);
+            let reconstructed = loaded.clone().reconstruct_regexes();
+            for state in 0..fa.state_count() {
+                assert!(
+                    fa.states[state]
+                        .regex
+                        .as_ref()
+                        .unwrap()
+                        .is_equivalent_to(reconstructed.states[state].regex.as_ref().unwrap()),
+                    "{}",
+                    regex
+                );
+            }
+            for word in &words {
+                assert_eq!(
+                    fa.to_matcher().next_iter(word),
+                    loaded.to_matcher().next_iter(word),
+                    "{}",
+                    regex
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_to_table_string_invalid_symbol() {
+        let tests: Vec<Regex<ApproximatelySimilarCanonical<String>>> = vec![
+            "a b".to_string().s(),
+            "a\tb".to_string().s(),
+            String::new().s(),
+        ];
+        for regex in tests {
+            assert_eq!(
+                Err(AutomatonTableError::InvalidSymbol),
+                regex.to_automaton().to_table_string(),
+                "{}",
+                regex
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_table_str_hand_written() {
+        let table = "
+            state accepting a b default
+            0     false     1 0 0
+
+            1     true      1 0 0
+        ";
+        let fa = FiniteAutomaton::<char>::from_table_str(table).expect("valid table");
+        assert!(fa.to_matcher().next_iter(['b', 'a', 'a']));
+        assert!(!fa.to_matcher().next_iter(['a', 'b']));
+        assert!(!fa.to_matcher().next_iter(['a', 'c']));
+        assert!(fa.to_matcher().next_iter(['c', 'a']));
+    }
+
+    #[test]
+    fn test_from_table_str_errors() {
+        let tests = vec![
+            ("", AutomatonTableError::InvalidHeader),
+            ("state accepting 1", AutomatonTableError::InvalidHeader),
+            (
+                "state accepting x default",
+                AutomatonTableError::InvalidSymbol,
+            ),
+            (
+                "state accepting 1 1 default",
+                AutomatonTableError::InvalidSymbol,
+            ),
+            ("state accepting 1 default", AutomatonTableError::NoStates),
+            (
+                "state accepting 1 default\n1 false 0 0",
+                AutomatonTableError::InvalidRow(2),
+            ),
+            (
+                "state accepting 1 default\n0 no 0 0",
+                AutomatonTableError::InvalidRow(2),
+            ),
+            (
+                "state accepting 1 default\n0 false 0",
+                AutomatonTableError::InvalidRow(2),
+            ),
+            (
+                "state accepting 1 default\n0 false 0 0\n1 true 2 0",
+                AutomatonTableError::InvalidTransition(3),
+            ),
+        ];
+        for (table, expected) in tests {
+            assert_eq!(
+                Some(expected),
+                FiniteAutomaton::<usize>::from_table_str(table).err(),
+                "{}",
+                table
+            );
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Build nondeterministic finite automata from regular expressions, using the Thompson
+//! construction.
+
+use crate::automaton::Nfa;
+use crate::builder::Builder;
+use crate::builder::Regex;
+
+impl<B: Builder> Regex<B> {
+    /// Build a nondeterministic finite automaton for this regular expression, using the
+    /// Thompson construction, or `None` if it contains an intersection or complement, which
+    /// the construction does not support.
+    ///
+    /// Every symbol, empty set, and empty string adds one state, every closure two, and every
+    /// alternation three, connected by epsilon transitions. Concatenation adds no states. This is the
+    /// classical alternative to the derivative automaton of [`Regex::to_automaton`], and can be
+    /// used to compare both. The automaton follows the structure of the regular expression,
+    /// so it depends on the simplifications done by its builder.
+    pub fn to_thompson_nfa(&self) -> Option<Nfa<B::Symbol>> {
+        let mut nfa = Nfa::new();
+        let end = self.thompson(&mut nfa, 0)?;
+        nfa.set_accepting(end, true);
+        Some(nfa)
+    }
+
+    /// Adds the states for this regular expression, starting at the given state, and returns
+    /// the state where they end.
+    fn thompson(&self, nfa: &mut Nfa<B::Symbol>, start: usize) -> Option<usize> {
+        let end = match self {
+            Self::EmptySet => nfa.add_state(false),
+            Self::EmptyString => {
+                let end = nfa.add_state(false);
+                nfa.add_epsilon_transition(start, end);
+                end
+            }
+            Self::Symbol(symbol) => {
+                let end = nfa.add_state(false);
+                nfa.add_transition(start, symbol.clone(), end);
+                end
+            }
+            Self::Concat(left, right) => {
+                let middle = left.thompson(nfa, start)?;
+                right.thompson(nfa, middle)?
+            }
+            Self::Closure(inner) => {
+                let inner_start = nfa.add_state(false);
+                let inner_end = inner.thompson(nfa, inner_start)?;
+                let end = nfa.add_state(false);
+                nfa.add_epsilon_transition(start, inner_start);
+                nfa.add_epsilon_transition(start, end);
+                nfa.add_epsilon_transition(inner_end, inner_start);
+                nfa.add_epsilon_transition(inner_end, end);
+                end
+            }
+            Self::Or(left, right) => {
+                let mut ends = Vec::new();
+                for operand in [left, right] {
+                    let operand_start = nfa.add_state(false);
+                    nfa.add_epsilon_transition(start, operand_start);
+                    ends.push(operand.thompson(nfa, operand_start)?);
+                }
+                let end = nfa.add_state(false);
+                for operand_end in ends {
+                    nfa.add_epsilon_transition(operand_end, end);
+                }
+                end
+            }
+            Self::And(_, _) | Self::Complement(_) => return None,
+        };
+        Some(end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::Pure;
+    use crate::ops::*;
+
+    use super::*;
+
+    type PureRegex = Regex<Pure<usize>>;
+
+    #[test]
+    fn test_to_thompson_nfa() {
+        let tests: Vec<(PureRegex, usize)> = vec![
+            (().r(), 2),
+            ([].r(), 2),
+            (1.s(), 2),
+            ([1.s(), 2.s()].r(), 3),
+            (1.s() | 2.s(), 6),
+            (1.s().c(), 4),
+            ([(1.s() | 2.s()).c(), 1.s(), [].r().c()].r(), 12),
+        ];
+        let words = vec![
+            vec![],
+            vec![1],
+            vec![2],
+            vec![1, 2],
+            vec![2, 1],
+            vec![1, 1, 1],
+            vec![3],
+        ];
+        for (regex, state_count) in tests {
+            let nfa = regex.to_thompson_nfa().expect("supported");
+            assert_eq!(state_count, nfa.state_count(), "{}", regex);
+            let fa = nfa.determinize();
+            for word in &words {
+                let expected = regex.is_match(word);
+                assert_eq!(expected, nfa.is_match(word), "{} on {:?}", regex, word);
+                assert_eq!(
+                    expected,
+                    fa.to_matcher().next_iter(word),
+                    "{} on {:?}",
+                    regex,
+                    word
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_to_thompson_nfa_unsupported() {
+        let tests: Vec<PureRegex> = vec![!1.s(), 1.s() & 2.s(), [1.s(), !().r()].r()];
+        for regex in tests {
+            assert!(regex.to_thompson_nfa().is_none(), "{}", regex);
+        }
+    }
+}
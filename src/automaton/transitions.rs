@@ -0,0 +1,378 @@
+//! Representations of the transitions of automaton states.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use itertools::Either;
+use itertools::Itertools;
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::State;
+use crate::automaton::StateId;
+use crate::derivation::Symbols;
+use crate::Alphabet;
+
+/// The transitions of a state for the symbols that occur in the regular expression.
+#[derive(Clone)]
+pub(crate) enum Transitions<S, I> {
+    /// Transitions in a hash map.
+    Hashed(HashMap<S, I>),
+    /// Transitions sorted by symbol, which are found by binary search. This is smaller and
+    /// faster than a hash map for states with few transitions.
+    Sparse(Vec<(S, I)>),
+    /// Transitions grouped by target state, with the set of symbols that lead to it. This is
+    /// smaller than the other representations for states where many symbols lead to the same
+    /// state.
+    Classes(Vec<(HashSet<S>, I)>),
+}
+
+/// The representation of the transitions of a state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Representation {
+    Hashed,
+    Sparse,
+    Classes,
+}
+
+impl<S: Alphabet, I: StateId> Transitions<S, I> {
+    /// Returns transitions in the given representation.
+    pub(crate) fn new(
+        representation: Representation,
+        transitions: impl IntoIterator<Item = (S, I)>,
+    ) -> Self {
+        match representation {
+            Representation::Hashed => Self::Hashed(transitions.into_iter().collect()),
+            Representation::Sparse => Self::Sparse(
+                transitions
+                    .into_iter()
+                    .sorted_by(|(left, _), (right, _)| left.cmp(right))
+                    .collect(),
+            ),
+            Representation::Classes => {
+                let mut classes: Vec<(HashSet<S>, I)> = Vec::new();
+                for (symbol, next) in transitions {
+                    match classes.iter_mut().find(|(_, target)| *target == next) {
+                        Some((symbols, _)) => {
+                            symbols.insert(symbol);
+                        }
+                        None => classes.push((HashSet::from([symbol]), next)),
+                    }
+                }
+                classes.sort_by_key(|(_, next)| next.index());
+                Self::Classes(classes)
+            }
+        }
+    }
+
+    pub(crate) fn representation(&self) -> Representation {
+        match self {
+            Self::Hashed(_) => Representation::Hashed,
+            Self::Sparse(_) => Representation::Sparse,
+            Self::Classes(_) => Representation::Classes,
+        }
+    }
+
+    /// Returns an estimate of the heap memory used by these transitions, in bytes. Memory
+    /// owned by the symbols themselves is not included.
+    pub(crate) fn heap_size(&self) -> usize {
+        // hash tables use an additional control byte per entry
+        match self {
+            Self::Hashed(transitions) => transitions.capacity() * (size_of::<(S, I)>() + 1),
+            Self::Sparse(transitions) => transitions.capacity() * size_of::<(S, I)>(),
+            Self::Classes(classes) => {
+                classes.capacity() * size_of::<(HashSet<S>, I)>()
+                    + classes
+                        .iter()
+                        .map(|(symbols, _)| symbols.capacity() * (size_of::<S>() + 1))
+                        .sum::<usize>()
+            }
+        }
+    }
+
+    pub(crate) fn get<Q>(&self, symbol: &Q) -> Option<I>
+    where
+        S: Borrow<Q>,
+        Q: Hash + Ord + ?Sized,
+    {
+        match self {
+            Self::Hashed(transitions) => transitions.get(symbol).cloned(),
+            Self::Sparse(transitions) => transitions
+                .binary_search_by(|(other, _)| other.borrow().cmp(symbol))
+                .ok()
+                .map(|idx| transitions[idx].1),
+            Self::Classes(classes) => classes
+                .iter()
+                .find(|(symbols, _)| symbols.contains(symbol))
+                .map(|(_, next)| *next),
+        }
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&S, I)> {
+        match self {
+            Self::Hashed(transitions) => Either::Left(Either::Left(
+                transitions.iter().map(|(symbol, next)| (symbol, *next)),
+            )),
+            Self::Sparse(transitions) => Either::Left(Either::Right(
+                transitions.iter().map(|(symbol, next)| (symbol, *next)),
+            )),
+            Self::Classes(classes) => Either::Right(
+                classes
+                    .iter()
+                    .flat_map(|(symbols, next)| symbols.iter().map(|symbol| (symbol, *next))),
+            ),
+        }
+    }
+
+    /// Returns the transitions as symbol sets with their target states. Symbols that lead to
+    /// the same state are in the same set.
+    pub(crate) fn classes(&self) -> Vec<(Symbols<S>, I)> {
+        match self {
+            Self::Classes(classes) => classes
+                .iter()
+                .map(|(symbols, next)| (Symbols::Include(symbols.iter().cloned().collect()), *next))
+                .collect(),
+            _ => match Self::new(
+                Representation::Classes,
+                self.iter().map(|(symbol, next)| (symbol.clone(), next)),
+            ) {
+                Self::Classes(classes) => classes
+                    .into_iter()
+                    .map(|(symbols, next)| (Symbols::Include(symbols.into_iter().collect()), next))
+                    .collect(),
+                _ => unreachable!("class representation"),
+            },
+        }
+    }
+}
+
+impl<S, I: Copy> IntoIterator for Transitions<S, I> {
+    type Item = (S, I);
+    type IntoIter = Either<std::collections::hash_map::IntoIter<S, I>, std::vec::IntoIter<(S, I)>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            Self::Hashed(transitions) => Either::Left(transitions.into_iter()),
+            Self::Sparse(transitions) => Either::Right(transitions.into_iter()),
+            Self::Classes(classes) => Either::Right(
+                classes
+                    .into_iter()
+                    .flat_map(|(symbols, next)| {
+                        symbols.into_iter().map(move |symbol| (symbol, next))
+                    })
+                    .collect_vec()
+                    .into_iter(),
+            ),
+        }
+    }
+}
+
+impl<S: Alphabet, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Returns an equivalent automaton that stores the transitions of every state as a
+    /// vector sorted by symbol, instead of a hash map.
+    ///
+    /// Transitions are found by binary search. This uses less memory, and is faster for
+    /// states with few transitions. Trimming and minimizing preserve the representation.
+    pub fn into_sparse(self) -> Self {
+        FiniteAutomaton {
+            states: self
+                .states
+                .into_iter()
+                .map(|state| State {
+                    transitions: Transitions::new(Representation::Sparse, state.transitions),
+                    ..state
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns an equivalent automaton that stores the transitions of every state as a list
+    /// of symbol sets, one for every target state.
+    ///
+    /// Transitions are found by searching the sets in order. This uses less memory for states
+    /// where many symbols lead to the same state. Transitions that are the same as the default
+    /// transition are removed. Trimming and minimizing preserve the representation.
+    pub fn into_classes(self) -> Self {
+        FiniteAutomaton {
+            states: self
+                .states
+                .into_iter()
+                .map(|state| {
+                    let default_transition = state.default_transition;
+                    State {
+                        transitions: Transitions::new(
+                            Representation::Classes,
+                            state
+                                .transitions
+                                .into_iter()
+                                .filter(|(_, next)| *next != default_transition),
+                        ),
+                        ..state
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the transitions of the given state, as sets of symbols with the state they lead
+    /// to.
+    ///
+    /// Symbols that lead to the same state are in the same set. The last set excludes all
+    /// symbols with explicit transitions, and leads to the state for the rest of the alphabet.
+    pub fn class_transitions(&self, state: usize) -> Vec<(Symbols<S>, usize)> {
+        let state = &self.states[state];
+        let mut classes = state
+            .transitions
+            .classes()
+            .into_iter()
+            .map(|(symbols, next)| (symbols, next.index()))
+            .collect_vec();
+        let excluded = state
+            .transitions
+            .iter()
+            .map(|(symbol, _)| symbol.clone())
+            .collect();
+        classes.push((Symbols::Exclude(excluded), state.default_transition.index()));
+        classes
+    }
+
+    /// Returns the edges of this automaton, as triples of a source state, a set of symbols,
+    /// and a target state.
+    ///
+    /// There is one edge for every pair of states with a transition between them, labeled with
+    /// all symbols that lead from the source to the target. Edges are ordered by source state,
+    /// and then by target state.
+    pub fn edges(&self) -> impl Iterator<Item = (usize, Symbols<S>, usize)> + '_ {
+        (0..self.states.len()).flat_map(move |from| {
+            self.class_transitions(from)
+                .into_iter()
+                .into_grouping_map_by(|(_, to)| *to)
+                .fold_first(|(left, to), _, (right, _)| (left | right, to))
+                .into_iter()
+                .sorted_by_key(|(to, _)| *to)
+                .map(move |(to, (symbols, _))| (from, symbols, to))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::derivation::Symbols;
+    use crate::ops::*;
+
+    use super::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+
+    #[test]
+    fn test_sparse_transitions() {
+        let tests: Vec<(AscRegex, Vec<usize>, bool)> = vec![
+            ([1.s(), 2.s()].r(), vec![1, 2], true),
+            ([1.s(), 2.s()].r(), vec![1, 3], false),
+            ((1.s() | 3.s() | 5.s()).c(), vec![5, 3, 1], true),
+            ((1.s() | 3.s() | 5.s()).c(), vec![5, 4], false),
+            (!3.s() & (2.s() | 3.s() | 4.s()), vec![4], true),
+            (!3.s() & (2.s() | 3.s() | 4.s()), vec![3], false),
+        ];
+        for (regex, word, expected) in tests {
+            let fa = regex.to_automaton_with_sparse_transitions();
+            assert!(fa
+                .states
+                .iter()
+                .all(|state| state.transitions.representation() == Representation::Sparse));
+            assert_eq!(expected, fa.to_matcher().next_iter(&word), "{}", regex);
+            let fa = regex.to_automaton().into_sparse();
+            assert_eq!(expected, fa.to_matcher().next_iter(&word), "{}", regex);
+            let fa = fa.trim().minimize();
+            assert!(fa
+                .states
+                .iter()
+                .all(|state| state.transitions.representation() == Representation::Sparse));
+            assert_eq!(expected, fa.to_matcher().next_iter(&word), "{}", regex);
+        }
+    }
+
+    #[test]
+    fn test_sparse_borrowed_symbols() {
+        let r: Regex<ApproximatelySimilarCanonical<String>> =
+            ["foo".to_string().s(), "bar".to_string().s().c()].r();
+        let fa = r.to_automaton().into_sparse();
+        let mut m = fa.to_matcher();
+        assert!(m.next("foo"));
+        assert!(m.next("bar"));
+        assert!(!m.next("baz"));
+    }
+
+    #[test]
+    fn test_class_transitions() {
+        let tests: Vec<(AscRegex, Vec<usize>, bool)> = vec![
+            ([1.s(), 2.s()].r(), vec![1, 2], true),
+            ([1.s(), 2.s()].r(), vec![1, 3], false),
+            ((1.s() | 3.s() | 5.s()).c(), vec![5, 3, 1], true),
+            ((1.s() | 3.s() | 5.s()).c(), vec![5, 4], false),
+            (!3.s() & (2.s() | 3.s() | 4.s()), vec![4], true),
+            (!3.s() & (2.s() | 3.s() | 4.s()), vec![3], false),
+        ];
+        for (regex, word, expected) in tests {
+            let fa = regex.to_automaton().into_classes();
+            assert_eq!(expected, fa.to_matcher().next_iter(&word), "{}", regex);
+            let fa = fa.trim().minimize();
+            assert!(fa
+                .states
+                .iter()
+                .all(|state| state.transitions.representation() == Representation::Classes));
+            assert_eq!(expected, fa.to_matcher().next_iter(&word), "{}", regex);
+        }
+    }
+
+    #[test]
+    fn test_edges() {
+        let regex: AscRegex = [1.s(), (2.s() | 3.s()).c()].r();
+        let fa = regex.to_automaton().trim();
+        let edges = fa.edges().collect_vec();
+        assert_eq!(5, edges.len());
+        assert_eq!((0, Symbols::include([1])), (edges[0].0, edges[0].1.clone()));
+        let (from, symbols, to) = &edges[1];
+        assert_eq!((0, &Symbols::Exclude([1].into())), (*from, symbols));
+        assert!(!fa.is_accepting(*to));
+        let (from, symbols, to) = &edges[2];
+        assert_eq!((edges[0].2, &Symbols::include([2, 3])), (*from, symbols));
+        assert_eq!(from, to);
+        let (from, symbols, to) = &edges[3];
+        assert_eq!(
+            (edges[0].2, &Symbols::Exclude([2, 3].into())),
+            (*from, symbols)
+        );
+        assert_eq!(edges[1].2, *to);
+        let (from, symbols, to) = &edges[4];
+        assert_eq!((edges[1].2, &Symbols::Exclude([].into())), (*from, symbols));
+        assert_eq!(from, to);
+        for (from, symbols, to) in fa.edges() {
+            for symbol in 0..5 {
+                if symbols.matches(&symbol) {
+                    assert_eq!(to, fa.next(from, &symbol));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_class_transitions_of_state() {
+        let regex: AscRegex = (1.s() | 3.s() | 5.s()).c() & !7.s();
+        let fa = regex.to_automaton().into_classes();
+        let classes = fa.class_transitions(0);
+        assert_eq!(2, classes.len());
+        let (symbols, next) = &classes[0];
+        assert_eq!(&Symbols::include([1, 3, 5]), symbols);
+        assert!(fa.is_accepting(*next));
+        let (symbols, next) = &classes[1];
+        assert_eq!(&Symbols::Exclude([1, 3, 5].into()), symbols);
+        assert!(!fa.is_accepting(*next));
+
+        let fa = regex.to_automaton();
+        assert_eq!(3, fa.class_transitions(0).len());
+    }
+}
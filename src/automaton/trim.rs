@@ -0,0 +1,197 @@
+//! Remove unreachable and dead states from finite automata.
+
+use crate::automaton::state_id;
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::State;
+use crate::automaton::StateId;
+use crate::automaton::Transitions;
+use crate::builder::Regex;
+use crate::Alphabet;
+
+impl<S: Alphabet, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Returns an equivalent automaton without states that are unreachable from the start
+    /// state, and with all states that cannot reach an accepting state merged into a single
+    /// sink state. Transitions that are the same as the default transition are removed.
+    ///
+    /// The sink state gets the label of the first dead state, and the empty set as its regular
+    /// expression. This does not minimize the automaton.
+    pub fn trim(self) -> Self {
+        let reachable = self.reachable_states();
+        let live = self.live_states();
+
+        let mut mapping = vec![None; self.states.len()];
+        let mut sink = None;
+        let mut next_idx = 0;
+        for idx in 0..self.states.len() {
+            if !reachable[idx] {
+                continue;
+            }
+            if live[idx] {
+                mapping[idx] = Some(next_idx);
+                next_idx += 1;
+            } else if let Some(sink) = sink {
+                mapping[idx] = Some(sink);
+            } else {
+                mapping[idx] = Some(next_idx);
+                sink = Some(next_idx);
+                next_idx += 1;
+            }
+        }
+
+        let mut states = Vec::with_capacity(next_idx);
+        for (idx, state) in self.states.into_iter().enumerate() {
+            let Some(new_idx) = mapping[idx] else {
+                continue;
+            };
+            if new_idx < states.len() {
+                continue;
+            }
+            if Some(new_idx) == sink {
+                states.push(State {
+                    regex: Some(Regex::EmptySet),
+                    accepting: false,
+                    universal: false,
+                    transitions: Transitions::new(state.transitions.representation(), []),
+                    default_transition: state_id(new_idx),
+                    label: state.label,
+                });
+            } else {
+                let default_transition =
+                    mapping[state.default_transition.index()].expect("successor is reachable");
+                let representation = state.transitions.representation();
+                let transitions = state
+                    .transitions
+                    .into_iter()
+                    .map(|(symbol, next)| {
+                        (
+                            symbol,
+                            mapping[next.index()].expect("successor is reachable"),
+                        )
+                    })
+                    .filter(|(_, next)| *next != default_transition)
+                    .map(|(symbol, next)| (symbol, state_id(next)));
+                states.push(State {
+                    regex: state.regex,
+                    accepting: state.accepting,
+                    universal: state.universal,
+                    transitions: Transitions::new(representation, transitions),
+                    default_transition: state_id(default_transition),
+                    label: state.label,
+                });
+            }
+        }
+
+        FiniteAutomaton { states }
+    }
+
+    /// Returns for every state whether it is reachable from the start state.
+    pub(crate) fn reachable_states(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.states.len()];
+        let mut stack = vec![0];
+        reachable[0] = true;
+        while let Some(idx) = stack.pop() {
+            for next in self.successors(idx) {
+                if !reachable[next] {
+                    reachable[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Returns for every state whether an accepting state can be reached from it.
+    pub(crate) fn live_states(&self) -> Vec<bool> {
+        self.can_reach(|state| state.accepting)
+    }
+
+    /// Returns this automaton with the empty set as the regular expression of the states from
+    /// which no accepting state can be reached, so that they are recognized as dead states.
+    pub(crate) fn mark_dead_states(mut self) -> Self {
+        let live = self.live_states();
+        for (state, live) in self.states.iter_mut().zip(live) {
+            if !live {
+                state.regex = Some(Regex::EmptySet);
+            }
+        }
+        self
+    }
+
+    /// Returns this automaton with the states from which only accepting states can be reached
+    /// marked as universal.
+    pub(crate) fn mark_universal_states(mut self) -> Self {
+        let rejecting = self.can_reach(|state| !state.accepting);
+        for (state, rejecting) in self.states.iter_mut().zip(rejecting) {
+            state.universal = !rejecting;
+        }
+        self
+    }
+
+    /// Returns for every state whether a state for which the given predicate holds can be
+    /// reached from it.
+    fn can_reach(&self, predicate: impl Fn(&State<S, L, I>) -> bool) -> Vec<bool> {
+        let mut predecessors = vec![Vec::new(); self.states.len()];
+        for idx in 0..self.states.len() {
+            for next in self.successors(idx) {
+                predecessors[next].push(idx);
+            }
+        }
+        let mut reaches = vec![false; self.states.len()];
+        let mut stack = Vec::new();
+        for (idx, state) in self.states.iter().enumerate() {
+            if predicate(state) {
+                reaches[idx] = true;
+                stack.push(idx);
+            }
+        }
+        while let Some(idx) = stack.pop() {
+            for prev in &predecessors[idx] {
+                if !reaches[*prev] {
+                    reaches[*prev] = true;
+                    stack.push(*prev);
+                }
+            }
+        }
+        reaches
+    }
+
+    /// Returns the successors of the given state, including the target of its default transition.
+    pub(crate) fn successors(&self, current: usize) -> impl Iterator<Item = usize> + '_ {
+        let state = &self.states[current];
+        state
+            .transitions
+            .iter()
+            .map(|(_, next)| next.index())
+            .chain(std::iter::once(state.default_transition.index()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+
+    #[test]
+    fn test_trim() {
+        let tests: Vec<(AscRegex, usize, Vec<usize>, bool)> = vec![
+            (().r(), 1, vec![], false),
+            ([1.s(), 2.s()].r(), 4, vec![1, 2], true),
+            ((1.s() & 2.s()) | [3.s(), 4.s()].r(), 4, vec![3, 4], true),
+            ((1.s() & 2.s()) | [3.s(), 4.s()].r(), 4, vec![1], false),
+            (!().r(), 1, vec![7], true),
+        ];
+        for (regex, state_count, word, expected) in tests {
+            let fa = regex.to_automaton().trim();
+            assert_eq!(state_count, fa.state_count(), "states of {}", regex);
+            assert_eq!(
+                expected,
+                fa.to_matcher().next_iter(&word),
+                "match {}",
+                regex
+            );
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Match regular expressions and automata within a budget.
+
+use std::borrow::Borrow;
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::StateId;
+use crate::builder::Builder;
+use crate::builder::Regex;
+use crate::simplify::Meter;
+use crate::Alphabet;
+use crate::Budget;
+
+/// The error returned by budgeted matching when the budget is spent before the input is
+/// consumed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BudgetExceeded;
+
+impl std::fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "budget exceeded")
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+impl<B: Builder> Regex<B> {
+    /// Returns whether the given symbols match this regular expression, as
+    /// [`Regex::is_match`] does, or an error if the given budget is spent first.
+    ///
+    /// Every consumed symbol is a step. The time limit is checked before every derivative is
+    /// computed, so a single expensive derivative can exceed it.
+    pub fn is_match_with_budget<I>(
+        &self,
+        symbols: impl IntoIterator<Item = I>,
+        budget: Budget,
+    ) -> Result<bool, BudgetExceeded>
+    where
+        I: Borrow<B::Symbol>,
+    {
+        let mut meter = Meter::for_steps(budget);
+        let mut regex = self.clone();
+        for symbol in symbols {
+            if !meter.tick() {
+                return Err(BudgetExceeded);
+            }
+            regex = regex.derive(symbol.borrow());
+        }
+        Ok(regex.is_nullable())
+    }
+}
+
+impl<S: Alphabet, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Returns whether the given symbols are accepted by this automaton, or an error if the
+    /// given budget is spent first.
+    ///
+    /// Every consumed symbol is a step. Symbols are not consumed beyond a state that accepts
+    /// every continuation or that is known to accept none, so such inputs succeed regardless
+    /// of their remaining length.
+    pub fn is_match_with_budget<T>(
+        &self,
+        symbols: impl IntoIterator<Item = T>,
+        budget: Budget,
+    ) -> Result<bool, BudgetExceeded>
+    where
+        T: Borrow<S>,
+    {
+        let mut meter = Meter::for_steps(budget);
+        let mut state = 0;
+        for symbol in symbols {
+            if self.is_universal(state) || self.is_empty_set(state) {
+                break;
+            }
+            if !meter.tick() {
+                return Err(BudgetExceeded);
+            }
+            state = self.next(state, symbol.borrow());
+        }
+        Ok(self.is_accepting(state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Pure;
+    use crate::ops::*;
+
+    use super::*;
+
+    #[test]
+    fn test_is_match_with_budget() {
+        let regex: Regex<ApproximatelySimilarCanonical<usize>> = [1.s(), 2.s()].r().c();
+        let fa = regex.to_automaton();
+        let tests = vec![
+            (vec![], Budget::unlimited().with_steps(0), Ok(true)),
+            (vec![1, 2], Budget::unlimited().with_steps(2), Ok(true)),
+            (vec![1, 2, 1], Budget::unlimited().with_steps(3), Ok(false)),
+            (
+                vec![1, 2],
+                Budget::unlimited().with_steps(1),
+                Err(BudgetExceeded),
+            ),
+            (vec![1, 2], Budget::unlimited(), Ok(true)),
+            (
+                vec![1, 2],
+                Budget::unlimited().with_time(Duration::ZERO),
+                Err(BudgetExceeded),
+            ),
+        ];
+        for (word, budget, expected) in tests {
+            assert_eq!(
+                expected,
+                regex.is_match_with_budget(&word, budget),
+                "{:?}",
+                word
+            );
+            assert_eq!(
+                expected,
+                fa.is_match_with_budget(&word, budget),
+                "{:?}",
+                word
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_match_with_budget_stops_early() {
+        let budget = Budget::unlimited().with_steps(2);
+        let regex: Regex<Pure<usize>> = [1.s(), !().r()].r();
+        let fa = regex.to_automaton();
+        assert_eq!(Ok(true), fa.is_match_with_budget(vec![1; 100], budget));
+        assert_eq!(Ok(false), fa.is_match_with_budget(vec![2; 100], budget));
+        assert_eq!(
+            Err(BudgetExceeded),
+            regex.is_match_with_budget(vec![1; 100], budget)
+        );
+    }
+}
@@ -2,14 +2,20 @@
 
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::marker::PhantomData;
 
 use crate::Alphabet;
 
 mod pure;
 mod similarity;
+mod strong;
 
+pub use pure::Nodes;
 pub use pure::Pure;
+pub use similarity::ApproximateSimilarity;
 pub use similarity::ApproximatelySimilarCanonical;
+pub use strong::StrongSimilarity;
+pub use strong::StronglyCanonical;
 
 /// The recommended regular expression builder.
 pub type Default<S> = ApproximatelySimilarCanonical<S>;
@@ -26,6 +32,140 @@ pub trait Builder: Eq + Hash + Sized {
     fn or(left: Regex<Self>, right: Regex<Self>) -> Regex<Self>;
     fn and(left: Regex<Self>, right: Regex<Self>) -> Regex<Self>;
     fn complement(inner: Regex<Self>) -> Regex<Self>;
+
+    /// Builds the concatenation of all operands, or the empty string if there are none.
+    fn concat_all(operands: impl IntoIterator<Item = Regex<Self>>) -> Regex<Self> {
+        operands
+            .into_iter()
+            .reduce(Self::concat)
+            .unwrap_or_else(Self::empty_string)
+    }
+
+    /// Builds the alternation of all operands, or the empty set if there are none.
+    fn or_all(operands: impl IntoIterator<Item = Regex<Self>>) -> Regex<Self> {
+        operands
+            .into_iter()
+            .reduce(Self::or)
+            .unwrap_or_else(Self::empty_set)
+    }
+
+    /// Builds the intersection of all operands, or the complement of the empty set if there are
+    /// none.
+    fn and_all(operands: impl IntoIterator<Item = Regex<Self>>) -> Regex<Self> {
+        operands
+            .into_iter()
+            .reduce(Self::and)
+            .unwrap_or_else(|| Self::complement(Self::empty_set()))
+    }
+}
+
+/// A layer in a builder stack.
+///
+/// Layers implement the constructor methods for any builder `B` that they are part of. A
+/// layer that rewrites regular expressions uses `B` to build subexpressions, so that they are
+/// rewritten by the whole stack, and delegates the creation of the resulting nodes to the next
+/// layer in the stack. The bottom of the stack is [`Nodes`], which creates nodes as-is.
+pub trait Layer: Eq + Hash + Sized {
+    fn empty_set<B: Builder>() -> Regex<B>;
+    fn empty_string<B: Builder>() -> Regex<B>;
+    fn symbol<B: Builder>(value: B::Symbol) -> Regex<B>;
+    fn closure<B: Builder>(inner: Regex<B>) -> Regex<B>;
+    fn concat<B: Builder>(left: Regex<B>, right: Regex<B>) -> Regex<B>;
+    fn or<B: Builder>(left: Regex<B>, right: Regex<B>) -> Regex<B>;
+    fn and<B: Builder>(left: Regex<B>, right: Regex<B>) -> Regex<B>;
+    fn complement<B: Builder>(inner: Regex<B>) -> Regex<B>;
+
+    /// Builds the concatenation of all operands. By default, this folds the operands using
+    /// [`Builder::concat`]. Layers can override it to rewrite all operands at once.
+    fn concat_all<B: Builder>(operands: impl IntoIterator<Item = Regex<B>>) -> Regex<B> {
+        operands
+            .into_iter()
+            .reduce(B::concat)
+            .unwrap_or_else(B::empty_string)
+    }
+
+    /// Builds the alternation of all operands. By default, this folds the operands using
+    /// [`Builder::or`]. Layers can override it to rewrite all operands at once.
+    fn or_all<B: Builder>(operands: impl IntoIterator<Item = Regex<B>>) -> Regex<B> {
+        operands
+            .into_iter()
+            .reduce(B::or)
+            .unwrap_or_else(B::empty_set)
+    }
+
+    /// Builds the intersection of all operands. By default, this folds the operands using
+    /// [`Builder::and`]. Layers can override it to rewrite all operands at once.
+    fn and_all<B: Builder>(operands: impl IntoIterator<Item = Regex<B>>) -> Regex<B> {
+        operands
+            .into_iter()
+            .reduce(B::and)
+            .unwrap_or_else(|| B::complement(B::empty_set()))
+    }
+}
+
+/// A builder for regular expressions over symbols `S`, composed of a stack of layers `L`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Stack<S: Alphabet, L: Layer> {
+    _phantom: PhantomData<(S, L)>,
+}
+
+impl<S: Alphabet, L: Layer> Builder for Stack<S, L> {
+    type Symbol = S;
+
+    #[inline]
+    fn empty_set() -> Regex<Self> {
+        L::empty_set()
+    }
+
+    #[inline]
+    fn empty_string() -> Regex<Self> {
+        L::empty_string()
+    }
+
+    #[inline]
+    fn symbol(value: Self::Symbol) -> Regex<Self> {
+        L::symbol(value)
+    }
+
+    #[inline]
+    fn closure(inner: Regex<Self>) -> Regex<Self> {
+        L::closure(inner)
+    }
+
+    #[inline]
+    fn concat(left: Regex<Self>, right: Regex<Self>) -> Regex<Self> {
+        L::concat(left, right)
+    }
+
+    #[inline]
+    fn or(left: Regex<Self>, right: Regex<Self>) -> Regex<Self> {
+        L::or(left, right)
+    }
+
+    #[inline]
+    fn and(left: Regex<Self>, right: Regex<Self>) -> Regex<Self> {
+        L::and(left, right)
+    }
+
+    #[inline]
+    fn complement(inner: Regex<Self>) -> Regex<Self> {
+        L::complement(inner)
+    }
+
+    #[inline]
+    fn concat_all(operands: impl IntoIterator<Item = Regex<Self>>) -> Regex<Self> {
+        L::concat_all(operands)
+    }
+
+    #[inline]
+    fn or_all(operands: impl IntoIterator<Item = Regex<Self>>) -> Regex<Self> {
+        L::or_all(operands)
+    }
+
+    #[inline]
+    fn and_all(operands: impl IntoIterator<Item = Regex<Self>>) -> Regex<Self> {
+        L::and_all(operands)
+    }
 }
 
 /// Data type describing regular expressions over values of type S.
@@ -81,6 +221,88 @@ impl<B: Builder> Regex<B> {
     pub fn complement(inner: Self) -> Self {
         B::complement(inner)
     }
+
+    /// Returns the concatenation of all operands, or the empty string if there are none.
+    ///
+    /// This is equivalent to concatenating the operands one by one, but builders can
+    /// canonicalize all operands at once.
+    #[inline]
+    pub fn concat_all(operands: impl IntoIterator<Item = Self>) -> Self {
+        B::concat_all(operands)
+    }
+
+    /// Returns the alternation of all operands, or the empty set if there are none.
+    ///
+    /// This is equivalent to combining the operands one by one, but builders can canonicalize
+    /// all operands at once.
+    #[inline]
+    pub fn or_all(operands: impl IntoIterator<Item = Self>) -> Self {
+        B::or_all(operands)
+    }
+
+    /// Returns the intersection of all operands, or the complement of the empty set if there
+    /// are none.
+    ///
+    /// This is equivalent to combining the operands one by one, but builders can canonicalize
+    /// all operands at once.
+    #[inline]
+    pub fn and_all(operands: impl IntoIterator<Item = Self>) -> Self {
+        B::and_all(operands)
+    }
+
+    /// Returns a regular expression matching exactly one symbol from the given alphabet.
+    ///
+    /// Unlike `¬∅`, which matches any string including symbols that were never mentioned,
+    /// this only matches the symbols that are explicitly part of the alphabet.
+    pub fn any(alphabet: impl IntoIterator<Item = B::Symbol>) -> Self {
+        B::or_all(alphabet.into_iter().map(B::symbol))
+    }
+
+    /// Returns a regular expression matching any string of symbols from the given alphabet.
+    #[inline]
+    pub fn any_star(alphabet: impl IntoIterator<Item = B::Symbol>) -> Self {
+        B::closure(Self::any(alphabet))
+    }
+
+    /// Returns a regular expression matching any single symbol, including symbols that do
+    /// not occur in any regular expression, as `¬ε & ¬(¬ε ¬ε)`.
+    pub(crate) fn single_symbol() -> Self {
+        let non_empty = B::complement(B::empty_string());
+        B::and(
+            non_empty.clone(),
+            B::complement(B::concat(non_empty.clone(), non_empty)),
+        )
+    }
+
+    /// Returns a regular expression matching exactly the given string of symbols.
+    pub fn word(word: &[B::Symbol]) -> Self {
+        B::concat_all(word.iter().cloned().map(B::symbol))
+    }
+
+    /// Returns a regular expression matching exactly the given strings of symbols, or the empty
+    /// set if there are none.
+    pub fn one_of_words<W: AsRef<[B::Symbol]>>(words: impl IntoIterator<Item = W>) -> Self {
+        B::or_all(words.into_iter().map(|word| Self::word(word.as_ref())))
+    }
+
+    /// Returns the complement of the given regular expression relative to the strings over the
+    /// given alphabet.
+    ///
+    /// Unlike `¬R`, the result does not match strings containing symbols outside the alphabet.
+    #[inline]
+    pub fn complement_within(inner: Self, alphabet: impl IntoIterator<Item = B::Symbol>) -> Self {
+        B::and(Self::any_star(alphabet), B::complement(inner))
+    }
+
+    /// Returns a regular expression matching any string over the given alphabet that contains
+    /// a string matching the given regular expression.
+    ///
+    /// Unlike `¬∅ R ¬∅`, the result does not match strings containing symbols outside the
+    /// alphabet.
+    pub fn contains(inner: Self, alphabet: impl IntoIterator<Item = B::Symbol>) -> Self {
+        let any_star = Self::any_star(alphabet);
+        B::concat(B::concat(any_star.clone(), inner), any_star)
+    }
 }
 
 impl<B: Builder> Regex<B> {
@@ -99,8 +321,116 @@ impl<B: Builder> Regex<B> {
     }
 }
 
+/// Copies the nodes as they are, without passing them through the builder again.
 impl<B: Builder> Clone for Regex<B> {
     fn clone(&self) -> Self {
-        self.rebuild()
+        match self {
+            Regex::EmptySet => Regex::EmptySet,
+            Regex::EmptyString => Regex::EmptyString,
+            Regex::Symbol(value) => Regex::Symbol(value.clone()),
+            Regex::Concat(left, right) => Regex::Concat(left.clone(), right.clone()),
+            Regex::Closure(inner) => Regex::Closure(inner.clone()),
+            Regex::Or(left, right) => Regex::Or(left.clone(), right.clone()),
+            Regex::And(left, right) => Regex::And(left.clone(), right.clone()),
+            Regex::Complement(inner) => Regex::Complement(inner.clone()),
+        }
+    }
+}
+
+impl<S: Alphabet> From<Regex<Pure<S>>> for Regex<ApproximatelySimilarCanonical<S>> {
+    #[inline]
+    fn from(value: Regex<Pure<S>>) -> Self {
+        value.rebuild()
+    }
+}
+
+impl<S: Alphabet> From<Regex<ApproximatelySimilarCanonical<S>>> for Regex<Pure<S>> {
+    #[inline]
+    fn from(value: Regex<ApproximatelySimilarCanonical<S>>) -> Self {
+        value.rebuild()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ops::*;
+
+    use super::*;
+
+    #[test]
+    fn test_cross_builder_conversion() {
+        let pure: Regex<Pure<usize>> = [].r() + (2.s() | 1.s());
+        let asc: Regex<ApproximatelySimilarCanonical<usize>> = pure.into();
+        let expected: Regex<Pure<usize>> = Regex::Or(1.s().into(), 2.s().into());
+        assert_eq!(expected, Regex::<Pure<usize>>::from(asc));
+    }
+
+    thread_local! {
+        static CLOSURES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    /// A layer that counts the closure nodes it creates.
+    #[derive(Debug, Hash, PartialEq, Eq)]
+    struct CountClosures<L: Layer> {
+        _phantom: PhantomData<L>,
+    }
+
+    impl<L: Layer> Layer for CountClosures<L> {
+        fn empty_set<B: Builder>() -> Regex<B> {
+            L::empty_set()
+        }
+
+        fn empty_string<B: Builder>() -> Regex<B> {
+            L::empty_string()
+        }
+
+        fn symbol<B: Builder>(value: B::Symbol) -> Regex<B> {
+            L::symbol(value)
+        }
+
+        fn closure<B: Builder>(inner: Regex<B>) -> Regex<B> {
+            CLOSURES.with(|count| count.set(count.get() + 1));
+            L::closure(inner)
+        }
+
+        fn concat<B: Builder>(left: Regex<B>, right: Regex<B>) -> Regex<B> {
+            L::concat(left, right)
+        }
+
+        fn or<B: Builder>(left: Regex<B>, right: Regex<B>) -> Regex<B> {
+            L::or(left, right)
+        }
+
+        fn and<B: Builder>(left: Regex<B>, right: Regex<B>) -> Regex<B> {
+            L::and(left, right)
+        }
+
+        fn complement<B: Builder>(inner: Regex<B>) -> Regex<B> {
+            L::complement(inner)
+        }
+    }
+
+    type CountingRegex = Regex<Stack<usize, ApproximateSimilarity<CountClosures<Nodes>>>>;
+
+    #[test]
+    fn test_stack_composition() {
+        CLOSURES.with(|count| count.set(0));
+        let regex: CountingRegex = 1.s().c().c() | [].r().c();
+        assert_eq!(2, CLOSURES.with(|count| count.get()));
+        let expected: Regex<ApproximatelySimilarCanonical<usize>> = 1.s().c().c() | [].r().c();
+        assert_eq!(
+            expected,
+            regex.rebuild::<ApproximatelySimilarCanonical<_>>()
+        );
+    }
+
+    #[test]
+    fn test_clone_does_not_rebuild() {
+        let regex: CountingRegex = [1.s().c(), 2.s().c()].r();
+        CLOSURES.with(|count| count.set(0));
+        assert_eq!(regex, regex.clone());
+        assert_eq!(0, CLOSURES.with(|count| count.get()));
+        let _ = regex.rebuild::<Stack<usize, ApproximateSimilarity<CountClosures<Nodes>>>>();
+        assert_eq!(2, CLOSURES.with(|count| count.get()));
     }
 }
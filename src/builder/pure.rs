@@ -1,56 +1,58 @@
 use std::marker::PhantomData;
 
 use crate::builder::Builder;
+use crate::builder::Layer;
 use crate::builder::Regex;
-use crate::Alphabet;
+use crate::builder::Stack;
 
 /// A pure regular expression builder that keeps the structure of the
 /// constructor calls in the result
+pub type Pure<S> = Stack<S, Nodes>;
+
+/// The bottom layer of a builder stack, which creates nodes without rewriting.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
-pub struct Pure<S: Alphabet> {
-    _phantom: PhantomData<S>,
+pub struct Nodes {
+    _phantom: PhantomData<()>,
 }
 
-impl<S: Alphabet> Builder for Pure<S> {
-    type Symbol = S;
-
+impl Layer for Nodes {
     #[inline]
-    fn empty_set() -> Regex<Self> {
+    fn empty_set<B: Builder>() -> Regex<B> {
         Regex::EmptySet
     }
 
     #[inline]
-    fn empty_string() -> Regex<Self> {
+    fn empty_string<B: Builder>() -> Regex<B> {
         Regex::EmptyString
     }
 
     #[inline]
-    fn symbol(value: S) -> Regex<Self> {
+    fn symbol<B: Builder>(value: B::Symbol) -> Regex<B> {
         Regex::Symbol(value)
     }
 
     #[inline]
-    fn closure(inner: Regex<Self>) -> Regex<Self> {
+    fn closure<B: Builder>(inner: Regex<B>) -> Regex<B> {
         Regex::Closure(inner.into())
     }
 
     #[inline]
-    fn concat(left: Regex<Self>, right: Regex<Self>) -> Regex<Self> {
+    fn concat<B: Builder>(left: Regex<B>, right: Regex<B>) -> Regex<B> {
         Regex::Concat(left.into(), right.into())
     }
 
     #[inline]
-    fn or(left: Regex<Self>, right: Regex<Self>) -> Regex<Self> {
+    fn or<B: Builder>(left: Regex<B>, right: Regex<B>) -> Regex<B> {
         Regex::Or(left.into(), right.into())
     }
 
     #[inline]
-    fn and(left: Regex<Self>, right: Regex<Self>) -> Regex<Self> {
+    fn and<B: Builder>(left: Regex<B>, right: Regex<B>) -> Regex<B> {
         Regex::And(left.into(), right.into())
     }
 
     #[inline]
-    fn complement(inner: Regex<Self>) -> Regex<Self> {
+    fn complement<B: Builder>(inner: Regex<B>) -> Regex<B> {
         Regex::Complement(inner.into())
     }
 }
@@ -1,4 +1,4 @@
-//! A builder implementation that produces regular expressions in approximately-similar canonical form.
+//! A builder layer that produces regular expressions in approximately-similar canonical form.
 
 use std::cmp::Ordering;
 use std::marker::PhantomData;
@@ -6,119 +6,185 @@ use std::marker::PhantomData;
 use itertools::Itertools;
 
 use crate::builder::Builder;
+use crate::builder::Layer;
+use crate::builder::Nodes;
 use crate::builder::Regex;
-use crate::Alphabet;
+use crate::builder::Stack;
 
+/// A builder that produces regular expressions in approximately-similar canonical form.
+pub type ApproximatelySimilarCanonical<S> = Stack<S, ApproximateSimilarity<Nodes>>;
+
+/// A layer that rewrites regular expressions into approximately-similar canonical form.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
-pub struct ApproximatelySimilarCanonical<S: Alphabet> {
-    _phantom: PhantomData<S>,
+pub struct ApproximateSimilarity<L: Layer> {
+    _phantom: PhantomData<L>,
 }
 
-impl<S: Alphabet> Builder for ApproximatelySimilarCanonical<S> {
-    type Symbol = S;
-
+impl<L: Layer> Layer for ApproximateSimilarity<L> {
     #[inline]
-    fn empty_set() -> Regex<Self> {
-        Regex::EmptySet
+    fn empty_set<B: Builder>() -> Regex<B> {
+        L::empty_set()
     }
 
     #[inline]
-    fn empty_string() -> Regex<Self> {
-        Regex::EmptyString
+    fn empty_string<B: Builder>() -> Regex<B> {
+        L::empty_string()
     }
 
     #[inline]
-    fn symbol(value: Self::Symbol) -> Regex<Self> {
-        Regex::Symbol(value)
+    fn symbol<B: Builder>(value: B::Symbol) -> Regex<B> {
+        L::symbol(value)
     }
 
-    fn closure(inner: Regex<Self>) -> Regex<Self> {
+    fn closure<B: Builder>(inner: Regex<B>) -> Regex<B> {
         match inner {
             // ()* --> e
-            Regex::EmptySet => Self::empty_string(),
+            Regex::EmptySet => B::empty_string(),
             // e* --> e
-            Regex::EmptyString => Self::empty_string(),
+            Regex::EmptyString => B::empty_string(),
             // e** --> e*
-            Regex::Closure(inner) => Regex::Closure(inner),
+            Regex::Closure(inner) => L::closure(*inner),
             // (build)
-            inner => Regex::Closure(inner.into()),
+            inner => L::closure(inner),
         }
     }
 
-    fn concat(left: Regex<Self>, right: Regex<Self>) -> Regex<Self> {
+    fn concat<B: Builder>(left: Regex<B>, right: Regex<B>) -> Regex<B> {
         match (left, right) {
             // 0 R --> 0
-            (Regex::EmptySet, _) | (_, Regex::EmptySet) => Self::empty_set(),
+            (Regex::EmptySet, _) | (_, Regex::EmptySet) => B::empty_set(),
             // e R --> R
             (Regex::EmptyString, inner) | (inner, Regex::EmptyString) => inner,
             // R (S T) --> (R S) T
             // (build)
             (left, right) => right
                 .into_reverse_concat_iter()
-                .chain(left.into_reverse_concat_iter())
                 .collect_vec()
                 .into_iter()
                 .rev()
-                .reduce(|l, r| Regex::Concat(l.into(), r.into()))
-                .expect("at least two items"),
+                .fold(left, L::concat),
         }
     }
 
-    fn or(left: Regex<Self>, right: Regex<Self>) -> Regex<Self> {
+    fn or<B: Builder>(left: Regex<B>, right: Regex<B>) -> Regex<B> {
         match (left, right) {
             // 0 | R --> R
             (Regex::EmptySet, inner) | (inner, Regex::EmptySet) => inner,
             // !0 | R --> !0
-            (any, _) | (_, any) if any.is_empty_set_complement() => {
-                Self::complement(Self::empty_set())
-            }
+            (any, _) | (_, any) if any.is_empty_set_complement() => B::complement(B::empty_set()),
             // R | R --> R
             // R | (S | T) --> (R | S) | T
             // S | R --> R | S
             // (build)
-            (left, right) => right
-                .into_reverse_or_iter()
-                .chain(left.into_reverse_or_iter())
-                .sorted_by(cmp)
-                .dedup()
-                .reduce(|l, r| Regex::Or(l.into(), r.into()))
-                .expect("at least two items"),
+            (left, right) => merge_spines(Spine::Or, left, right, L::or),
         }
     }
 
-    fn and(left: Regex<Self>, right: Regex<Self>) -> Regex<Self> {
+    fn and<B: Builder>(left: Regex<B>, right: Regex<B>) -> Regex<B> {
         match (left, right) {
             // 0 & R --> 0
-            (Regex::EmptySet, _) | (_, Regex::EmptySet) => Self::empty_set(),
+            (Regex::EmptySet, _) | (_, Regex::EmptySet) => B::empty_set(),
             // !0 & R --> R
             (any, inner) | (inner, any) if any.is_empty_set_complement() => inner,
             // R & R --> R
             // R & (S & T) --> (R & S) & T
             // S | R --> R | S
             // (build)
-            (left, right) => right
-                .into_reverse_and_iter()
-                .chain(left.into_reverse_and_iter())
-                .sorted_by(cmp)
-                .dedup()
-                .reduce(|l, r| Regex::And(l.into(), r.into()))
-                .expect("at least two items"),
+            (left, right) => merge_spines(Spine::And, left, right, L::and),
         }
     }
 
-    fn complement(inner: Regex<Self>) -> Regex<Self> {
+    fn complement<B: Builder>(inner: Regex<B>) -> Regex<B> {
         match inner {
             // !!R --> R
             Regex::Complement(inner) => *inner,
             // (build)
-            inner => Regex::Complement(inner.into()),
+            inner => L::complement(inner),
+        }
+    }
+
+    fn concat_all<B: Builder>(operands: impl IntoIterator<Item = Regex<B>>) -> Regex<B> {
+        let mut items = Vec::new();
+        for operand in operands {
+            match operand {
+                // 0 R --> 0
+                Regex::EmptySet => return B::empty_set(),
+                // e R --> R
+                Regex::EmptyString => {}
+                // R (S T) --> (R S) T
+                operand => {
+                    let start = items.len();
+                    items.extend(operand.into_reverse_concat_iter());
+                    items[start..].reverse();
+                }
+            }
+        }
+        // (build)
+        items
+            .into_iter()
+            .reduce(L::concat)
+            .unwrap_or_else(B::empty_string)
+    }
+
+    fn or_all<B: Builder>(operands: impl IntoIterator<Item = Regex<B>>) -> Regex<B> {
+        let mut items = Vec::new();
+        for operand in operands {
+            match operand {
+                // 0 | R --> R
+                Regex::EmptySet => {}
+                // !0 | R --> !0
+                any if any.is_empty_set_complement() => return B::complement(B::empty_set()),
+                // R | (S | T) --> (R | S) | T
+                operand => items.extend(operand.into_reverse_or_iter()),
+            }
         }
+        // R | R --> R
+        // S | R --> R | S
+        // (build)
+        items
+            .into_iter()
+            .sorted_by(cmp)
+            .dedup()
+            .reduce(L::or)
+            .unwrap_or_else(B::empty_set)
+    }
+
+    fn and_all<B: Builder>(operands: impl IntoIterator<Item = Regex<B>>) -> Regex<B> {
+        let mut items = Vec::new();
+        for operand in operands {
+            match operand {
+                // 0 & R --> 0
+                Regex::EmptySet => return B::empty_set(),
+                // !0 & R --> R
+                any if any.is_empty_set_complement() => {}
+                // R & (S & T) --> (R & S) & T
+                operand => items.extend(operand.into_reverse_and_iter()),
+            }
+        }
+        // R & R --> R
+        // S & R --> R & S
+        // (build)
+        items
+            .into_iter()
+            .sorted_by(cmp)
+            .dedup()
+            .reduce(L::and)
+            .unwrap_or_else(|| B::complement(B::empty_set()))
     }
 }
 
-impl<S: Alphabet> Regex<ApproximatelySimilarCanonical<S>> {
+impl<B: Builder> Regex<B> {
+    /// Compares this regular expression with another in the canonical order used to sort the
+    /// operands of alternations and intersections.
+    ///
+    /// Nodes of different kinds are ordered by kind, in the order of the [`Regex`] variants.
+    /// Nodes of the same kind are ordered lexicographically by their symbols and operands.
+    pub fn canonical_cmp(&self, other: &Self) -> Ordering {
+        cmp(self, other)
+    }
+
     /// Iterate in reverse over nested "concat" regular expressions.
-    fn into_reverse_concat_iter(self) -> impl Iterator<Item = Self> {
+    pub(super) fn into_reverse_concat_iter(self) -> impl Iterator<Item = Self> {
         ReverseIter(Some(self), |r| {
             if let Regex::Concat(next, value) = r {
                 (*value, Some(*next))
@@ -129,7 +195,7 @@ impl<S: Alphabet> Regex<ApproximatelySimilarCanonical<S>> {
     }
 
     /// Iterate in reverse over nested "or" regular expressions.
-    fn into_reverse_or_iter(self) -> impl Iterator<Item = Self> {
+    pub(super) fn into_reverse_or_iter(self) -> impl Iterator<Item = Self> {
         ReverseIter(Some(self), |r| {
             if let Regex::Or(next, value) = r {
                 (*value, Some(*next))
@@ -140,7 +206,7 @@ impl<S: Alphabet> Regex<ApproximatelySimilarCanonical<S>> {
     }
 
     /// Iterate in reverse over nested "and" regular expressions.
-    fn into_reverse_and_iter(self) -> impl Iterator<Item = Self> {
+    pub(super) fn into_reverse_and_iter(self) -> impl Iterator<Item = Self> {
         ReverseIter(Some(self), |r| {
             if let Regex::And(next, value) = r {
                 (*value, Some(*next))
@@ -151,7 +217,7 @@ impl<S: Alphabet> Regex<ApproximatelySimilarCanonical<S>> {
     }
 
     // Returns whether this regular expression is the complement of the empty set.
-    fn is_empty_set_complement(&self) -> bool {
+    pub(crate) fn is_empty_set_complement(&self) -> bool {
         if let Regex::Complement(inner) = self {
             matches!(inner.as_ref(), Regex::EmptySet)
         } else {
@@ -160,27 +226,126 @@ impl<S: Alphabet> Regex<ApproximatelySimilarCanonical<S>> {
     }
 }
 
-struct ReverseIter<S, F>(Option<Regex<ApproximatelySimilarCanonical<S>>>, F)
+/// The kind of nodes that make up a spine of nested alternations or intersections.
+#[derive(Clone, Copy)]
+enum Spine {
+    Or,
+    And,
+}
+
+impl Spine {
+    /// Splits a spine into the spine below its top operand, if any, and its top operand.
+    fn split<B: Builder>(self, regex: Regex<B>) -> (Option<Regex<B>>, Regex<B>) {
+        match (self, regex) {
+            (Spine::Or, Regex::Or(rest, value)) | (Spine::And, Regex::And(rest, value)) => {
+                (Some(*rest), *value)
+            }
+            (_, regex) => (None, regex),
+        }
+    }
+
+    /// Returns the top operand of a spine.
+    fn top<B: Builder>(self, regex: &Regex<B>) -> &Regex<B> {
+        match (self, regex) {
+            (Spine::Or, Regex::Or(_, value)) | (Spine::And, Regex::And(_, value)) => value,
+            (_, regex) => regex,
+        }
+    }
+
+    /// Returns the operands of a spine, from its top to its bottom.
+    fn into_operands<B: Builder>(self, regex: Regex<B>) -> impl Iterator<Item = Regex<B>> {
+        let mut rest = Some(regex);
+        std::iter::from_fn(move || {
+            let (next, value) = self.split(rest.take()?);
+            rest = next;
+            Some(value)
+        })
+    }
+
+    /// Returns whether the operands of a spine strictly increase from its bottom to its top,
+    /// as they do in spines built by this builder.
+    fn is_sorted<B: Builder>(self, mut regex: &Regex<B>) -> bool {
+        loop {
+            match (self, regex) {
+                (Spine::Or, Regex::Or(rest, value)) | (Spine::And, Regex::And(rest, value)) => {
+                    if cmp(self.top(rest), value).is_ge() {
+                        return false;
+                    }
+                    regex = rest;
+                }
+                _ => return true,
+            }
+        }
+    }
+}
+
+/// Merges two spines with sorted and deduplicated operands into a single such spine.
+///
+/// Spines are nested to the left, so the top of a spine is its greatest operand. Operands are
+/// taken from the tops of both spines until one of them is exhausted, and the remainder of the
+/// other spine is reused as is. Adding a single operand to a spine therefore only rebuilds the
+/// part of the spine with greater operands.
+///
+/// Spines built by this builder are always sorted and deduplicated, but nodes that are
+/// constructed directly may not be. If either spine is not sorted, the operands of both
+/// spines are sorted and deduplicated instead.
+fn merge_spines<B: Builder>(
+    spine: Spine,
+    left: Regex<B>,
+    right: Regex<B>,
+    build: impl Fn(Regex<B>, Regex<B>) -> Regex<B>,
+) -> Regex<B> {
+    if !spine.is_sorted(&left) || !spine.is_sorted(&right) {
+        return spine
+            .into_operands(right)
+            .chain(spine.into_operands(left))
+            .sorted_by(cmp)
+            .dedup()
+            .reduce(build)
+            .expect("at least two operands");
+    }
+    let mut left = Some(left);
+    let mut right = Some(right);
+    let mut tops = Vec::new();
+    let base = loop {
+        match (left.take(), right.take()) {
+            (Some(l), Some(r)) => match cmp(spine.top(&l), spine.top(&r)) {
+                Ordering::Greater => {
+                    let (rest, value) = spine.split(l);
+                    tops.push(value);
+                    (left, right) = (rest, Some(r));
+                }
+                Ordering::Less => {
+                    let (rest, value) = spine.split(r);
+                    tops.push(value);
+                    (left, right) = (Some(l), rest);
+                }
+                Ordering::Equal => {
+                    let (left_rest, value) = spine.split(l);
+                    let (right_rest, _) = spine.split(r);
+                    tops.push(value);
+                    (left, right) = (left_rest, right_rest);
+                }
+            },
+            (rest, None) | (None, rest) => break rest,
+        }
+    };
+    let mut tops = tops.into_iter().rev();
+    let base = base.or_else(|| tops.next()).expect("at least one operand");
+    tops.fold(base, build)
+}
+
+struct ReverseIter<B, F>(Option<Regex<B>>, F)
 where
-    S: Alphabet,
-    F: Fn(
-        Regex<ApproximatelySimilarCanonical<S>>,
-    ) -> (
-        Regex<ApproximatelySimilarCanonical<S>>,
-        Option<Regex<ApproximatelySimilarCanonical<S>>>,
-    );
-
-impl<S, F> Iterator for ReverseIter<S, F>
+    B: Builder,
+    F: Fn(Regex<B>) -> (Regex<B>, Option<Regex<B>>);
+
+impl<B, F> Iterator for ReverseIter<B, F>
 where
-    S: Alphabet,
-    F: Fn(
-        Regex<ApproximatelySimilarCanonical<S>>,
-    ) -> (
-        Regex<ApproximatelySimilarCanonical<S>>,
-        Option<Regex<ApproximatelySimilarCanonical<S>>>,
-    ),
+    B: Builder,
+    F: Fn(Regex<B>) -> (Regex<B>, Option<Regex<B>>),
 {
-    type Item = Regex<ApproximatelySimilarCanonical<S>>;
+    type Item = Regex<B>;
     fn next(&mut self) -> Option<Self::Item> {
         let mut r = None;
         std::mem::swap(&mut r, &mut self.0);
@@ -194,13 +359,13 @@ where
     }
 }
 
-fn cmp<B: Builder>(left: &Regex<B>, right: &Regex<B>) -> Ordering {
+pub(super) fn cmp<B: Builder>(left: &Regex<B>, right: &Regex<B>) -> Ordering {
     match (left, right) {
         (Regex::Symbol(left_value), Regex::Symbol(right_value)) => left_value.cmp(right_value),
         (Regex::Concat(left_left, left_right), Regex::Concat(right_left, right_right)) => {
             cmp(left_left, right_left).then(cmp(left_right, right_right))
         }
-        (Regex::Closure(left_inner), Regex::Closure(right_inner)) => cmp(&left_inner, &right_inner),
+        (Regex::Closure(left_inner), Regex::Closure(right_inner)) => cmp(left_inner, right_inner),
         (Regex::Or(left_left, left_right), Regex::Or(right_left, right_right)) => {
             cmp(left_left, right_left).then(cmp(left_right, right_right))
         }
@@ -208,7 +373,7 @@ fn cmp<B: Builder>(left: &Regex<B>, right: &Regex<B>) -> Ordering {
             cmp(left_left, right_left).then(cmp(left_right, right_right))
         }
         (Regex::Complement(left_inner), Regex::Complement(right_inner)) => {
-            cmp(&left_inner, &right_inner)
+            cmp(left_inner, right_inner)
         }
         (left, right) => rank(left).cmp(&rank(right)),
     }
@@ -234,12 +399,12 @@ mod tests {
 
     use super::*;
 
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+    type PureRegex = Regex<Pure<usize>>;
+
     #[test]
     fn test_canonical_forms() {
-        let tests: Vec<(
-            Regex<ApproximatelySimilarCanonical<usize>>,
-            Regex<Pure<usize>>,
-        )> = vec![
+        let tests: Vec<(AscRegex, PureRegex)> = vec![
             (().r(), ().r()),
             (().r().c(), [].r()),
             ([].r().c(), [].r()),
@@ -255,12 +420,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_canonical_cmp() {
+        let tests: Vec<(PureRegex, PureRegex, Ordering)> = vec![
+            (().r(), [].r(), Ordering::Less),
+            (11.s(), 42.s(), Ordering::Less),
+            (42.s(), 11.s(), Ordering::Greater),
+            (11.s() | 42.s(), 11.s() | 42.s(), Ordering::Equal),
+            (!11.s(), 11.s() & 42.s(), Ordering::Greater),
+            (42.s().c(), [11.s(), 42.s()].r(), Ordering::Greater),
+        ];
+        for test in tests {
+            assert_eq!(test.2, test.0.canonical_cmp(&test.1));
+        }
+    }
+
+    #[test]
+    fn test_unsorted_spine() {
+        let unsorted: AscRegex = Regex::Or(Box::new(42.s()), Box::new(11.s()));
+        assert_eq!(7.s() | 11.s() | 42.s(), unsorted.clone() | 7.s());
+        assert_eq!(7.s() | 11.s() | 42.s(), 7.s() | unsorted.clone());
+        assert_eq!(11.s() | 42.s(), unsorted | 42.s());
+        let unsorted: AscRegex = Regex::And(Box::new(42.s()), Box::new(11.s()));
+        assert_eq!(7.s() & 11.s() & 42.s(), unsorted & 7.s());
+    }
+
     #[test]
     fn test_equivalent_forms() {
-        let tests: Vec<(
-            Regex<ApproximatelySimilarCanonical<usize>>,
-            Regex<ApproximatelySimilarCanonical<usize>>,
-        )> = vec![
+        let tests: Vec<(AscRegex, AscRegex)> = vec![
             (11.s() & 42.s() & 7.s(), 7.s() & 42.s() & 11.s()),
             (11.s() & 7.s() & 42.s(), 42.s() & 7.s() & 11.s()),
             (11.s() | 42.s() | 7.s(), 7.s() | 42.s() | 11.s()),
@@ -276,4 +463,71 @@ mod tests {
             assert_eq!(test.1, test.0.rebuild());
         }
     }
+
+    #[test]
+    fn test_merged_spines() {
+        let symbols = [5, 3, 8, 1, 9, 3, 7, 2, 6, 1, 4];
+        let expected_or: PureRegex = (1..=9).map(|s| s.s()).reduce(|l, r| l | r).unwrap();
+        let expected_and: PureRegex = (1..=9).map(|s| s.s()).reduce(|l, r| l & r).unwrap();
+        let tests: Vec<(AscRegex, AscRegex)> = vec![
+            (
+                symbols
+                    .into_iter()
+                    .map(|s| s.s())
+                    .reduce(|l, r| l | r)
+                    .unwrap(),
+                symbols
+                    .into_iter()
+                    .map(|s| s.s())
+                    .reduce(|l, r| l & r)
+                    .unwrap(),
+            ),
+            (
+                symbols
+                    .into_iter()
+                    .map(|s| s.s())
+                    .reduce(|l, r| r | l)
+                    .unwrap(),
+                symbols
+                    .into_iter()
+                    .map(|s| s.s())
+                    .reduce(|l, r| r & l)
+                    .unwrap(),
+            ),
+            (
+                (1.s() | 4.s() | 7.s() | 8.s()) | (2.s() | 3.s() | 4.s() | 5.s() | 6.s() | 9.s()),
+                (1.s() & 4.s() & 7.s() & 8.s()) & (2.s() & 3.s() & 4.s() & 5.s() & 6.s() & 9.s()),
+            ),
+        ];
+        for (or, and) in tests {
+            assert_eq!(expected_or, or.rebuild());
+            assert_eq!(expected_and, and.rebuild());
+        }
+    }
+
+    #[test]
+    fn test_n_ary_forms() {
+        let tests: Vec<Vec<AscRegex>> = vec![
+            vec![],
+            vec![11.s()],
+            vec![42.s(), 11.s(), 42.s()],
+            vec![11.s() | 42.s(), 7.s() | 11.s(), [].r()],
+            vec![11.s() & 42.s(), 7.s() & 11.s(), [].r()],
+            vec![[11.s(), 42.s()].r(), [].r(), [7.s(), 11.s()].r()],
+            vec![11.s(), ().r(), 42.s()],
+            vec![11.s(), !().r(), 42.s()],
+            vec![!11.s(), 42.s().c(), [11.s(), 7.s()].r()],
+        ];
+        for operands in tests {
+            let fold = |op: fn(AscRegex, AscRegex) -> AscRegex, unit: AscRegex| {
+                operands.iter().cloned().fold(unit, op)
+            };
+            assert_eq!(
+                fold(Regex::concat, [].r()),
+                Regex::concat_all(operands.clone())
+            );
+            assert_eq!(fold(Regex::or, ().r()), Regex::or_all(operands.clone()));
+            assert_eq!(fold(Regex::and, !().r()), Regex::and_all(operands.clone()));
+        }
+    }
 }
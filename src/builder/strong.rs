@@ -0,0 +1,495 @@
+//! A builder layer that applies more similarity rules than the approximately-similar canonical
+//! builder.
+
+use std::marker::PhantomData;
+
+use itertools::Itertools;
+
+use crate::builder::similarity::cmp;
+use crate::builder::Builder;
+use crate::builder::Layer;
+use crate::builder::Nodes;
+use crate::builder::Regex;
+use crate::builder::Stack;
+
+/// A builder that extends the rules of [`ApproximatelySimilarCanonical`](super::ApproximatelySimilarCanonical)
+/// with rules for nullability, complementary operands, closures, and common prefixes. This
+/// reduces the number of distinct derivatives further, at the cost of more work per constructor
+/// call.
+pub type StronglyCanonical<S> = Stack<S, StrongSimilarity<Nodes>>;
+
+/// A layer that rewrites regular expressions using the rules of [`StronglyCanonical`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct StrongSimilarity<L: Layer> {
+    _phantom: PhantomData<L>,
+}
+
+impl<L: Layer> Layer for StrongSimilarity<L> {
+    #[inline]
+    fn empty_set<B: Builder>() -> Regex<B> {
+        L::empty_set()
+    }
+
+    #[inline]
+    fn empty_string<B: Builder>() -> Regex<B> {
+        L::empty_string()
+    }
+
+    #[inline]
+    fn symbol<B: Builder>(value: B::Symbol) -> Regex<B> {
+        L::symbol(value)
+    }
+
+    fn closure<B: Builder>(inner: Regex<B>) -> Regex<B> {
+        match inner {
+            // ()* --> e
+            Regex::EmptySet => B::empty_string(),
+            // e* --> e
+            Regex::EmptyString => B::empty_string(),
+            // e** --> e*
+            Regex::Closure(inner) => L::closure(*inner),
+            // (e | R)* --> R*
+            inner @ Regex::Or(_, _) if has_empty_string_operand(&inner) => B::closure(
+                inner
+                    .into_reverse_or_iter()
+                    .filter(|r| !matches!(r, Regex::EmptyString))
+                    .reduce(|r, l| B::or(l, r))
+                    .unwrap_or_else(B::empty_string),
+            ),
+            // (build)
+            inner => L::closure(inner),
+        }
+    }
+
+    fn concat<B: Builder>(left: Regex<B>, right: Regex<B>) -> Regex<B> {
+        match (left, right) {
+            // 0 R --> 0
+            (Regex::EmptySet, _) | (_, Regex::EmptySet) => B::empty_set(),
+            // e R --> R
+            (Regex::EmptyString, inner) | (inner, Regex::EmptyString) => inner,
+            // R (S T) --> (R S) T
+            // (build)
+            (left, right) => right
+                .into_reverse_concat_iter()
+                .collect_vec()
+                .into_iter()
+                .rev()
+                .fold(left, L::concat),
+        }
+    }
+
+    fn or<B: Builder>(left: Regex<B>, right: Regex<B>) -> Regex<B> {
+        match (left, right) {
+            // 0 | R --> R
+            (Regex::EmptySet, inner) | (inner, Regex::EmptySet) => inner,
+            // !0 | R --> !0
+            (any, _) | (_, any) if any.is_empty_set_complement() => B::complement(B::empty_set()),
+            // R | R --> R
+            // R | (S | T) --> (R | S) | T
+            // S | R --> R | S
+            (left, right) => Self::or_operands(merge_operands(
+                left.into_reverse_or_iter(),
+                right.into_reverse_or_iter(),
+            )),
+        }
+    }
+
+    fn and<B: Builder>(left: Regex<B>, right: Regex<B>) -> Regex<B> {
+        match (left, right) {
+            // 0 & R --> 0
+            (Regex::EmptySet, _) | (_, Regex::EmptySet) => B::empty_set(),
+            // !0 & R --> R
+            (any, inner) | (inner, any) if any.is_empty_set_complement() => inner,
+            // R & R --> R
+            // R & (S & T) --> (R & S) & T
+            // S | R --> R | S
+            (left, right) => Self::and_operands(merge_operands(
+                left.into_reverse_and_iter(),
+                right.into_reverse_and_iter(),
+            )),
+        }
+    }
+
+    fn complement<B: Builder>(inner: Regex<B>) -> Regex<B> {
+        match inner {
+            // !!R --> R
+            Regex::Complement(inner) => *inner,
+            // (build)
+            inner => L::complement(inner),
+        }
+    }
+
+    fn concat_all<B: Builder>(operands: impl IntoIterator<Item = Regex<B>>) -> Regex<B> {
+        let mut items = Vec::new();
+        for operand in operands {
+            match operand {
+                // 0 R --> 0
+                Regex::EmptySet => return B::empty_set(),
+                // e R --> R
+                Regex::EmptyString => {}
+                // R (S T) --> (R S) T
+                operand => {
+                    let start = items.len();
+                    items.extend(operand.into_reverse_concat_iter());
+                    items[start..].reverse();
+                }
+            }
+        }
+        // (build)
+        items
+            .into_iter()
+            .reduce(L::concat)
+            .unwrap_or_else(B::empty_string)
+    }
+
+    fn or_all<B: Builder>(operands: impl IntoIterator<Item = Regex<B>>) -> Regex<B> {
+        let mut items = Vec::new();
+        for operand in operands {
+            match operand {
+                // 0 | R --> R
+                Regex::EmptySet => {}
+                // !0 | R --> !0
+                any if any.is_empty_set_complement() => return B::complement(B::empty_set()),
+                // R | (S | T) --> (R | S) | T
+                operand => items.extend(operand.into_reverse_or_iter()),
+            }
+        }
+        if items.is_empty() {
+            return B::empty_set();
+        }
+        // R | R --> R
+        // S | R --> R | S
+        Self::or_operands(items.into_iter().sorted_by(cmp).dedup().collect())
+    }
+
+    fn and_all<B: Builder>(operands: impl IntoIterator<Item = Regex<B>>) -> Regex<B> {
+        let mut items = Vec::new();
+        for operand in operands {
+            match operand {
+                // 0 & R --> 0
+                Regex::EmptySet => return B::empty_set(),
+                // !0 & R --> R
+                any if any.is_empty_set_complement() => {}
+                // R & (S & T) --> (R & S) & T
+                operand => items.extend(operand.into_reverse_and_iter()),
+            }
+        }
+        if items.is_empty() {
+            return B::complement(B::empty_set());
+        }
+        // R & R --> R
+        // S & R --> R & S
+        Self::and_operands(items.into_iter().sorted_by(cmp).dedup().collect())
+    }
+}
+
+impl<L: Layer> StrongSimilarity<L> {
+    /// Build an alternation from sorted and deduplicated operands.
+    ///
+    /// After a rule rewrites some operands, the operands are sorted again and all rules are
+    /// applied to them again. The rules therefore only see sorted operands, so the result does
+    /// not depend on the order of the operands, or on the position of the rewritten ones.
+    fn or_operands<B: Builder>(mut operands: Vec<Regex<B>>) -> Regex<B> {
+        // R | !S --> !0  (if S is R, or S is subsumed by R)
+        if operands.iter().any(|r| {
+            operands.iter().any(|s| {
+                matches!(s, Regex::Complement(inner)
+                    if inner.as_ref() == r || is_subsumed_by_closure(inner, r))
+            })
+        }) {
+            return B::complement(B::empty_set());
+        }
+        // R | S* --> S*  (if R is S, S S*, or an operand of S)
+        if let Some(subsumed) = operands
+            .iter()
+            .position(|r| operands.iter().any(|s| is_subsumed_by_closure(r, s)))
+        {
+            operands.remove(subsumed);
+            return Self::or_resorted(operands);
+        }
+        // e | R --> R  (if R is nullable)
+        if let Some(empty) = operands
+            .iter()
+            .position(|r| matches!(r, Regex::EmptyString))
+        {
+            if operands
+                .iter()
+                .any(|r| !matches!(r, Regex::EmptyString) && r.is_nullable())
+            {
+                operands.remove(empty);
+                return Self::or_resorted(operands);
+            }
+        }
+        // R R* | S --> R* | S  (if S is nullable)
+        if operands.iter().any(|r| r.is_nullable()) {
+            if let Some(plus) = operands.iter().position(is_plus) {
+                let Regex::Concat(_, closure) = operands.remove(plus) else {
+                    unreachable!("plus is a concat");
+                };
+                operands.push(*closure);
+                return Self::or_resorted(operands);
+            }
+        }
+        // R S | R T --> R (S | T)  (if smaller)
+        for (i, j) in (0..operands.len()).tuple_combinations() {
+            if let Some(factored) = factor_prefix(&operands[i], &operands[j]) {
+                operands.remove(j);
+                operands.remove(i);
+                operands.push(factored);
+                return Self::or_resorted(operands);
+            }
+        }
+        // (build)
+        operands
+            .into_iter()
+            .reduce(L::or)
+            .expect("at least two items")
+    }
+
+    /// Build an alternation from operands that were rewritten by a rule, by sorting and
+    /// deduplicating them again before applying the rules.
+    fn or_resorted<B: Builder>(operands: Vec<Regex<B>>) -> Regex<B> {
+        let mut operands = operands
+            .into_iter()
+            .flat_map(Regex::into_reverse_or_iter)
+            .sorted_by(cmp)
+            .dedup()
+            .collect_vec();
+        match operands.len() {
+            0 => B::empty_set(),
+            1 => operands.pop().expect("one operand"),
+            _ => Self::or_operands(operands),
+        }
+    }
+
+    /// Build an intersection from sorted and deduplicated operands.
+    fn and_operands<B: Builder>(operands: Vec<Regex<B>>) -> Regex<B> {
+        // R & !R --> 0
+        if operands.iter().any(|r| has_complement_in(r, &operands)) {
+            return B::empty_set();
+        }
+        // e & R --> e  (if R is nullable)
+        // e & R --> 0  (otherwise)
+        if operands.iter().any(|r| matches!(r, Regex::EmptyString)) {
+            if operands.iter().all(|r| r.is_nullable()) {
+                return B::empty_string();
+            } else {
+                return B::empty_set();
+            }
+        }
+        // a & b --> 0  (if a != b)
+        if operands
+            .iter()
+            .filter(|r| matches!(r, Regex::Symbol(_)))
+            .nth(1)
+            .is_some()
+        {
+            return B::empty_set();
+        }
+        // (build)
+        operands
+            .into_iter()
+            .reduce(L::and)
+            .expect("at least two items")
+    }
+}
+
+/// Merges the operands of two spines with sorted and deduplicated operands, which are given
+/// in reverse order, into a single sorted and deduplicated vector.
+///
+/// The spines of regular expressions built by this builder are sorted and deduplicated, but
+/// those of nodes that are constructed directly may not be. If the merged operands are not
+/// sorted, they are sorted and deduplicated again.
+fn merge_operands<B: Builder>(
+    left: impl Iterator<Item = Regex<B>>,
+    right: impl Iterator<Item = Regex<B>>,
+) -> Vec<Regex<B>> {
+    let mut operands = left
+        .merge_by(right, |l, r| cmp(l, r).is_ge())
+        .dedup()
+        .collect_vec();
+    operands.reverse();
+    if !operands
+        .windows(2)
+        .all(|pair| cmp(&pair[0], &pair[1]).is_lt())
+    {
+        operands.sort_by(cmp);
+        operands.dedup();
+    }
+    operands
+}
+
+/// Returns `R (S | T)` for `R S` and `R T`, if it is smaller than the alternation.
+fn factor_prefix<B: Builder>(left: &Regex<B>, right: &Regex<B>) -> Option<Regex<B>> {
+    let or_size = left.size() + right.size() + 1;
+    let left = left.clone().into_reverse_concat_iter().collect_vec();
+    let right = right.clone().into_reverse_concat_iter().collect_vec();
+    let prefix_len = left
+        .iter()
+        .rev()
+        .zip(right.iter().rev())
+        .take_while(|(l, r)| l == r)
+        .count();
+    if prefix_len == 0 {
+        return None;
+    }
+    let concat = |items: &[Regex<B>]| {
+        items
+            .iter()
+            .rev()
+            .cloned()
+            .reduce(B::concat)
+            .unwrap_or_else(B::empty_string)
+    };
+    let prefix = concat(&left[left.len() - prefix_len..]);
+    let left_rest = concat(&left[..left.len() - prefix_len]);
+    let right_rest = concat(&right[..right.len() - prefix_len]);
+    let factored = B::concat(prefix, B::or(left_rest, right_rest));
+    if factored.size() < or_size {
+        Some(factored)
+    } else {
+        None
+    }
+}
+
+/// Returns whether the complement of the regular expression is one of the operands.
+fn has_complement_in<B: Builder>(regex: &Regex<B>, operands: &[Regex<B>]) -> bool {
+    operands
+        .iter()
+        .any(|r| matches!(r, Regex::Complement(inner) if inner.as_ref() == regex))
+}
+
+/// Returns whether the regular expression is an alternation with the empty string as one of
+/// its operands.
+fn has_empty_string_operand<B: Builder>(regex: &Regex<B>) -> bool {
+    match regex {
+        Regex::EmptyString => true,
+        Regex::Or(left, right) => has_empty_string_operand(left) || has_empty_string_operand(right),
+        _ => false,
+    }
+}
+
+/// Returns whether the language of the regular expression is included in that of the other,
+/// because the other is a closure `S*` and the regular expression is `S`, `S S*`, or an
+/// operand of `S`.
+fn is_subsumed_by_closure<B: Builder>(regex: &Regex<B>, other: &Regex<B>) -> bool {
+    let Regex::Closure(inner) = other else {
+        return false;
+    };
+    regex == inner.as_ref()
+        || matches!(regex, Regex::Concat(left, right) if left == inner && right.as_ref() == other)
+        || inner
+            .clone()
+            .into_reverse_or_iter()
+            .any(|operand| operand == *regex)
+}
+
+/// Returns whether the regular expression has the form `R R*`.
+fn is_plus<B: Builder>(regex: &Regex<B>) -> bool {
+    match regex {
+        Regex::Concat(left, right) => {
+            matches!(right.as_ref(), Regex::Closure(inner) if inner == left)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::Pure;
+    use crate::ops::*;
+
+    use super::*;
+
+    type StrongRegex = Regex<StronglyCanonical<usize>>;
+    type PureRegex = Regex<Pure<usize>>;
+
+    #[test]
+    fn test_canonical_forms() {
+        let tests: Vec<(StrongRegex, PureRegex)> = vec![
+            ((1.s() | [].r()).c(), 1.s().c()),
+            ([].r() | [1.s(), 1.s().c()].r(), 1.s().c()),
+            ([].r() | [1.s(), 1.s().c()].r() | 2.s(), 2.s() | 1.s().c()),
+            ([].r() | 1.s().c(), 1.s().c()),
+            (1.s() | !1.s(), !().r()),
+            (1.s() & !1.s(), ().r()),
+            ([].r() & 1.s(), ().r()),
+            ([].r() & 1.s().c(), [].r()),
+            (1.s() & 2.s(), ().r()),
+            (
+                [1.s(), 2.s(), 3.s()].r() | [1.s(), 2.s(), 4.s()].r(),
+                [1.s(), 2.s(), (3.s() | 4.s())].r(),
+            ),
+            (
+                [1.s(), 2.s()].r() | [3.s(), 2.s()].r(),
+                [1.s(), 2.s()].r() | [3.s(), 2.s()].r(),
+            ),
+        ];
+        for test in tests {
+            assert_eq!(test.1, test.0.rebuild());
+        }
+    }
+
+    #[test]
+    fn test_n_ary_forms() {
+        let tests: Vec<(StrongRegex, PureRegex)> = vec![
+            (Regex::or_all([]), ().r()),
+            (Regex::and_all([]), !().r()),
+            (Regex::concat_all([]), [].r()),
+            (Regex::or_all([2.s(), 1.s(), 2.s()]), 1.s() | 2.s()),
+            (Regex::or_all([[].r(), 1.s(), 1.s().c()]), 1.s().c()),
+            (Regex::and_all([1.s(), [].r(), 1.s().c()]), ().r()),
+            (Regex::and_all([1.s().c(), !1.s()]), 1.s().c() & !1.s()),
+            (
+                Regex::or_all([[1.s(), 2.s()].r(), [1.s(), 3.s()].r()]),
+                [1.s(), (2.s() | 3.s())].r(),
+            ),
+            (
+                Regex::concat_all([[1.s(), 2.s()].r(), [].r(), 3.s()]),
+                [1.s(), 2.s(), 3.s()].r(),
+            ),
+        ];
+        for test in tests {
+            assert_eq!(test.1, test.0.rebuild());
+        }
+    }
+
+    #[test]
+    fn test_operand_order() {
+        let operands: Vec<StrongRegex> = vec![
+            1.s(),
+            2.s(),
+            [].r(),
+            1.s().c(),
+            [1.s(), 1.s().c()].r(),
+            !1.s(),
+            [2.s(), 2.s().c()].r(),
+            (1.s() | 2.s()).c(),
+        ];
+        for (a, b, c) in operands.iter().cloned().tuple_combinations() {
+            let expected = Regex::or_all([a.clone(), b.clone(), c.clone()]);
+            let orders = [
+                Regex::or_all([c.clone(), b.clone(), a.clone()]),
+                (a.clone() | b.clone()) | c.clone(),
+                a.clone() | (b.clone() | c.clone()),
+                (c.clone() | a.clone()) | b.clone(),
+                (b.clone() | c.clone()) | a.clone(),
+            ];
+            for actual in orders {
+                assert_eq!(expected, actual, "{} | {} | {}", a, b, c);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unsorted_spine() {
+        let unsorted: StrongRegex = Regex::Or(Box::new(42.s()), Box::new(11.s()));
+        assert_eq!(
+            Regex::or_all([7.s(), 11.s(), 42.s()]),
+            unsorted.clone() | 7.s()
+        );
+        assert_eq!(Regex::or_all([11.s(), 42.s()]), unsorted | 42.s());
+        let unsorted: StrongRegex = Regex::And(Box::new(42.s()), Box::new(11.s()));
+        assert_eq!(Regex::and_all([7.s(), 11.s(), 42.s()]), unsorted & 7.s());
+    }
+}
@@ -0,0 +1,126 @@
+//! Generate source code for matchers of finite automata.
+
+use std::fmt::Debug;
+use std::fmt::Write;
+
+use itertools::Itertools;
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::StateId;
+use crate::Alphabet;
+
+/// A symbol type that can be used in generated Rust code, by
+/// [`FiniteAutomaton::codegen_rust`].
+///
+/// Symbols are passed by value, so the type must be `Copy`. Its name, as returned by
+/// [`std::any::type_name`], must be usable without imports, and the `Debug` representation of
+/// its values must be a valid Rust pattern. This is the case for the primitive integer types,
+/// `char`, and `bool`, for which this trait is implemented.
+pub trait RustLiteral: Alphabet + Copy + Debug {}
+
+macro_rules! impl_rust_literal {
+    ($($ty:ty),*) => {
+        $(
+            impl RustLiteral for $ty {}
+        )*
+    };
+}
+
+impl_rust_literal!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, char, bool);
+
+impl<S: RustLiteral, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Returns the Rust source code of a standalone function with the given name, that returns
+    /// whether a sequence of symbols is accepted by this automaton.
+    ///
+    /// The generated code does not depend on this crate, or on the standard library, and can
+    /// be written to a file by a build script and included using `include!`. Symbols are
+    /// rendered as literal patterns using their `Debug` representation, which is why the
+    /// symbol type must implement [`RustLiteral`].
+    pub fn codegen_rust(&self, name: &str) -> String {
+        let mut code = String::new();
+        self.write_rust(&mut code, name)
+            .expect("can write to string");
+        code
+    }
+
+    fn write_rust(&self, code: &mut String, name: &str) -> std::fmt::Result {
+        let symbol_type = std::any::type_name::<S>();
+        writeln!(
+            code,
+            "pub fn {}<I: IntoIterator<Item = {}>>(symbols: I) -> bool {{",
+            name, symbol_type
+        )?;
+        writeln!(code, "    let mut state: usize = 0;")?;
+        writeln!(code, "    for symbol in symbols {{")?;
+        writeln!(code, "        state = match (state, symbol) {{")?;
+        for state in 0..self.state_count() {
+            for (symbol, target) in self
+                .symbol_transitions(state)
+                .sorted_by(|(left, _), (right, _)| left.cmp(right))
+            {
+                writeln!(code, "            ({}, {:?}) => {},", state, symbol, target)?;
+            }
+            writeln!(
+                code,
+                "            ({}, _) => {},",
+                state,
+                self.default_transition(state)
+            )?;
+        }
+        writeln!(code, "            _ => unreachable!(),")?;
+        writeln!(code, "        }};")?;
+        writeln!(code, "    }}")?;
+        let accepting = (0..self.state_count())
+            .filter(|state| self.is_accepting(*state))
+            .join(" | ");
+        if accepting.is_empty() {
+            writeln!(code, "    let _ = state;")?;
+            writeln!(code, "    false")?;
+        } else {
+            writeln!(code, "    matches!(state, {})", accepting)?;
+        }
+        writeln!(code, "}}")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    #[test]
+    fn test_codegen_rust() {
+        let r: Regex<ApproximatelySimilarCanonical<u8>> = [1.s(), 2.s().c()].r();
+        let expected = r#"pub fn is_match<I: IntoIterator<Item = u8>>(symbols: I) -> bool {
+    let mut state: usize = 0;
+    for symbol in symbols {
+        state = match (state, symbol) {
+            (0, 1) => 1,
+            (0, 2) => 2,
+            (0, _) => 2,
+            (1, 1) => 2,
+            (1, 2) => 1,
+            (1, _) => 2,
+            (2, 1) => 2,
+            (2, 2) => 2,
+            (2, _) => 2,
+            _ => unreachable!(),
+        };
+    }
+    matches!(state, 1)
+}
+"#;
+        assert_eq!(expected, r.to_automaton().codegen_rust("is_match"));
+    }
+
+    #[test]
+    fn test_codegen_rust_char() {
+        let r: Regex<ApproximatelySimilarCanonical<char>> = ['\''.s(), '\\'.s()].r();
+        let code = r.to_automaton().codegen_rust("is_match");
+        assert!(code.contains("Item = char"), "{}", code);
+        assert!(code.contains("(0, '\\'') => "), "{}", code);
+        assert!(code.contains("(1, '\\\\') => "), "{}", code);
+    }
+}
@@ -0,0 +1,160 @@
+//! Adapters to use regular expressions as `nom` parsers.
+
+use std::borrow::Borrow;
+
+use nom::error::ErrorKind;
+use nom::error::ParseError;
+use nom::IResult;
+use nom::Parser;
+
+use crate::builder::Builder;
+use crate::builder::Regex;
+
+/// A `nom` parser that recognizes a prefix of its input that is in the language of a regular
+/// expression, and returns it as output.
+///
+/// The input is treated as complete. If no prefix matches, the parser fails with an error of
+/// kind [`ErrorKind::RegexpMatch`].
+#[derive(Clone)]
+pub struct RegexParser<B: Builder> {
+    regex: Regex<B>,
+    longest: bool,
+}
+
+impl<B: Builder> Regex<B> {
+    /// Returns a `nom` parser that recognizes the longest prefix of its input that is in the
+    /// language of this regular expression.
+    pub fn longest_prefix_parser(&self) -> RegexParser<B> {
+        RegexParser {
+            regex: self.clone(),
+            longest: true,
+        }
+    }
+
+    /// Returns a `nom` parser that recognizes the shortest prefix of its input that is in the
+    /// language of this regular expression.
+    pub fn shortest_prefix_parser(&self) -> RegexParser<B> {
+        RegexParser {
+            regex: self.clone(),
+            longest: false,
+        }
+    }
+}
+
+impl<B: Builder> RegexParser<B> {
+    /// Returns the end of the longest or shortest matching prefix of the given symbols, which
+    /// are given together with the position after them in the input.
+    fn matching_prefix<T>(&self, symbols: impl IntoIterator<Item = (usize, T)>) -> Option<usize>
+    where
+        T: Borrow<B::Symbol>,
+    {
+        let mut regex = self.regex.clone();
+        let mut end = regex.is_nullable().then_some(0);
+        if end.is_some() && !self.longest {
+            return end;
+        }
+        for (after, symbol) in symbols {
+            regex = regex.derive(symbol.borrow());
+            if matches!(regex, Regex::EmptySet) {
+                break;
+            }
+            if regex.is_nullable() {
+                end = Some(after);
+                if !self.longest {
+                    break;
+                }
+            }
+        }
+        end
+    }
+}
+
+impl<'a, B, E> Parser<&'a [B::Symbol], &'a [B::Symbol], E> for RegexParser<B>
+where
+    B: Builder,
+    E: ParseError<&'a [B::Symbol]>,
+{
+    fn parse(&mut self, input: &'a [B::Symbol]) -> IResult<&'a [B::Symbol], &'a [B::Symbol], E> {
+        let symbols = input
+            .iter()
+            .enumerate()
+            .map(|(idx, symbol)| (idx + 1, symbol));
+        match self.matching_prefix(symbols) {
+            Some(len) => Ok((&input[len..], &input[..len])),
+            None => Err(nom::Err::Error(E::from_error_kind(
+                input,
+                ErrorKind::RegexpMatch,
+            ))),
+        }
+    }
+}
+
+impl<'a, B, E> Parser<&'a str, &'a str, E> for RegexParser<B>
+where
+    B: Builder<Symbol = char>,
+    E: ParseError<&'a str>,
+{
+    fn parse(&mut self, input: &'a str) -> IResult<&'a str, &'a str, E> {
+        let chars = input
+            .char_indices()
+            .map(|(offset, c)| (offset + c.len_utf8(), c));
+        match self.matching_prefix(chars) {
+            Some(offset) => Ok((&input[offset..], &input[..offset])),
+            None => Err(nom::Err::Error(E::from_error_kind(
+                input,
+                ErrorKind::RegexpMatch,
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::error::Error;
+    use nom::multi::many1;
+    use nom::sequence::pair;
+
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::ops::*;
+
+    use super::*;
+
+    type AscRegex<S> = Regex<ApproximatelySimilarCanonical<S>>;
+
+    #[test]
+    fn test_parse_slice() {
+        let regex: AscRegex<usize> = [1.s(), 2.s().c()].r();
+        let input = [1, 2, 2, 1, 3];
+
+        let result: IResult<_, _, Error<_>> = regex.longest_prefix_parser().parse(&input[..]);
+        assert_eq!(Ok((&input[3..], &input[..3])), result);
+
+        let result: IResult<_, _, Error<_>> = regex.shortest_prefix_parser().parse(&input[..]);
+        assert_eq!(Ok((&input[1..], &input[..1])), result);
+
+        let result: IResult<_, _, Error<_>> = regex.longest_prefix_parser().parse(&input[4..]);
+        assert_eq!(
+            Err(nom::Err::Error(Error::new(
+                &input[4..],
+                ErrorKind::RegexpMatch
+            ))),
+            result
+        );
+    }
+
+    #[test]
+    fn test_parse_str() {
+        let word: AscRegex<char> = ('a'.s() | 'ä'.s()).c() & !().r().c();
+        let space: AscRegex<char> = ' '.s();
+        let mut words = many1(pair(
+            word.longest_prefix_parser(),
+            space.longest_prefix_parser(),
+        ));
+
+        let result: IResult<_, _, Error<_>> = words.parse("aä a b");
+        assert_eq!(Ok(("b", vec![("aä", " "), ("a", " ")])), result);
+
+        let result: IResult<_, _, Error<_>> = word.shortest_prefix_parser().parse("äa");
+        assert_eq!(Ok(("a", "ä")), result);
+    }
+}
@@ -1,13 +1,17 @@
 //! Derivation and derivation-based matching for regular expressions.
 
 use std::borrow::Borrow;
+use std::collections::BTreeSet;
 use std::collections::HashSet;
 
+use itertools::Either;
 use itertools::Itertools;
 
+use crate::builder::ApproximatelySimilarCanonical;
 use crate::builder::Builder;
 use crate::builder::Regex;
 use crate::Alphabet;
+use crate::FiniteAlphabet;
 
 impl<B: Builder> Regex<B> {
     /// Returns the derivative of this regular expression w.r.t. the given symbols.
@@ -29,30 +33,135 @@ impl<B: Builder> Regex<B> {
     }
 
     pub(crate) fn derive_symbols(&self, symbol: &Symbols<B::Symbol>) -> Regex<B> {
+        self.derive_symbols_nullable(symbol).0
+    }
+
+    /// Returns the derivative of this regular expression w.r.t. the given symbols, together
+    /// with the nullability of this regular expression.
+    ///
+    /// Computing both in the same traversal avoids walking the left operand of every
+    /// concatenation again to determine its nullability, which is quadratic for long
+    /// concatenations.
+    fn derive_symbols_nullable(&self, symbol: &Symbols<B::Symbol>) -> (Regex<B>, bool) {
         match self {
-            Self::EmptySet => B::empty_set(),
-            Self::EmptyString => B::empty_set(),
+            Self::EmptySet => (B::empty_set(), false),
+            Self::EmptyString => (B::empty_set(), true),
             Self::Symbol(inner) => {
                 if symbol.matches(inner) {
-                    B::empty_string()
+                    (B::empty_string(), false)
                 } else {
-                    B::empty_set()
+                    (B::empty_set(), false)
                 }
             }
-            Self::Concat(left, right) => B::or(
-                B::concat(left.derive_symbols(symbol), *right.clone()),
-                B::concat(left.nullable(), right.derive_symbols(symbol)),
-            ),
+            Self::Concat(left, right) => {
+                let (left_derivative, left_nullable) = left.derive_symbols_nullable(symbol);
+                let (right_derivative, right_nullable) = right.derive_symbols_nullable(symbol);
+                let derivative = B::or(
+                    B::concat(left_derivative, *right.clone()),
+                    B::concat(nullable::<B>(left_nullable), right_derivative),
+                );
+                (derivative, left_nullable && right_nullable)
+            }
+            Self::Closure(inner) => {
+                let (inner_derivative, _) = inner.derive_symbols_nullable(symbol);
+                (B::concat(inner_derivative, self.clone()), true)
+            }
+            Self::Or(left, right) => {
+                let (left_derivative, left_nullable) = left.derive_symbols_nullable(symbol);
+                let (right_derivative, right_nullable) = right.derive_symbols_nullable(symbol);
+                (
+                    B::or(left_derivative, right_derivative),
+                    left_nullable || right_nullable,
+                )
+            }
+            Self::And(left, right) => {
+                let (left_derivative, left_nullable) = left.derive_symbols_nullable(symbol);
+                let (right_derivative, right_nullable) = right.derive_symbols_nullable(symbol);
+                (
+                    B::and(left_derivative, right_derivative),
+                    left_nullable && right_nullable,
+                )
+            }
+            Self::Complement(inner) => {
+                let (inner_derivative, inner_nullable) = inner.derive_symbols_nullable(symbol);
+                (B::complement(inner_derivative), !inner_nullable)
+            }
+        }
+    }
+
+    /// Returns the right derivative of this regular expression w.r.t. the given suffix, which
+    /// matches the strings that are in the language when followed by the suffix.
+    ///
+    /// The symbols of the suffix are consumed from the end.
+    pub fn derive_right_iter<I>(
+        &self,
+        suffix: impl IntoIterator<Item = I, IntoIter: DoubleEndedIterator>,
+    ) -> Regex<B>
+    where
+        I: Borrow<B::Symbol>,
+    {
+        let mut d = self.clone();
+        for symbol in suffix.into_iter().rev() {
+            d = d.derive_right(symbol.borrow());
+        }
+        d
+    }
+
+    /// Returns the right derivative of this regular expression w.r.t. the given symbol, which
+    /// matches the strings that are in the language when followed by the symbol.
+    pub fn derive_right(&self, symbol: &B::Symbol) -> Regex<B> {
+        self.derive_right_nullable(symbol).0
+    }
+
+    /// Returns the right derivative of this regular expression w.r.t. the given symbol,
+    /// together with the nullability of this regular expression.
+    fn derive_right_nullable(&self, symbol: &B::Symbol) -> (Regex<B>, bool) {
+        match self {
+            Self::EmptySet => (B::empty_set(), false),
+            Self::EmptyString => (B::empty_set(), true),
+            Self::Symbol(inner) => {
+                if inner == symbol {
+                    (B::empty_string(), false)
+                } else {
+                    (B::empty_set(), false)
+                }
+            }
+            Self::Concat(left, right) => {
+                let (right_derivative, right_nullable) = right.derive_right_nullable(symbol);
+                let (left_derivative, left_nullable) = left.derive_right_nullable(symbol);
+                let derivative = B::or(
+                    B::concat(*left.clone(), right_derivative),
+                    B::concat(left_derivative, nullable::<B>(right_nullable)),
+                );
+                (derivative, left_nullable && right_nullable)
+            }
             Self::Closure(inner) => {
-                B::concat(inner.derive_symbols(symbol), B::closure(*inner.clone()))
+                let (inner_derivative, _) = inner.derive_right_nullable(symbol);
+                (
+                    B::concat(B::closure(*inner.clone()), inner_derivative),
+                    true,
+                )
             }
             Self::Or(left, right) => {
-                B::or(left.derive_symbols(symbol), right.derive_symbols(symbol))
+                let (left_derivative, left_nullable) = left.derive_right_nullable(symbol);
+                let (right_derivative, right_nullable) = right.derive_right_nullable(symbol);
+                (
+                    B::or(left_derivative, right_derivative),
+                    left_nullable || right_nullable,
+                )
             }
             Self::And(left, right) => {
-                B::and(left.derive_symbols(symbol), right.derive_symbols(symbol))
+                let (left_derivative, left_nullable) = left.derive_right_nullable(symbol);
+                let (right_derivative, right_nullable) = right.derive_right_nullable(symbol);
+                (
+                    B::and(left_derivative, right_derivative),
+                    left_nullable && right_nullable,
+                )
+            }
+            Self::Complement(inner) => {
+                let (inner_derivative, inner_nullable) = inner.derive_right_nullable(symbol);
+                (B::complement(inner_derivative), !inner_nullable)
             }
-            Self::Complement(inner) => B::complement(inner.derive_symbols(symbol)),
         }
     }
 
@@ -63,14 +172,250 @@ impl<B: Builder> Regex<B> {
     {
         self.derive_iter(symbols).is_nullable()
     }
+
+    /// Returns whether the string of symbols is in the language of this regular expression,
+    /// normalizing derivatives using the [`ApproximatelySimilarCanonical`] builder.
+    ///
+    /// Derivatives of regular expressions from builders that do not simplify, such as
+    /// [`Pure`](crate::builder::Pure), can grow with every symbol. Normalized derivatives are
+    /// bounded in size, so the cost per symbol is bounded regardless of the input length.
+    pub fn is_match_normalized<I>(&self, symbols: impl IntoIterator<Item = I>) -> bool
+    where
+        I: Borrow<B::Symbol>,
+    {
+        self.rebuild::<ApproximatelySimilarCanonical<B::Symbol>>()
+            .is_match(symbols)
+    }
+
+    /// Returns the length of the longest prefix of the given symbols that is in the language
+    /// of this regular expression, or `None` if no prefix is.
+    ///
+    /// Symbols are consumed until the derivative is the empty set, so the input does not have
+    /// to be finite if the language only contains finite strings with the input as prefix.
+    pub fn longest_matching_prefix<I>(&self, symbols: impl IntoIterator<Item = I>) -> Option<usize>
+    where
+        I: Borrow<B::Symbol>,
+    {
+        let mut regex = self.clone();
+        let mut longest = regex.is_nullable().then_some(0);
+        for (idx, symbol) in symbols.into_iter().enumerate() {
+            regex = regex.derive(symbol.borrow());
+            if matches!(regex, Regex::EmptySet) {
+                break;
+            }
+            if regex.is_nullable() {
+                longest = Some(idx + 1);
+            }
+        }
+        longest
+    }
+
+    /// Returns the length of the shortest prefix of the given symbols that is in the language
+    /// of this regular expression, or `None` if no prefix is.
+    ///
+    /// Symbols are consumed until a prefix matches or the derivative is the empty set.
+    pub fn shortest_matching_prefix<I>(&self, symbols: impl IntoIterator<Item = I>) -> Option<usize>
+    where
+        I: Borrow<B::Symbol>,
+    {
+        let mut regex = self.clone();
+        if regex.is_nullable() {
+            return Some(0);
+        }
+        for (idx, symbol) in symbols.into_iter().enumerate() {
+            regex = regex.derive(symbol.borrow());
+            if matches!(regex, Regex::EmptySet) {
+                break;
+            }
+            if regex.is_nullable() {
+                return Some(idx + 1);
+            }
+        }
+        None
+    }
+}
+
+/// Returns empty string if nullable, otherwise returns empty set.
+fn nullable<B: Builder>(nullable: bool) -> Regex<B> {
+    if nullable {
+        B::empty_string()
+    } else {
+        B::empty_set()
+    }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub(crate) enum Symbols<S: Alphabet> {
+/// A set of symbols, given by the symbols it includes or by the symbols it excludes.
+///
+/// The symbols are kept in order, so sets are displayed and compared deterministically.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Symbols<S: Alphabet> {
     /// Only the given symbols.
-    Include(HashSet<S>),
+    Include(SymbolSet<S>),
     /// All except the given symbols.
-    Exclude(HashSet<S>),
+    Exclude(SymbolSet<S>),
+}
+
+/// An ordered set of symbols, used by [`Symbols`].
+///
+/// Every derivative w.r.t. a symbol uses a set with just that symbol, so sets with at most one
+/// symbol are stored without allocating. Larger sets are stored in a [`BTreeSet`].
+#[derive(Clone, Eq, Hash, PartialEq)]
+pub struct SymbolSet<S: Alphabet>(SymbolSetRepr<S>);
+
+/// The representation of a [`SymbolSet`]. Every set has a single representation, so that the
+/// derived equality and hash agree with set equality.
+#[derive(Clone, Eq, Hash, PartialEq)]
+enum SymbolSetRepr<S: Alphabet> {
+    Empty,
+    One(S),
+    /// At least two symbols.
+    Many(BTreeSet<S>),
+}
+
+impl<S: Alphabet> SymbolSet<S> {
+    /// Returns an empty set.
+    pub fn new() -> Self {
+        Self(SymbolSetRepr::Empty)
+    }
+
+    /// Returns the number of symbols in this set.
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            SymbolSetRepr::Empty => 0,
+            SymbolSetRepr::One(_) => 1,
+            SymbolSetRepr::Many(symbols) => symbols.len(),
+        }
+    }
+
+    /// Returns whether this set is empty.
+    pub fn is_empty(&self) -> bool {
+        matches!(self.0, SymbolSetRepr::Empty)
+    }
+
+    /// Returns whether the given symbol is in this set.
+    pub fn contains(&self, symbol: &S) -> bool {
+        match &self.0 {
+            SymbolSetRepr::Empty => false,
+            SymbolSetRepr::One(one) => one == symbol,
+            SymbolSetRepr::Many(symbols) => symbols.contains(symbol),
+        }
+    }
+
+    /// Adds the given symbol, and returns whether it was not in this set yet.
+    pub fn insert(&mut self, symbol: S) -> bool {
+        match std::mem::replace(&mut self.0, SymbolSetRepr::Empty) {
+            SymbolSetRepr::Empty => {
+                self.0 = SymbolSetRepr::One(symbol);
+                true
+            }
+            SymbolSetRepr::One(one) if one == symbol => {
+                self.0 = SymbolSetRepr::One(one);
+                false
+            }
+            SymbolSetRepr::One(one) => {
+                self.0 = SymbolSetRepr::Many(BTreeSet::from([one, symbol]));
+                true
+            }
+            SymbolSetRepr::Many(mut symbols) => {
+                let inserted = symbols.insert(symbol);
+                self.0 = SymbolSetRepr::Many(symbols);
+                inserted
+            }
+        }
+    }
+
+    /// Returns the symbols in this set, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &S> + '_ {
+        match &self.0 {
+            SymbolSetRepr::Empty => Either::Left(None.into_iter()),
+            SymbolSetRepr::One(one) => Either::Left(Some(one).into_iter()),
+            SymbolSetRepr::Many(symbols) => Either::Right(symbols.iter()),
+        }
+    }
+
+    /// Returns the symbols in this set or the other set.
+    pub(crate) fn union(&self, other: &Self) -> Self {
+        self.iter().merge(other.iter()).dedup().cloned().collect()
+    }
+
+    /// Returns the symbols in both this set and the other set.
+    pub(crate) fn intersection(&self, other: &Self) -> Self {
+        self.iter()
+            .filter(|symbol| other.contains(symbol))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the symbols in this set but not in the other set.
+    pub(crate) fn difference(&self, other: &Self) -> Self {
+        self.iter()
+            .filter(|symbol| !other.contains(symbol))
+            .cloned()
+            .collect()
+    }
+}
+
+impl<S: Alphabet> Default for SymbolSet<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Alphabet + std::fmt::Debug> std::fmt::Debug for SymbolSet<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Sets are ordered by their symbols, as for [`BTreeSet`].
+impl<S: Alphabet> Ord for SymbolSet<S> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<S: Alphabet> PartialOrd for SymbolSet<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S: Alphabet> FromIterator<S> for SymbolSet<S> {
+    fn from_iter<T: IntoIterator<Item = S>>(iter: T) -> Self {
+        let mut set = Self::new();
+        for symbol in iter {
+            set.insert(symbol);
+        }
+        set
+    }
+}
+
+impl<S: Alphabet> From<BTreeSet<S>> for SymbolSet<S> {
+    fn from(symbols: BTreeSet<S>) -> Self {
+        match symbols.len() {
+            0 | 1 => symbols.into_iter().collect(),
+            _ => Self(SymbolSetRepr::Many(symbols)),
+        }
+    }
+}
+
+impl<S: Alphabet, const N: usize> From<[S; N]> for SymbolSet<S> {
+    fn from(symbols: [S; N]) -> Self {
+        symbols.into_iter().collect()
+    }
+}
+
+impl<S: Alphabet> IntoIterator for SymbolSet<S> {
+    type Item = S;
+    type IntoIter = Either<std::option::IntoIter<S>, std::collections::btree_set::IntoIter<S>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self.0 {
+            SymbolSetRepr::Empty => Either::Left(None.into_iter()),
+            SymbolSetRepr::One(one) => Either::Left(Some(one).into_iter()),
+            SymbolSetRepr::Many(symbols) => Either::Right(symbols.into_iter()),
+        }
+    }
 }
 
 impl<S: Alphabet> std::fmt::Display for Symbols<S>
@@ -88,16 +433,29 @@ where
 impl<S: Alphabet> Symbols<S> {
     #[inline]
     pub(crate) fn include<const N: usize>(symbols: [S; N]) -> Self {
-        Self::Include(HashSet::from(symbols))
+        Self::Include(symbols.into())
     }
 
     #[cfg(test)]
     #[inline]
     pub(crate) fn exclude<const N: usize>(symbols: [S; N]) -> Self {
-        Self::Exclude(HashSet::from(symbols))
+        Self::Exclude(symbols.into())
+    }
+
+    /// Returns the classes of symbols that can be distinguished by regular expressions over the
+    /// given symbols: a singleton class for every symbol, and a class for all other symbols.
+    pub(crate) fn classes(symbols: HashSet<S>) -> Vec<Self> {
+        let symbols = symbols.into_iter().collect::<BTreeSet<_>>();
+        let mut classes = symbols
+            .iter()
+            .map(|symbol| Self::include([symbol.clone()]))
+            .collect::<Vec<_>>();
+        classes.push(Self::Exclude(symbols.into()));
+        classes
     }
 
-    pub(crate) fn matches(&self, symbol: &S) -> bool {
+    /// Returns whether the given symbol is in this set.
+    pub fn matches(&self, symbol: &S) -> bool {
         match self {
             Self::Include(included) => included.contains(symbol),
             Self::Exclude(excluded) => !excluded.contains(symbol),
@@ -105,6 +463,18 @@ impl<S: Alphabet> Symbols<S> {
     }
 }
 
+impl<S: FiniteAlphabet> Symbols<S> {
+    /// Enumerates the symbols in this set, using the universe to resolve excluded symbols.
+    pub(crate) fn enumerate(&self) -> impl Iterator<Item = S> + '_ {
+        match self {
+            Self::Include(included) => Either::Left(included.iter().cloned()),
+            Self::Exclude(excluded) => {
+                Either::Right(S::universe().filter(|symbol| !excluded.contains(symbol)))
+            }
+        }
+    }
+}
+
 impl<S: Alphabet> std::ops::BitOr for Symbols<S> {
     type Output = Self;
 
@@ -112,17 +482,13 @@ impl<S: Alphabet> std::ops::BitOr for Symbols<S> {
         // either this or the other regular expressions must match
         match (self, other) {
             // include all included symbols
-            (Self::Include(left), Self::Include(right)) => {
-                Self::Include(HashSet::union(&left, &right).cloned().collect())
-            }
+            (Self::Include(left), Self::Include(right)) => Self::Include(left.union(&right)),
             // exclude shared excluded symbols
-            (Self::Exclude(left), Self::Exclude(right)) => {
-                Self::Exclude(HashSet::intersection(&left, &right).cloned().collect())
-            }
+            (Self::Exclude(left), Self::Exclude(right)) => Self::Exclude(left.intersection(&right)),
             // exclude the excluded symbols except the included symbols
             (Self::Include(included), Self::Exclude(excluded))
             | (Self::Exclude(excluded), Self::Include(included)) => {
-                Self::Exclude(excluded.difference(&included).cloned().collect())
+                Self::Exclude(excluded.difference(&included))
             }
         }
     }
@@ -135,17 +501,13 @@ impl<S: Alphabet> std::ops::BitAnd for Symbols<S> {
         // both this and the other regular expression must match
         match (self, other) {
             // include shared included symbols
-            (Self::Include(left), Self::Include(right)) => {
-                Self::Include(HashSet::intersection(&left, &right).cloned().collect())
-            }
+            (Self::Include(left), Self::Include(right)) => Self::Include(left.intersection(&right)),
             // exclude all excluded symbols
-            (Self::Exclude(left), Self::Exclude(right)) => {
-                Self::Exclude(HashSet::union(&left, &right).cloned().collect())
-            }
+            (Self::Exclude(left), Self::Exclude(right)) => Self::Exclude(left.union(&right)),
             // include the included symbols except the excluded symbols
             (Self::Include(included), Self::Exclude(excluded))
             | (Self::Exclude(excluded), Self::Include(included)) => {
-                Self::Include(included.difference(&excluded).cloned().collect())
+                Self::Include(included.difference(&excluded))
             }
         }
     }
@@ -166,13 +528,16 @@ impl<S: Alphabet> std::ops::Not for Symbols<S> {
 mod tests {
     use crate::builder::ApproximatelySimilarCanonical;
     use crate::builder::Pure;
+    use crate::builder::StronglyCanonical;
     use crate::ops::*;
 
     use super::*;
 
+    type PureRegex = Regex<Pure<usize>>;
+
     #[test]
     fn test_derive_symbols() {
-        let tests: Vec<(Regex<Pure<usize>>, Symbols<usize>, Regex<Pure<usize>>)> = vec![
+        let tests: Vec<(PureRegex, Symbols<usize>, PureRegex)> = vec![
             (().r(), Symbols::include([42]), ().r()),
             (().r(), Symbols::exclude([42]), ().r()),
             ([].r(), Symbols::include([42]), ().r()),
@@ -194,6 +559,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_derive_nullable() {
+        let tests: Vec<PureRegex> = vec![
+            ().r(),
+            [].r(),
+            42.s(),
+            !42.s(),
+            [42.s(), 11.s().c()].r(),
+            [11.s().c(), 42.s().c()].r(),
+            (11.s() | [].r()) & !42.s(),
+            ![11.s().c(), !().r()].r(),
+        ];
+        for regex in tests {
+            let symbols = Symbols::include([42]);
+            let (_, nullable) = regex.derive_symbols_nullable(&symbols);
+            assert_eq!(regex.is_nullable(), nullable, "{}", regex);
+            let (_, nullable) = regex.derive_right_nullable(&42);
+            assert_eq!(regex.is_nullable(), nullable, "{}", regex);
+        }
+    }
+
     #[test]
     fn test_is_match_pure() {
         test_is_match::<Pure<_>>();
@@ -204,6 +590,11 @@ mod tests {
         test_is_match::<ApproximatelySimilarCanonical<_>>();
     }
 
+    #[test]
+    fn test_is_match_strong() {
+        test_is_match::<StronglyCanonical<_>>();
+    }
+
     fn test_is_match<B: Builder<Symbol = usize> + Clone>() {
         let tests: Vec<(Regex<B>, Vec<_>, bool)> = vec![
             ((().r()), vec![], false),
@@ -233,9 +624,195 @@ mod tests {
             ((!().r()), vec![11], true),
             ((!11.s()), vec![42], true),
             ((!11.s()), vec![11], false),
+            (Regex::any([11, 42]), vec![42], true),
+            (Regex::any([11, 42]), vec![7], false),
+            (Regex::any([11, 42]), vec![11, 42], false),
+            (Regex::any_star([11, 42]), vec![], true),
+            (Regex::any_star([11, 42]), vec![11, 42, 11], true),
+            (Regex::any_star([11, 42]), vec![11, 7], false),
+            (Regex::complement_within(11.s(), [11, 42]), vec![], true),
+            (Regex::complement_within(11.s(), [11, 42]), vec![42], true),
+            (Regex::complement_within(11.s(), [11, 42]), vec![11], false),
+            (Regex::complement_within(11.s(), [11, 42]), vec![7], false),
+            (Regex::word(&[]), vec![], true),
+            (Regex::word(&[11, 42]), vec![11, 42], true),
+            (Regex::word(&[11, 42]), vec![11], false),
+            (
+                Regex::one_of_words([[11, 42], [42, 11]]),
+                vec![42, 11],
+                true,
+            ),
+            (
+                Regex::one_of_words([vec![11], vec![11, 42]]),
+                vec![11],
+                true,
+            ),
+            (
+                Regex::one_of_words([vec![11], vec![11, 42]]),
+                vec![42],
+                false,
+            ),
+            (Regex::one_of_words(Vec::<Vec<_>>::new()), vec![], false),
         ];
         for test in tests {
+            assert_eq!(test.2, test.0.is_match_normalized(&test.1));
             assert_eq!(test.2, test.0.is_match(test.1));
         }
     }
+
+    #[test]
+    fn test_is_match_normalized_bounded() {
+        let regex: PureRegex = (1.s() | 2.s()).c();
+        let word = vec![1; 6];
+        assert!(regex.derive_iter(&word).size() > 100);
+        assert!(regex.is_match_normalized(&word));
+    }
+
+    #[test]
+    fn test_longest_matching_prefix() {
+        type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+        let tests: Vec<(AscRegex, Vec<usize>, Option<usize>)> = vec![
+            (().r(), vec![1], None),
+            ([].r(), vec![1], Some(0)),
+            (1.s(), vec![2], None),
+            (1.s(), vec![1, 1], Some(1)),
+            (1.s().c(), vec![1, 1, 2, 1], Some(2)),
+            ([1.s(), 2.s()].r().c(), vec![1, 2, 1, 2, 1], Some(4)),
+            ([1.s(), 2.s()].r().c(), vec![1, 1, 2], Some(0)),
+            ([1.s(), 2.s(), 3.s()].r() | 1.s(), vec![1, 2, 4], Some(1)),
+        ];
+        for (regex, word, expected) in tests {
+            assert_eq!(expected, regex.longest_matching_prefix(&word), "{}", regex);
+            let pure = regex.rebuild::<Pure<_>>();
+            assert_eq!(expected, pure.longest_matching_prefix(&word), "{}", regex);
+        }
+        let regex: AscRegex = [1.s(), 2.s()].r().c();
+        let infinite = [1, 2, 3].into_iter().chain(std::iter::repeat(1));
+        assert_eq!(Some(2), regex.longest_matching_prefix(infinite));
+    }
+
+    #[test]
+    fn test_shortest_matching_prefix() {
+        type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+        let tests: Vec<(AscRegex, Vec<usize>, Option<usize>)> = vec![
+            (().r(), vec![1], None),
+            ([].r(), vec![1], Some(0)),
+            (1.s(), vec![2], None),
+            (1.s(), vec![1, 1], Some(1)),
+            (1.s().c(), vec![1, 1, 2, 1], Some(0)),
+            ([1.s(), 1.s().c()].r(), vec![1, 1, 2, 1], Some(1)),
+            ([1.s(), 2.s(), 3.s()].r(), vec![1, 2], None),
+            (
+                [1.s(), 2.s(), 3.s()].r() | [1.s(), 2.s()].r(),
+                vec![1, 2, 3],
+                Some(2),
+            ),
+        ];
+        for (regex, word, expected) in tests {
+            assert_eq!(expected, regex.shortest_matching_prefix(&word), "{}", regex);
+            let pure = regex.rebuild::<Pure<_>>();
+            assert_eq!(expected, pure.shortest_matching_prefix(&word), "{}", regex);
+        }
+        let regex: AscRegex = [1.s(), 2.s()].r();
+        let infinite = [1, 2].into_iter().chain(std::iter::repeat(1));
+        assert_eq!(Some(2), regex.shortest_matching_prefix(infinite));
+    }
+
+    #[test]
+    fn test_derive_right() {
+        type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+        let tests: Vec<(AscRegex, Vec<usize>, Vec<usize>)> = vec![
+            (1.s(), vec![], vec![1]),
+            ([1.s(), 2.s()].r(), vec![1], vec![2]),
+            ([1.s(), 2.s(), 3.s()].r(), vec![1, 2], vec![3]),
+            ([1.s(), 2.s(), 3.s()].r(), vec![1], vec![2, 3]),
+            ([1.s(), 2.s()].r().c(), vec![1, 2, 1], vec![2]),
+            (!1.s() & [2.s().c(), 1.s()].r(), vec![2], vec![1]),
+            ([1.s().c(), 2.s().c()].r(), vec![1, 1], vec![]),
+        ];
+        for (regex, prefix, suffix) in tests {
+            let derived = regex.derive_right_iter(&suffix);
+            assert!(derived.is_match(&prefix), "{} / {:?}", regex, suffix);
+            let word = prefix.iter().chain(&suffix).collect::<Vec<_>>();
+            assert!(regex.is_match(word), "{}", regex);
+            for other in [vec![], vec![3], vec![1, 3]] {
+                let word = other.iter().chain(&suffix).collect::<Vec<_>>();
+                assert_eq!(regex.is_match(word), derived.is_match(&other));
+            }
+        }
+    }
+
+    #[test]
+    fn test_symbols_display() {
+        let tests: Vec<(Symbols<usize>, &str)> = vec![
+            (Symbols::include([]), "{}"),
+            (Symbols::include([42, 7, 11]), "{7, 11, 42}"),
+            (Symbols::exclude([42, 7, 11]), "Σ∖{7, 11, 42}"),
+            (
+                Symbols::include([42, 7]) | Symbols::include([11]),
+                "{7, 11, 42}",
+            ),
+        ];
+        for (symbols, expected) in tests {
+            assert_eq!(expected, symbols.to_string());
+        }
+    }
+
+    #[test]
+    fn test_symbols_order() {
+        let mut symbols = vec![
+            Symbols::exclude([1]),
+            Symbols::include([2]),
+            Symbols::include([1, 3]),
+            Symbols::exclude([]),
+        ];
+        symbols.sort();
+        assert_eq!(
+            vec![
+                Symbols::include([1, 3]),
+                Symbols::include([2]),
+                Symbols::exclude([]),
+                Symbols::exclude([1]),
+            ],
+            symbols
+        );
+        let set = symbols.iter().cloned().collect::<HashSet<_>>();
+        assert!(set.contains(&Symbols::include([3, 1])));
+    }
+
+    #[test]
+    fn test_symbols_operations() {
+        let tests: Vec<(Symbols<usize>, Symbols<usize>)> = vec![
+            (
+                Symbols::include([1, 2]) & Symbols::include([2, 3]),
+                Symbols::include([2]),
+            ),
+            (
+                Symbols::include([1, 2]) & Symbols::exclude([1, 2]),
+                Symbols::include([]),
+            ),
+            (
+                Symbols::include([1]) | Symbols::include([1]),
+                Symbols::include([1]),
+            ),
+            (
+                Symbols::exclude([1, 2, 3]) | Symbols::include([1, 3]),
+                Symbols::exclude([2]),
+            ),
+            (
+                Symbols::exclude([1]) & Symbols::exclude([2]),
+                Symbols::exclude([2, 1]),
+            ),
+            (!Symbols::include([4]), Symbols::exclude([4])),
+        ];
+        for (actual, expected) in tests {
+            assert_eq!(expected, actual);
+            assert_eq!(
+                HashSet::from([expected.clone()]),
+                HashSet::from([actual.clone()]),
+                "{}",
+                actual
+            );
+        }
+    }
 }
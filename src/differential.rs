@@ -0,0 +1,249 @@
+//! Cross-check the ways of matching regular expressions against each other.
+
+use crate::automaton::FiniteAutomaton;
+use crate::builder::ApproximatelySimilarCanonical;
+use crate::builder::Builder;
+use crate::builder::Pure;
+use crate::builder::Regex;
+
+/// A word on which the ways of matching a regular expression disagree, returned by
+/// [`Regex::cross_check`] and [`Regex::cross_check_words`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Disagreement<S> {
+    /// The word on which the results differ.
+    pub word: Vec<S>,
+    /// Whether the word matches using derivatives from the builder of the regular expression.
+    pub builder: bool,
+    /// Whether the word matches using derivatives from the [`Pure`] builder.
+    pub pure: bool,
+    /// Whether the word matches using derivatives from the [`ApproximatelySimilarCanonical`]
+    /// builder.
+    pub canonical: bool,
+    /// Whether the word is accepted by the automaton for the regular expression.
+    pub automaton: bool,
+}
+
+impl<B: Builder> Regex<B> {
+    /// Returns the first word over the given alphabet, of at most the given length, on which
+    /// matching with derivatives from the builder of this regular expression, from the [`Pure`]
+    /// builder, from the [`ApproximatelySimilarCanonical`] builder, and with its automaton do
+    /// not all agree, or `None` if they agree on all words.
+    ///
+    /// Words are checked in order of length, so the returned word is a shortest one. All words
+    /// up to the given length are checked, and the derivatives from the [`Pure`] builder grow
+    /// with the length of the word, so this is only feasible for short words.
+    pub fn cross_check(
+        &self,
+        alphabet: &[B::Symbol],
+        max_len: usize,
+    ) -> Option<Disagreement<B::Symbol>> {
+        let residuals = Residuals::new(self);
+        let automaton = residuals.canonical.to_automaton();
+        let mut word = Vec::with_capacity(max_len);
+        (0..=max_len).find_map(|len| residuals.search(&automaton, alphabet, len, &mut word))
+    }
+
+    /// Returns the first of the given words on which the ways of matching this regular
+    /// expression do not all agree, as [`Regex::cross_check`] does, or `None` if they agree on
+    /// all of them.
+    pub fn cross_check_words<W: AsRef<[B::Symbol]>>(
+        &self,
+        words: impl IntoIterator<Item = W>,
+    ) -> Option<Disagreement<B::Symbol>> {
+        let residuals = Residuals::new(self);
+        let automaton = residuals.canonical.to_automaton();
+        words.into_iter().find_map(|word| {
+            word.as_ref()
+                .iter()
+                .fold(residuals.clone(), |residuals, symbol| {
+                    residuals.derive(&automaton, symbol)
+                })
+                .disagreement(&automaton, word.as_ref())
+        })
+    }
+}
+
+/// The derivatives of a regular expression w.r.t. the same word, for every way of matching.
+struct Residuals<B: Builder> {
+    builder: Regex<B>,
+    pure: Regex<Pure<B::Symbol>>,
+    canonical: Regex<ApproximatelySimilarCanonical<B::Symbol>>,
+    state: usize,
+}
+
+impl<B: Builder> Clone for Residuals<B> {
+    fn clone(&self) -> Self {
+        Self {
+            builder: self.builder.clone(),
+            pure: self.pure.clone(),
+            canonical: self.canonical.clone(),
+            state: self.state,
+        }
+    }
+}
+
+impl<B: Builder> Residuals<B> {
+    fn new(regex: &Regex<B>) -> Self {
+        Self {
+            builder: regex.clone(),
+            pure: regex.rebuild(),
+            canonical: regex.rebuild(),
+            state: 0,
+        }
+    }
+
+    fn derive(&self, automaton: &FiniteAutomaton<B::Symbol>, symbol: &B::Symbol) -> Self {
+        Self {
+            builder: self.builder.derive(symbol),
+            pure: self.pure.derive(symbol),
+            canonical: self.canonical.derive(symbol),
+            state: automaton.next(self.state, symbol),
+        }
+    }
+
+    fn disagreement(
+        &self,
+        automaton: &FiniteAutomaton<B::Symbol>,
+        word: &[B::Symbol],
+    ) -> Option<Disagreement<B::Symbol>> {
+        let disagreement = Disagreement {
+            word: word.to_vec(),
+            builder: self.builder.is_nullable(),
+            pure: self.pure.is_nullable(),
+            canonical: self.canonical.is_nullable(),
+            automaton: automaton.is_accepting(self.state),
+        };
+        let results = [
+            disagreement.pure,
+            disagreement.canonical,
+            disagreement.automaton,
+        ];
+        if results.iter().any(|result| *result != disagreement.builder) {
+            Some(disagreement)
+        } else {
+            None
+        }
+    }
+
+    /// Searches the words of exactly the given length that extend the given word, in the order
+    /// of the alphabet, reusing the derivatives for shared prefixes.
+    fn search(
+        &self,
+        automaton: &FiniteAutomaton<B::Symbol>,
+        alphabet: &[B::Symbol],
+        len: usize,
+        word: &mut Vec<B::Symbol>,
+    ) -> Option<Disagreement<B::Symbol>> {
+        if word.len() == len {
+            return self.disagreement(automaton, word);
+        }
+        for symbol in alphabet {
+            word.push(symbol.clone());
+            let found = self
+                .derive(automaton, symbol)
+                .search(automaton, alphabet, len, word);
+            word.pop();
+            if found.is_some() {
+                return found;
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    use crate::builder::ApproximateSimilarity;
+    use crate::builder::Layer;
+    use crate::builder::Nodes;
+    use crate::builder::Stack;
+    use crate::builder::StronglyCanonical;
+    use crate::ops::*;
+
+    use super::*;
+
+    /// A layer with a wrong rule, which rewrites `e | R` to `e`.
+    #[derive(Debug, Hash, PartialEq, Eq)]
+    struct Absorb<L: Layer> {
+        _phantom: PhantomData<L>,
+    }
+
+    impl<L: Layer> Layer for Absorb<L> {
+        fn empty_set<B: Builder>() -> Regex<B> {
+            L::empty_set()
+        }
+
+        fn empty_string<B: Builder>() -> Regex<B> {
+            L::empty_string()
+        }
+
+        fn symbol<B: Builder>(value: B::Symbol) -> Regex<B> {
+            L::symbol(value)
+        }
+
+        fn closure<B: Builder>(inner: Regex<B>) -> Regex<B> {
+            L::closure(inner)
+        }
+
+        fn concat<B: Builder>(left: Regex<B>, right: Regex<B>) -> Regex<B> {
+            L::concat(left, right)
+        }
+
+        fn or<B: Builder>(left: Regex<B>, right: Regex<B>) -> Regex<B> {
+            match (left, right) {
+                (Regex::EmptyString, _) | (_, Regex::EmptyString) => B::empty_string(),
+                (left, right) => L::or(left, right),
+            }
+        }
+
+        fn and<B: Builder>(left: Regex<B>, right: Regex<B>) -> Regex<B> {
+            L::and(left, right)
+        }
+
+        fn complement<B: Builder>(inner: Regex<B>) -> Regex<B> {
+            L::complement(inner)
+        }
+    }
+
+    type AbsorbingRegex = Regex<Stack<usize, Absorb<ApproximateSimilarity<Nodes>>>>;
+
+    #[test]
+    fn test_cross_check_agrees() {
+        let tests: Vec<Regex<StronglyCanonical<usize>>> = vec![
+            ().r(),
+            [].r(),
+            [1.s(), (2.s() | 3.s()).c()].r(),
+            !(1.s().c() & [2.s(), 1.s()].r().c()),
+            [].r() | [1.s(), 1.s().c()].r(),
+        ];
+        for regex in tests {
+            assert_eq!(None, regex.cross_check(&[1, 2, 3], 4), "{}", regex);
+            assert_eq!(
+                None,
+                regex.cross_check_words([vec![1, 1, 1, 1, 1, 1], vec![2, 1, 2, 1]]),
+                "{}",
+                regex
+            );
+        }
+    }
+
+    #[test]
+    fn test_cross_check_disagrees() {
+        let regex: AbsorbingRegex = 1.s() | [1.s(), 2.s()].r();
+        let expected = Some(Disagreement {
+            word: vec![1, 2],
+            builder: false,
+            pure: true,
+            canonical: true,
+            automaton: true,
+        });
+        assert_eq!(expected, regex.cross_check(&[1, 2], 3));
+        assert_eq!(
+            expected,
+            regex.cross_check_words([vec![2], vec![1, 2], vec![1, 2, 2]])
+        );
+        assert_eq!(None, regex.cross_check(&[1, 2], 1));
+    }
+}
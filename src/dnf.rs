@@ -0,0 +1,111 @@
+//! Disjunctive normal form of the boolean structure of regular expressions.
+
+use itertools::Itertools;
+
+use crate::builder::Builder;
+use crate::builder::Regex;
+
+impl<B: Builder> Regex<B> {
+    /// Rewrites the top-level boolean structure of this regular expression into disjunctive
+    /// normal form.
+    ///
+    /// The result is an alternation of intersections of literals, where a literal is a regular
+    /// expression that is not an alternation, intersection, or complement, or the complement of
+    /// such a regular expression. Regular expressions below a literal are left unchanged.
+    pub fn to_dnf(&self) -> Regex<B> {
+        self.dnf_clauses(false)
+            .into_iter()
+            .map(|clause| clause.into_iter().reduce(B::and).expect("non-empty clause"))
+            .reduce(B::or)
+            .expect("non-empty disjunction")
+    }
+
+    /// Returns the clauses of the disjunctive normal form of this regular expression, or of its
+    /// complement if `negated` is set.
+    fn dnf_clauses(&self, negated: bool) -> Vec<Vec<Regex<B>>> {
+        match (self, negated) {
+            (Self::Or(left, right), false) | (Self::And(left, right), true) => {
+                let mut clauses = left.dnf_clauses(negated);
+                clauses.extend(right.dnf_clauses(negated));
+                clauses
+            }
+            (Self::And(left, right), false) | (Self::Or(left, right), true) => {
+                let right = right.dnf_clauses(negated);
+                left.dnf_clauses(negated)
+                    .into_iter()
+                    .cartesian_product(right)
+                    .map(|(mut left, right)| {
+                        left.extend(right);
+                        left
+                    })
+                    .collect()
+            }
+            (Self::Complement(inner), negated) => inner.dnf_clauses(!negated),
+            (literal, false) => vec![vec![literal.clone()]],
+            (literal, true) => vec![vec![B::complement(literal.clone())]],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::Pure;
+    use crate::ops::*;
+
+    use super::*;
+
+    type PureRegex = Regex<Pure<usize>>;
+
+    #[test]
+    fn test_to_dnf() {
+        let tests: Vec<(PureRegex, PureRegex)> = vec![
+            (11.s(), 11.s()),
+            (!!11.s(), 11.s()),
+            (!().r(), !().r()),
+            (
+                11.s() & (17.s() | 42.s()),
+                (11.s() & 17.s()) | (11.s() & 42.s()),
+            ),
+            (
+                (11.s() | 17.s()) & 42.s(),
+                (11.s() & 42.s()) | (17.s() & 42.s()),
+            ),
+            (!(11.s() | 17.s()) & 42.s(), !11.s() & !17.s() & 42.s()),
+            (!(11.s() & 17.s()), !11.s() | !17.s()),
+            (
+                (11.s() | 17.s()) & (42.s() | 7.s()),
+                (11.s() & 42.s()) | (11.s() & 7.s()) | (17.s() & 42.s()) | (17.s() & 7.s()),
+            ),
+            (
+                [11.s() | 17.s(), 42.s()].r() & !!7.s(),
+                [11.s() | 17.s(), 42.s()].r() & 7.s(),
+            ),
+        ];
+        for test in tests {
+            assert_eq!(test.1, test.0.to_dnf());
+        }
+    }
+
+    #[test]
+    fn test_to_dnf_preserves_language() {
+        let tests: Vec<PureRegex> = vec![
+            !(11.s() | 17.s().c()) & [11.s(), 17.s()].r(),
+            !(11.s() & !(17.s() | [].r())),
+            (11.s() | 17.s()).c() & !(11.s().c() | [17.s(), 11.s()].r()),
+        ];
+        let words: Vec<Vec<usize>> = vec![
+            vec![],
+            vec![11],
+            vec![17],
+            vec![11, 17],
+            vec![17, 11],
+            vec![17, 17],
+        ];
+        for regex in tests {
+            let dnf = regex.to_dnf();
+            for word in &words {
+                assert_eq!(regex.is_match(word), dnf.is_match(word));
+            }
+        }
+    }
+}
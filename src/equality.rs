@@ -0,0 +1,132 @@
+//! Equality of regular expressions modulo algebraic laws.
+
+use std::collections::BTreeSet;
+
+use crate::builder::Builder;
+use crate::builder::Regex;
+
+/// A regular expression in which nested alternatives and intersections are flattened into
+/// sets, which makes them associative, commutative, and idempotent.
+#[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
+enum AciNormal<'a, S> {
+    EmptySet,
+    EmptyString,
+    Symbol(&'a S),
+    Concat(Box<Self>, Box<Self>),
+    Closure(Box<Self>),
+    Or(BTreeSet<Self>),
+    And(BTreeSet<Self>),
+    Complement(Box<Self>),
+}
+
+impl<'a, S: Ord> AciNormal<'a, S> {
+    fn or(mut operands: BTreeSet<Self>) -> Self {
+        if operands.len() == 1 {
+            operands.pop_first().expect("one operand")
+        } else {
+            Self::Or(operands)
+        }
+    }
+
+    fn and(mut operands: BTreeSet<Self>) -> Self {
+        if operands.len() == 1 {
+            operands.pop_first().expect("one operand")
+        } else {
+            Self::And(operands)
+        }
+    }
+}
+
+impl<B: Builder> Regex<B> {
+    /// Returns whether this regular expression is equal to the given regular expression, modulo
+    /// associativity, commutativity, and idempotence of alternation and intersection.
+    ///
+    /// The regular expressions may have been created by different builders.
+    pub fn eq_modulo_aci<X: Builder<Symbol = B::Symbol>>(&self, other: &Regex<X>) -> bool {
+        self.aci_normal() == other.aci_normal()
+    }
+
+    fn aci_normal(&self) -> AciNormal<'_, B::Symbol> {
+        match self {
+            Regex::EmptySet => AciNormal::EmptySet,
+            Regex::EmptyString => AciNormal::EmptyString,
+            Regex::Symbol(value) => AciNormal::Symbol(value),
+            Regex::Concat(left, right) => {
+                AciNormal::Concat(left.aci_normal().into(), right.aci_normal().into())
+            }
+            Regex::Closure(inner) => AciNormal::Closure(inner.aci_normal().into()),
+            Regex::Or(_, _) => {
+                let mut operands = BTreeSet::new();
+                self.collect_or_operands(&mut operands);
+                AciNormal::or(operands)
+            }
+            Regex::And(_, _) => {
+                let mut operands = BTreeSet::new();
+                self.collect_and_operands(&mut operands);
+                AciNormal::and(operands)
+            }
+            Regex::Complement(inner) => AciNormal::Complement(inner.aci_normal().into()),
+        }
+    }
+
+    fn collect_or_operands<'a>(&'a self, operands: &mut BTreeSet<AciNormal<'a, B::Symbol>>) {
+        if let Regex::Or(left, right) = self {
+            left.collect_or_operands(operands);
+            right.collect_or_operands(operands);
+        } else {
+            operands.insert(self.aci_normal());
+        }
+    }
+
+    fn collect_and_operands<'a>(&'a self, operands: &mut BTreeSet<AciNormal<'a, B::Symbol>>) {
+        if let Regex::And(left, right) = self {
+            left.collect_and_operands(operands);
+            right.collect_and_operands(operands);
+        } else {
+            operands.insert(self.aci_normal());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Pure;
+    use crate::ops::*;
+
+    use super::*;
+
+    type PureRegex = Regex<Pure<usize>>;
+
+    #[test]
+    fn test_eq_modulo_aci() {
+        let tests: Vec<(PureRegex, PureRegex, bool)> = vec![
+            (1.s() | 2.s(), 2.s() | 1.s(), true),
+            (1.s() | (2.s() | 3.s()), (3.s() | 1.s()) | 2.s(), true),
+            (1.s() & (2.s() & 1.s()), 2.s() & 1.s(), true),
+            (1.s() | 1.s(), 1.s(), true),
+            (!(1.s() | 2.s()).c(), !(2.s() | 1.s()).c(), true),
+            ([1.s(), 2.s()].r(), [2.s(), 1.s()].r(), false),
+            (1.s() | 2.s(), 1.s() & 2.s(), false),
+            ((1.s() | 2.s()) & 3.s(), 1.s() | (2.s() & 3.s()), false),
+            (1.s() | ().r(), 1.s(), false),
+        ];
+        for (left, right, expected) in tests {
+            assert_eq!(
+                expected,
+                left.eq_modulo_aci(&right),
+                "{} = {} modulo ACI",
+                left,
+                right
+            );
+        }
+    }
+
+    #[test]
+    fn test_eq_modulo_aci_across_builders() {
+        let left: PureRegex = 3.s() | (1.s() & 2.s());
+        let right: Regex<ApproximatelySimilarCanonical<usize>> = (2.s() & 1.s()) | 3.s();
+        assert!(left.eq_modulo_aci(&right));
+        assert!(right.eq_modulo_aci(&left));
+    }
+}
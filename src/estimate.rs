@@ -0,0 +1,76 @@
+//! Estimate the size of automata without constructing them.
+
+use crate::builder::Builder;
+use crate::builder::Regex;
+
+impl<B: Builder> Regex<B> {
+    /// Returns an upper bound on the number of states of the automaton for this regular
+    /// expression, without constructing it.
+    ///
+    /// The bound follows the structure of the regular expression: alternations and
+    /// intersections multiply the bounds of their operands, while concatenations and closures
+    /// are exponential in the bound of (one of) their operands. It holds for builders that
+    /// identify regular expressions modulo associativity, commutativity, and idempotence of
+    /// alternation, such as [`ApproximatelySimilarCanonical`](crate::builder::ApproximatelySimilarCanonical).
+    /// The bound is usually far from tight, but it is cheap to compute, and can be used to
+    /// reject regular expressions before construction. It saturates at [`usize::MAX`].
+    pub fn estimate_state_count(&self) -> usize {
+        match self {
+            // R, ∅
+            Regex::EmptySet => 1,
+            // ε, ∅
+            Regex::EmptyString => 2,
+            // a, ε, ∅
+            Regex::Symbol(_) => 3,
+            Regex::Concat(left, right) => left
+                .estimate_state_count()
+                .saturating_mul(saturating_pow2(right.estimate_state_count())),
+            Regex::Closure(inner) => saturating_pow2(inner.estimate_state_count()),
+            Regex::Or(left, right) | Regex::And(left, right) => left
+                .estimate_state_count()
+                .saturating_mul(right.estimate_state_count()),
+            Regex::Complement(inner) => inner.estimate_state_count(),
+        }
+    }
+}
+
+fn saturating_pow2(exponent: usize) -> usize {
+    u32::try_from(exponent)
+        .ok()
+        .and_then(|exponent| 1usize.checked_shl(exponent))
+        .unwrap_or(usize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::ops::*;
+
+    use super::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+
+    #[test]
+    fn test_estimate_state_count() {
+        let tests: Vec<(AscRegex, usize)> = vec![
+            (().r(), 1),
+            ([].r(), 2),
+            (1.s(), 3),
+            (!1.s(), 3),
+            (1.s() | 2.s(), 9),
+            (1.s().c(), 8),
+            ([1.s(), 2.s()].r(), 24),
+            (
+                [1.s(), 2.s(), 3.s(), 4.s(), 5.s(), 6.s(), 7.s()].r().c(),
+                usize::MAX,
+            ),
+        ];
+        for (regex, expected) in tests {
+            let estimate = regex.estimate_state_count();
+            assert_eq!(expected, estimate, "estimate for {}", regex);
+            if estimate < 1000 {
+                assert!(regex.to_automaton().state_count() <= estimate);
+            }
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! Literal factors that every string in the language of a regular expression contains.
+
+use crate::builder::Builder;
+use crate::builder::Regex;
+use crate::Alphabet;
+
+/// Literal strings known about a non-empty language.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Literals<S> {
+    /// The only string in the language, if there is exactly one.
+    exact: Option<Vec<S>>,
+    /// A string that every string in the language starts with.
+    prefix: Vec<S>,
+    /// A string that every string in the language ends with.
+    suffix: Vec<S>,
+    /// A string that every string in the language contains.
+    factor: Vec<S>,
+}
+
+impl<S: Alphabet> Literals<S> {
+    fn exact(word: Vec<S>) -> Self {
+        Self {
+            exact: Some(word.clone()),
+            prefix: word.clone(),
+            suffix: word.clone(),
+            factor: word,
+        }
+    }
+
+    fn unknown() -> Self {
+        Self {
+            exact: None,
+            prefix: Vec::new(),
+            suffix: Vec::new(),
+            factor: Vec::new(),
+        }
+    }
+
+    fn concat(left: Self, right: Self) -> Self {
+        let exact = match (&left.exact, &right.exact) {
+            (Some(left), Some(right)) => Some([left.as_slice(), right].concat()),
+            _ => None,
+        };
+        let prefix = match &left.exact {
+            Some(left) => [left.as_slice(), &right.prefix].concat(),
+            None => left.prefix,
+        };
+        let suffix = match &right.exact {
+            Some(right) => [left.suffix.as_slice(), right].concat(),
+            None => right.suffix,
+        };
+        let junction = [left.suffix.as_slice(), &right.prefix].concat();
+        let factor = longest([
+            left.factor,
+            right.factor,
+            junction,
+            prefix.clone(),
+            suffix.clone(),
+        ]);
+        Self {
+            exact,
+            prefix,
+            suffix,
+            factor,
+        }
+    }
+
+    fn or(left: Self, right: Self) -> Self {
+        let exact = match (left.exact, right.exact) {
+            (Some(left), Some(right)) if left == right => Some(left),
+            _ => None,
+        };
+        let prefix = left
+            .prefix
+            .iter()
+            .zip(&right.prefix)
+            .take_while(|(left, right)| left == right)
+            .map(|(symbol, _)| symbol.clone())
+            .collect::<Vec<_>>();
+        let mut suffix = left
+            .suffix
+            .iter()
+            .rev()
+            .zip(right.suffix.iter().rev())
+            .take_while(|(left, right)| left == right)
+            .map(|(symbol, _)| symbol.clone())
+            .collect::<Vec<_>>();
+        suffix.reverse();
+        let mut factors = vec![prefix.clone(), suffix.clone()];
+        if contains(&right.factor, &left.factor) {
+            factors.push(left.factor.clone());
+        }
+        if contains(&left.factor, &right.factor) {
+            factors.push(right.factor);
+        }
+        Self {
+            exact,
+            prefix,
+            suffix,
+            factor: longest(factors),
+        }
+    }
+
+    fn and(left: Self, right: Self) -> Self {
+        Self {
+            exact: left.exact.or(right.exact),
+            prefix: longest([left.prefix, right.prefix]),
+            suffix: longest([left.suffix, right.suffix]),
+            factor: longest([left.factor, right.factor]),
+        }
+    }
+}
+
+impl<B: Builder> Regex<B> {
+    /// Returns a non-empty string that every string in the language of this regular expression
+    /// contains, or `None` if no such string is found.
+    ///
+    /// The string is found by a syntactic analysis, which does not look into complements and
+    /// closures. It can be used to quickly reject inputs that do not contain it, before
+    /// searching for matches. An empty language has no required factor.
+    pub fn required_factor(&self) -> Option<Vec<B::Symbol>> {
+        self.literals()
+            .map(|literals| literals.factor)
+            .filter(|factor| !factor.is_empty())
+    }
+
+    /// Returns the literal strings known about the language of this regular expression, or
+    /// `None` if the language is empty.
+    fn literals(&self) -> Option<Literals<B::Symbol>> {
+        match self {
+            Regex::EmptySet => None,
+            Regex::EmptyString => Some(Literals::exact(Vec::new())),
+            Regex::Symbol(value) => Some(Literals::exact(vec![value.clone()])),
+            Regex::Concat(left, right) => {
+                Some(Literals::concat(left.literals()?, right.literals()?))
+            }
+            Regex::Closure(inner) => match inner.literals() {
+                Some(Literals {
+                    exact: Some(word), ..
+                }) if word.is_empty() => Some(Literals::exact(word)),
+                _ => Some(Literals::unknown()),
+            },
+            Regex::Or(left, right) => match (left.literals(), right.literals()) {
+                (Some(left), Some(right)) => Some(Literals::or(left, right)),
+                (literals, None) | (None, literals) => literals,
+            },
+            Regex::And(left, right) => Some(Literals::and(left.literals()?, right.literals()?)),
+            Regex::Complement(_) => Some(Literals::unknown()),
+        }
+    }
+}
+
+/// Returns the longest of the given strings, preferring earlier ones.
+fn longest<S>(words: impl IntoIterator<Item = Vec<S>>) -> Vec<S> {
+    words
+        .into_iter()
+        .reduce(|longest, word| {
+            if word.len() > longest.len() {
+                word
+            } else {
+                longest
+            }
+        })
+        .unwrap_or_default()
+}
+
+/// Returns whether the given needle is a substring of the given haystack.
+fn contains<S: Eq>(haystack: &[S], needle: &[S]) -> bool {
+    needle.is_empty()
+        || haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Pure;
+    use crate::ops::*;
+
+    use super::*;
+
+    #[test]
+    fn test_required_factor_pure() {
+        test_required_factor::<Pure<_>>();
+    }
+
+    #[test]
+    fn test_required_factor_asc() {
+        test_required_factor::<ApproximatelySimilarCanonical<_>>();
+    }
+
+    fn test_required_factor<B: Builder<Symbol = usize>>() {
+        let tests: Vec<(Regex<B>, Option<Vec<usize>>)> = vec![
+            (().r(), None),
+            ([].r(), None),
+            (1.s(), Some(vec![1])),
+            (1.s().c(), None),
+            (!1.s(), None),
+            ([1.s(), 2.s(), 3.s()].r(), Some(vec![1, 2, 3])),
+            ([1.s(), 2.s().c(), 3.s(), 4.s()].r(), Some(vec![3, 4])),
+            ([9.s().c(), 1.s(), 2.s(), 9.s().c()].r(), Some(vec![1, 2])),
+            ([1.s(), 2.s()].r() | [1.s(), 3.s()].r(), Some(vec![1])),
+            ([1.s(), 3.s()].r() | [2.s(), 3.s()].r(), Some(vec![3])),
+            (
+                [9.s().c(), 1.s(), 2.s(), 9.s().c()].r()
+                    | [8.s().c(), 0.s(), 1.s(), 2.s(), 3.s()].r(),
+                Some(vec![1, 2]),
+            ),
+            ([1.s(), 2.s()].r() | [3.s(), 4.s()].r(), None),
+            ([1.s(), 2.s()].r() | ().r(), Some(vec![1, 2])),
+            (
+                [1.s(), 9.s().c()].r() & [9.s().c(), 2.s(), 3.s()].r(),
+                Some(vec![2, 3]),
+            ),
+            ([[1.s(), 2.s()].r() | [].r(), 3.s()].r(), Some(vec![3])),
+            ([1.s(), ().r()].r(), None),
+        ];
+        for (regex, expected) in tests {
+            assert_eq!(expected, regex.required_factor(), "{}", regex);
+        }
+    }
+}
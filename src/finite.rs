@@ -0,0 +1,145 @@
+//! Operations on regular expressions over finite alphabets.
+
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+use crate::builder::ApproximatelySimilarCanonical;
+use crate::builder::Builder;
+use crate::builder::Regex;
+use crate::derivation::Symbols;
+use crate::FiniteAlphabet;
+
+impl<B: Builder> Regex<B>
+where
+    B::Symbol: FiniteAlphabet,
+{
+    /// Returns a regular expression matching exactly one symbol from the universe.
+    #[inline]
+    pub fn any_symbol() -> Self {
+        Self::any(B::Symbol::universe())
+    }
+
+    /// Returns a regular expression matching any string of symbols from the universe.
+    #[inline]
+    pub fn universal() -> Self {
+        Self::any_star(B::Symbol::universe())
+    }
+
+    /// Returns the complement of the given regular expression relative to the universe.
+    ///
+    /// For finite alphabets this is equivalent to `¬R`, but it is expressed in terms of the
+    /// universe, so that it does not accept symbols outside of it.
+    #[inline]
+    pub fn complement_in_universe(inner: Self) -> Self {
+        Self::complement_within(inner, B::Symbol::universe())
+    }
+
+    /// Returns a regular expression matching any string of symbols from the universe that
+    /// contains a string matching the given regular expression.
+    #[inline]
+    pub fn contains_in_universe(inner: Self) -> Self {
+        Self::contains(inner, B::Symbol::universe())
+    }
+
+    /// Returns whether every string of symbols from the universe is in the language of
+    /// this regular expression.
+    pub fn is_universal(&self) -> bool {
+        let regex = self.rebuild::<ApproximatelySimilarCanonical<B::Symbol>>();
+
+        let mut symbols = HashSet::new();
+        regex.collect_symbols(&mut symbols);
+        let mut classes = Symbols::classes(symbols);
+        // the class of other symbols is empty if all symbols of the universe occur
+        if classes
+            .last()
+            .is_some_and(|other| other.enumerate().next().is_none())
+        {
+            classes.pop();
+        }
+
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::new();
+        visited.insert(regex.clone());
+        queue.push_back(regex);
+        while let Some(regex) = queue.pop_front() {
+            if !regex.is_nullable() {
+                return false;
+            }
+            for class in &classes {
+                let next = regex.derive_symbols(class);
+                if !visited.contains(&next) {
+                    visited.insert(next.clone());
+                    queue.push_back(next);
+                }
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Pure;
+    use crate::ops::*;
+
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    enum Bit {
+        Zero,
+        One,
+    }
+
+    impl FiniteAlphabet for Bit {
+        fn universe() -> impl Iterator<Item = Self> {
+            [Bit::Zero, Bit::One].into_iter()
+        }
+    }
+
+    #[test]
+    fn test_enumerate_symbols() {
+        assert_eq!(
+            vec![Bit::One],
+            Symbols::exclude([Bit::Zero])
+                .enumerate()
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            0,
+            Symbols::exclude([Bit::Zero, Bit::One]).enumerate().count()
+        );
+    }
+
+    #[test]
+    fn test_is_universal_pure() {
+        test_is_universal::<Pure<_>>();
+    }
+
+    #[test]
+    fn test_is_universal_asc() {
+        test_is_universal::<ApproximatelySimilarCanonical<_>>();
+    }
+
+    fn test_is_universal<B: Builder<Symbol = Bit> + Clone>() {
+        let tests: Vec<(Regex<B>, bool)> = vec![
+            (().r(), false),
+            ([].r(), false),
+            (!().r(), true),
+            (Bit::Zero.s().c(), false),
+            ((Bit::Zero.s() | Bit::One.s()).c(), true),
+            (Regex::universal(), true),
+            (Regex::any_symbol(), false),
+            (Regex::complement_in_universe(Bit::Zero.s()), false),
+            (
+                Regex::complement_in_universe(Bit::Zero.s()) | Bit::Zero.s(),
+                true,
+            ),
+            (Regex::contains_in_universe(Bit::Zero.s()), false),
+            (Regex::contains_in_universe([].r()), true),
+        ];
+        for test in tests {
+            assert_eq!(test.1, test.0.is_universal());
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Match text by extended grapheme clusters instead of by `char`.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::Matcher;
+use crate::automaton::StateId;
+use crate::builder::Builder;
+use crate::builder::Regex;
+
+impl<B: Builder<Symbol = String>> Regex<B> {
+    /// Returns a regular expression matching exactly the given text, with an extended grapheme
+    /// cluster as every symbol.
+    ///
+    /// A user-visible character can consist of several `char`s, such as a letter followed by
+    /// combining marks. Matching by grapheme cluster treats it as a single symbol.
+    pub fn graphemes(text: &str) -> Self {
+        Self::concat_all(
+            text.graphemes(true)
+                .map(|grapheme| B::symbol(grapheme.to_string())),
+        )
+    }
+}
+
+impl<L, I: StateId> FiniteAutomaton<String, L, I> {
+    /// Returns whether the given text, split into extended grapheme clusters, is accepted by
+    /// this automaton.
+    pub fn is_match_graphemes(&self, text: &str) -> bool {
+        self.to_matcher().next_graphemes(text)
+    }
+}
+
+impl<L, I: StateId> Matcher<'_, String, L, I> {
+    /// Consume the extended grapheme clusters of the given text and return whether the matcher
+    /// is in an accepting state. The clusters are looked up as `&str`, without allocating.
+    pub fn next_graphemes(&mut self, text: &str) -> bool {
+        self.next_iter_borrowed(text.graphemes(true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::ops::*;
+
+    use super::*;
+
+    type StringRegex = Regex<ApproximatelySimilarCanonical<String>>;
+
+    #[test]
+    fn test_graphemes() {
+        let accent = "e\u{301}";
+        let tests: Vec<(StringRegex, &str, bool)> = vec![
+            (Regex::graphemes(accent), accent, true),
+            (Regex::graphemes(accent), "e", false),
+            ("e".to_string().s(), accent, false),
+            ([!().r(), "e".to_string().s()].r(), accent, false),
+            ([!().r(), "e".to_string().s()].r(), "e\u{301}e", true),
+            (Regex::graphemes("🇳🇱x"), "🇳🇱x", true),
+            (Regex::graphemes(""), "", true),
+            (
+                [Regex::graphemes(accent), "x".to_string().s().c()].r(),
+                "e\u{301}xx",
+                true,
+            ),
+        ];
+        for (regex, text, expected) in tests {
+            let fa = regex.to_automaton();
+            assert_eq!(
+                expected,
+                fa.is_match_graphemes(text),
+                "{} on {:?}",
+                regex,
+                text
+            );
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Guarded strings, in the spirit of Kleene Algebra with Tests.
+
+use crate::builder::Builder;
+use crate::builder::Regex;
+use crate::Alphabet;
+
+/// A symbol of a regular expression over guarded strings, which is either a test or an action.
+///
+/// A guarded string alternates states and actions, starting and ending with a state. A test
+/// is a predicate over states, which matches without consuming anything if it holds in the
+/// current state. An action is consumed, and moves to the next state. Negated tests are
+/// expressed in the test type, because the complement of a regular expression is taken over
+/// guarded strings.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Guarded<T, A> {
+    /// A test, which matches the empty string if it holds in the current state.
+    Test(T),
+    /// An action, which is matched by consuming it.
+    Action(A),
+}
+
+impl<T: Alphabet, A: Alphabet, B: Builder<Symbol = Guarded<T, A>>> Regex<B> {
+    /// Returns whether the guarded string consisting of a single state is in the language of
+    /// this regular expression. The tests that hold in the state are given by a function.
+    pub fn is_nullable_guarded(&self, holds: impl Fn(&T) -> bool) -> bool {
+        self.derive_guarded_nullable(&holds, None).1
+    }
+
+    /// Returns the derivative of this regular expression w.r.t. the given state and action,
+    /// which matches the rest of the guarded strings that start with them. The tests that
+    /// hold in the state are given by a function.
+    pub fn derive_guarded(&self, holds: impl Fn(&T) -> bool, action: &A) -> Regex<B> {
+        self.derive_guarded_nullable(&holds, Some(action)).0
+    }
+
+    /// Returns whether the given guarded string is in the language of this regular
+    /// expression. The guarded string is given by its first state, followed by pairs of an
+    /// action and the state after it. Whether a test holds in a state is given by a function.
+    pub fn is_match_guarded<V>(
+        &self,
+        test: impl Fn(&T, &V) -> bool,
+        start: V,
+        steps: impl IntoIterator<Item = (A, V)>,
+    ) -> bool {
+        let mut regex = self.clone();
+        let mut state = start;
+        for (action, next) in steps {
+            regex = regex.derive_guarded(|t| test(t, &state), &action);
+            state = next;
+        }
+        regex.is_nullable_guarded(|t| test(t, &state))
+    }
+
+    /// Returns the derivative of this regular expression w.r.t. the given state and action,
+    /// together with the nullability of this regular expression in that state. Without an
+    /// action, the derivative is the empty set.
+    fn derive_guarded_nullable<F: Fn(&T) -> bool>(
+        &self,
+        holds: &F,
+        action: Option<&A>,
+    ) -> (Regex<B>, bool) {
+        match self {
+            Self::EmptySet => (B::empty_set(), false),
+            Self::EmptyString => (B::empty_set(), true),
+            Self::Symbol(Guarded::Test(test)) => (B::empty_set(), holds(test)),
+            Self::Symbol(Guarded::Action(inner)) => {
+                if action == Some(inner) {
+                    (B::empty_string(), false)
+                } else {
+                    (B::empty_set(), false)
+                }
+            }
+            Self::Concat(left, right) => {
+                let (left_derivative, left_nullable) = left.derive_guarded_nullable(holds, action);
+                let (right_derivative, right_nullable) =
+                    right.derive_guarded_nullable(holds, action);
+                let derivative = if left_nullable {
+                    B::or(B::concat(left_derivative, *right.clone()), right_derivative)
+                } else {
+                    B::concat(left_derivative, *right.clone())
+                };
+                (derivative, left_nullable && right_nullable)
+            }
+            Self::Closure(inner) => {
+                let (inner_derivative, _) = inner.derive_guarded_nullable(holds, action);
+                (B::concat(inner_derivative, self.clone()), true)
+            }
+            Self::Or(left, right) => {
+                let (left_derivative, left_nullable) = left.derive_guarded_nullable(holds, action);
+                let (right_derivative, right_nullable) =
+                    right.derive_guarded_nullable(holds, action);
+                (
+                    B::or(left_derivative, right_derivative),
+                    left_nullable || right_nullable,
+                )
+            }
+            Self::And(left, right) => {
+                let (left_derivative, left_nullable) = left.derive_guarded_nullable(holds, action);
+                let (right_derivative, right_nullable) =
+                    right.derive_guarded_nullable(holds, action);
+                (
+                    B::and(left_derivative, right_derivative),
+                    left_nullable && right_nullable,
+                )
+            }
+            Self::Complement(inner) => {
+                let (inner_derivative, inner_nullable) =
+                    inner.derive_guarded_nullable(holds, action);
+                (B::complement(inner_derivative), !inner_nullable)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::ops::*;
+
+    use super::*;
+
+    /// Tests on a counter.
+    #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    enum Test {
+        Zero,
+        NonZero,
+    }
+
+    /// Actions on a counter.
+    #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    enum Action {
+        Inc,
+        Dec,
+    }
+
+    type KatRegex = Regex<ApproximatelySimilarCanonical<Guarded<Test, Action>>>;
+
+    fn test(t: Test) -> KatRegex {
+        Guarded::Test(t).s()
+    }
+
+    fn action(a: Action) -> KatRegex {
+        Guarded::Action(a).s()
+    }
+
+    fn holds(test: &Test, count: &usize) -> bool {
+        match test {
+            Test::Zero => *count == 0,
+            Test::NonZero => *count != 0,
+        }
+    }
+
+    #[test]
+    fn test_is_match_guarded() {
+        // while nonzero do dec; assert zero
+        let countdown: KatRegex = [
+            [test(Test::NonZero), action(Action::Dec)].r().c(),
+            test(Test::Zero),
+        ]
+        .r();
+        // inc, then anything that does not start in zero
+        let guarded: KatRegex = [action(Action::Inc), !test(Test::Zero)].r();
+        let tests = vec![
+            (&countdown, 0, vec![], true),
+            (&countdown, 1, vec![], false),
+            (
+                &countdown,
+                2,
+                vec![(Action::Dec, 1), (Action::Dec, 0)],
+                true,
+            ),
+            (&countdown, 2, vec![(Action::Dec, 1)], false),
+            (&countdown, 0, vec![(Action::Dec, 0)], false),
+            (&countdown, 1, vec![(Action::Inc, 0)], false),
+            (&guarded, 0, vec![(Action::Inc, 1)], true),
+            (&guarded, 0, vec![(Action::Inc, 0)], false),
+            (&guarded, 0, vec![(Action::Inc, 0), (Action::Dec, 0)], true),
+        ];
+        for (regex, start, steps, expected) in tests {
+            assert_eq!(
+                expected,
+                regex.is_match_guarded(holds, start, steps.clone()),
+                "{:?} from {}",
+                steps,
+                start
+            );
+        }
+    }
+
+    #[test]
+    fn test_derive_guarded() {
+        let regex: KatRegex = [test(Test::NonZero), action(Action::Dec)].r();
+        assert!(!regex.is_nullable_guarded(|_| true));
+        assert_eq!(
+            KatRegex::EmptyString,
+            regex.derive_guarded(|t| *t == Test::NonZero, &Action::Dec)
+        );
+        assert_eq!(
+            KatRegex::EmptySet,
+            regex.derive_guarded(|t| *t == Test::Zero, &Action::Dec)
+        );
+        assert_eq!(
+            KatRegex::EmptySet,
+            regex.derive_guarded(|_| true, &Action::Inc)
+        );
+        assert!(test(Test::Zero).is_nullable_guarded(|t| *t == Test::Zero));
+        assert!(!test(Test::Zero).is_nullable_guarded(|t| *t == Test::NonZero));
+    }
+}
@@ -0,0 +1,181 @@
+//! Language inclusion and equivalence of regular expressions.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+use crate::builder::ApproximatelySimilarCanonical;
+use crate::builder::Builder;
+use crate::builder::Regex;
+use crate::derivation::Symbols;
+use crate::Alphabet;
+
+type Asc<S> = Regex<ApproximatelySimilarCanonical<S>>;
+
+impl<B: Builder> Regex<B> {
+    /// Returns whether the language of this regular expression is a subset of the language of
+    /// the given regular expression.
+    ///
+    /// The check explores pairs of a derivative of this regular expression and the set of
+    /// alternatives of the derivative of the given regular expression, w.r.t. the same
+    /// strings. The search is done on the fly and stops at the first pair where the left is
+    /// nullable and none of the alternatives are. Pairs are pruned by subsumption: a pair is
+    /// not explored if a pair with the same left and a subset of its alternatives has been
+    /// seen, because that pair is at least as hard to satisfy.
+    pub fn is_subset_of<X: Builder<Symbol = B::Symbol>>(&self, other: &Regex<X>) -> bool {
+        let left = self.rebuild::<ApproximatelySimilarCanonical<B::Symbol>>();
+        let right = other.rebuild::<ApproximatelySimilarCanonical<B::Symbol>>();
+
+        let mut symbols = HashSet::new();
+        left.collect_symbols(&mut symbols);
+        right.collect_symbols(&mut symbols);
+        let classes = Symbols::classes(symbols);
+
+        let mut antichain = Antichain::default();
+        let mut queue = VecDeque::new();
+        let right = alternatives([right]);
+        antichain.insert(&left, &right);
+        queue.push_back((left, right));
+        while let Some((left, right)) = queue.pop_front() {
+            if right.contains(&left)
+                || right.iter().any(Regex::is_empty_set_complement)
+                || matches!(left, Regex::EmptySet)
+            {
+                continue;
+            }
+            if left.is_nullable() && !right.iter().any(Regex::is_nullable) {
+                return false;
+            }
+            for class in &classes {
+                let next_left = left.derive_symbols(class);
+                let next_right =
+                    alternatives(right.iter().map(|regex| regex.derive_symbols(class)));
+                if antichain.insert(&next_left, &next_right) {
+                    queue.push_back((next_left, next_right));
+                }
+            }
+        }
+        true
+    }
+
+    /// Returns whether this regular expression and the given regular expression have the
+    /// same language, by checking inclusion in both directions.
+    pub fn is_equivalent_to<X: Builder<Symbol = B::Symbol>>(&self, other: &Regex<X>) -> bool {
+        self.is_subset_of(other) && other.is_subset_of(self)
+    }
+}
+
+/// Returns the alternatives of the given regular expressions, which are their operands if
+/// they are alternations, excluding the empty set.
+fn alternatives<S: Alphabet>(regexes: impl IntoIterator<Item = Asc<S>>) -> HashSet<Asc<S>> {
+    let mut alternatives = HashSet::new();
+    let mut stack = regexes.into_iter().collect::<Vec<_>>();
+    while let Some(regex) = stack.pop() {
+        match regex {
+            Regex::EmptySet => {}
+            Regex::Or(left, right) => {
+                stack.push(*left);
+                stack.push(*right);
+            }
+            regex => {
+                alternatives.insert(regex);
+            }
+        }
+    }
+    alternatives
+}
+
+/// The pairs seen by the inclusion check, keeping only the minimal sets of alternatives for
+/// every left regular expression.
+struct Antichain<S: Alphabet>(HashMap<Asc<S>, Vec<HashSet<Asc<S>>>>);
+
+impl<S: Alphabet> Default for Antichain<S> {
+    fn default() -> Self {
+        Self(HashMap::new())
+    }
+}
+
+impl<S: Alphabet> Antichain<S> {
+    /// Adds the given pair, and returns whether it was not subsumed by a pair that was seen
+    /// before.
+    fn insert(&mut self, left: &Asc<S>, right: &HashSet<Asc<S>>) -> bool {
+        let seen = self.0.entry(left.clone()).or_default();
+        if seen.iter().any(|other| other.is_subset(right)) {
+            return false;
+        }
+        seen.retain(|other| !right.is_subset(other));
+        seen.push(right.clone());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::Pure;
+    use crate::ops::*;
+
+    use super::*;
+
+    type PureRegex = Regex<Pure<usize>>;
+
+    #[test]
+    fn test_is_subset_of() {
+        let tests: Vec<(PureRegex, PureRegex, bool)> = vec![
+            (().r(), 1.s(), true),
+            (1.s(), ().r(), false),
+            (1.s(), 1.s() | 2.s(), true),
+            (1.s() | 2.s(), 1.s(), false),
+            ([1.s(), 1.s()].r(), 1.s().c(), true),
+            (1.s().c(), [1.s(), 1.s()].r(), false),
+            (1.s(), !().r(), true),
+            (!().r(), 1.s().c(), false),
+            (!1.s(), !().r(), true),
+            (!1.s(), !2.s(), false),
+            (1.s() & 2.s(), ().r(), true),
+            (
+                (1.s() | 2.s()).c() & !(1.s().c()),
+                !().r() + 2.s() + !().r(),
+                true,
+            ),
+            (
+                [1.s(), 2.s() | 3.s()].r(),
+                [1.s(), 2.s()].r() | [1.s(), 3.s()].r(),
+                true,
+            ),
+            (
+                [1.s() | 2.s(), 3.s()].r().c(),
+                [1.s(), 3.s()].r().c() | [2.s(), 3.s()].r(),
+                false,
+            ),
+            (
+                [1.s() | 2.s(), 3.s()].r().c(),
+                ([1.s(), 3.s()].r() | [2.s(), 3.s()].r()).c() | !(1.s().c()),
+                true,
+            ),
+        ];
+        for (left, right, expected) in tests {
+            assert_eq!(expected, left.is_subset_of(&right), "{} ⊆ {}", left, right);
+        }
+    }
+
+    #[test]
+    fn test_is_equivalent_to() {
+        let tests: Vec<(PureRegex, PureRegex, bool)> = vec![
+            (1.s().c().c(), 1.s().c(), true),
+            ([1.s(), 1.s().c()].r(), [1.s().c(), 1.s()].r(), true),
+            (!(1.s() | 2.s()), !1.s() & !2.s(), true),
+            ((1.s() | [].r()).c(), 1.s().c(), true),
+            (1.s().c(), [1.s(), 1.s().c()].r(), false),
+            (!1.s(), 1.s(), false),
+        ];
+        for (left, right, expected) in tests {
+            assert_eq!(
+                expected,
+                left.is_equivalent_to(&right),
+                "{} = {}",
+                left,
+                right
+            );
+        }
+    }
+}
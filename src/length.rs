@@ -0,0 +1,150 @@
+//! Compute the lengths of the strings in the language of regular expressions.
+
+use std::collections::HashMap;
+
+use crate::builder::ApproximatelySimilarCanonical;
+use crate::builder::Builder;
+use crate::builder::Regex;
+
+/// A set of natural numbers, described as a union of arithmetic progressions.
+///
+/// Every progression is a pair `(offset, period)`, containing the numbers `offset + k * period`
+/// for all `k >= 0`. A progression with period `0` contains only its offset. All progressions
+/// with a non-zero period share the same period.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LengthSet {
+    progressions: Vec<(usize, usize)>,
+}
+
+impl LengthSet {
+    /// Returns the progressions in this set, ordered by offset.
+    pub fn progressions(&self) -> &[(usize, usize)] {
+        &self.progressions
+    }
+
+    /// Returns whether the given number is in this set.
+    pub fn contains(&self, len: usize) -> bool {
+        self.progressions.iter().any(|(offset, period)| {
+            if *period == 0 {
+                len == *offset
+            } else {
+                len >= *offset && (len - offset).is_multiple_of(*period)
+            }
+        })
+    }
+
+    /// Returns whether this set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.progressions.is_empty()
+    }
+
+    /// Returns whether this set is finite.
+    pub fn is_finite(&self) -> bool {
+        self.progressions.iter().all(|(_, period)| *period == 0)
+    }
+
+    /// Returns the smallest number in this set, if any.
+    pub fn min(&self) -> Option<usize> {
+        self.progressions.first().map(|(offset, _)| *offset)
+    }
+}
+
+impl<B: Builder> Regex<B> {
+    /// Returns the set of lengths of the strings in the language of this regular expression.
+    ///
+    /// The lengths are computed from the automaton for this regular expression, by following
+    /// the sets of states that are reachable with strings of increasing length, until a set
+    /// repeats. The number of steps is small in practice, but can be exponential in the number
+    /// of states in the worst case.
+    pub fn length_set(&self) -> LengthSet {
+        let fa = self
+            .rebuild::<ApproximatelySimilarCanonical<B::Symbol>>()
+            .to_automaton()
+            .trim();
+
+        let mut seen = HashMap::new();
+        let mut accepting = Vec::new();
+        let mut current = vec![false; fa.state_count()];
+        current[0] = true;
+        let (start, end) = loop {
+            if let Some(start) = seen.get(&current) {
+                break (*start, accepting.len());
+            }
+            seen.insert(current.clone(), accepting.len());
+            accepting.push((0..fa.state_count()).any(|idx| current[idx] && fa.is_accepting(idx)));
+            let mut next = vec![false; fa.state_count()];
+            for idx in (0..fa.state_count()).filter(|idx| current[*idx]) {
+                for successor in fa.successors(idx) {
+                    next[successor] = true;
+                }
+            }
+            current = next;
+        };
+
+        // The acceptance of lengths `start..end` repeats with period `end - start`. Move the
+        // start of the cycle back as far as possible, and find the smallest period of the cycle.
+        let (mut start, mut end) = (start, end);
+        while start > 0 && accepting[start - 1] == accepting[end - 1] {
+            start -= 1;
+            end -= 1;
+        }
+        let cycle = &accepting[start..end];
+        let period = (1..=cycle.len())
+            .filter(|period| cycle.len().is_multiple_of(*period))
+            .find(|period| {
+                (0..cycle.len()).all(|idx| cycle[idx] == cycle[(idx + period) % cycle.len()])
+            })
+            .unwrap_or(cycle.len());
+        let progressions = accepting[..start + period]
+            .iter()
+            .enumerate()
+            .filter(|(_, accepting)| **accepting)
+            .map(|(len, _)| (len, if len < start { 0 } else { period }))
+            .collect();
+        LengthSet { progressions }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::ops::*;
+
+    use super::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+
+    #[test]
+    fn test_length_set() {
+        let tests: Vec<(AscRegex, Vec<(usize, usize)>)> = vec![
+            (().r(), vec![]),
+            ([].r(), vec![(0, 0)]),
+            (1.s() | [1.s(), 2.s(), 3.s()].r(), vec![(1, 0), (3, 0)]),
+            (1.s().c(), vec![(0, 1)]),
+            ([1.s(), 2.s()].r().c(), vec![(0, 2)]),
+            (
+                [1.s(), 2.s(), [1.s(), 1.s(), 1.s()].r().c()].r(),
+                vec![(2, 3)],
+            ),
+            ([1.s(), 1.s()].r().c() & !([].r()), vec![(2, 2)]),
+            (!1.s(), vec![(0, 1)]),
+            (
+                [1.s(), 1.s()].r().c() | [1.s(), 1.s(), 1.s()].r().c(),
+                vec![(0, 6), (2, 6), (3, 6), (4, 6)],
+            ),
+        ];
+        for (regex, expected) in tests {
+            let lengths = regex.length_set();
+            assert_eq!(expected, lengths.progressions(), "lengths of {}", regex);
+            for len in 0..20 {
+                assert_eq!(
+                    regex.has_word_of_len_exactly(len),
+                    lengths.contains(len),
+                    "{} in lengths of {}",
+                    len,
+                    regex
+                );
+            }
+        }
+    }
+}
@@ -1,21 +1,114 @@
 //! Brzozowski regular expressions.
 
+#![cfg_attr(feature = "pattern", feature(pattern))]
+
 // FIXME add usage documentation (and move integration tests to here as doc tests?)
 
 use std::hash::Hash;
 
 mod automaton;
-mod builder;
+mod budgeted;
+pub mod builder;
+mod codegen;
+#[cfg(feature = "nom")]
+mod combinator;
 mod derivation;
+mod differential;
 mod display;
+mod dnf;
+mod equality;
+mod estimate;
+mod factor;
+mod finite;
+#[cfg(feature = "unicode-segmentation")]
+mod grapheme;
+mod guarded;
+mod inclusion;
+mod length;
+mod mutate;
 mod nullability;
 pub mod ops;
+mod prefix;
+mod product;
+pub mod raw;
+mod search;
+mod simplify;
+mod static_automaton;
+mod stats;
+mod temporal;
+mod transducer;
+mod witness;
 
 pub type Regex<S> = builder::Regex<builder::Default<S>>;
 
+pub use automaton::AlternatingAutomaton;
+pub use automaton::AutomatonPartsError;
+#[cfg(feature = "pattern")]
+pub use automaton::AutomatonSearcher;
+pub use automaton::AutomatonTableError;
+pub use automaton::BitParallelAutomaton;
+pub use automaton::Corpus;
+pub use automaton::CorpusOptions;
+pub use automaton::DeltaAutomaton;
+pub use automaton::DeltaMatcher;
+pub use automaton::DenseAutomaton;
+pub use automaton::DenseMatcher;
+pub use automaton::DerivativeCache;
 pub use automaton::FiniteAutomaton;
+pub use automaton::IndexedAutomaton;
+pub use automaton::IndexedMatcher;
+pub use automaton::LineScanner;
 pub use automaton::Matcher;
+pub use automaton::MemoryUsage;
+pub use automaton::Mismatch;
+pub use automaton::Monitor;
+pub use automaton::Nfa;
+pub use automaton::Position;
+pub use automaton::Production;
+pub use automaton::ReadScanError;
+pub use automaton::ReadScanner;
+pub use automaton::Recovery;
+pub use automaton::Rescan;
+pub use automaton::RightLinearGrammar;
+pub use automaton::Scan;
+pub use automaton::SharedAutomaton;
+pub use automaton::SharedDerivativeCache;
+pub use automaton::Span;
+pub use automaton::StateClass;
+pub use automaton::StateId;
+pub use automaton::SuffixAutomaton;
+pub use automaton::Token;
+pub use automaton::Trace;
+pub use automaton::Transition;
+pub use automaton::Verdict;
+pub use budgeted::BudgetExceeded;
+pub use codegen::RustLiteral;
+#[cfg(feature = "nom")]
+pub use combinator::RegexParser;
+pub use derivation::SymbolSet;
+pub use derivation::Symbols;
+pub use differential::Disagreement;
+pub use guarded::Guarded;
+pub use length::LengthSet;
+pub use mutate::Mutant;
+pub use mutate::Mutation;
+pub use simplify::Budget;
+pub use static_automaton::StaticAutomaton;
+pub use static_automaton::StaticAutomatonError;
+pub use static_automaton::StaticMatcher;
+pub use stats::RegexStats;
+pub use transducer::Transducer;
+pub use transducer::Translator;
 
 pub trait Alphabet: Clone + Eq + Hash + Ord {}
 
 impl<S> Alphabet for S where S: Clone + Eq + Hash + Ord {}
+
+/// An alphabet with a finite number of symbols that can be enumerated.
+///
+/// Implementing this trait is opt-in. It enables operations that need to know the
+/// complete alphabet, such as a complement that only accepts symbols from the alphabet.
+pub trait FiniteAlphabet: Alphabet {
+    /// Returns all symbols in the alphabet.
+    fn universe() -> impl Iterator<Item = Self>;
+}
@@ -0,0 +1,223 @@
+//! Systematic small mutations of regular expressions, for mutation testing of specifications.
+
+use std::collections::HashSet;
+
+use crate::builder::Builder;
+use crate::builder::Regex;
+
+/// The kinds of mutations produced by [`Regex::mutants`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Mutation {
+    /// Swaps the operands of a concatenation.
+    SwapOperands,
+    /// Replaces a concatenation, alternation, or intersection by one of its operands.
+    DropOperand,
+    /// Replaces a symbol by another symbol from the alphabet.
+    ChangeSymbol,
+    /// Widens a subterm `R` that is not already repeated to the closure `R*`.
+    WidenClosure,
+}
+
+/// A regular expression obtained by a single mutation of another, returned by
+/// [`Regex::mutants`].
+#[derive(Eq, PartialEq)]
+pub struct Mutant<B: Builder> {
+    /// The kind of mutation that was applied.
+    pub mutation: Mutation,
+    /// The mutated regular expression.
+    pub regex: Regex<B>,
+}
+
+impl<B: Builder> Clone for Mutant<B> {
+    fn clone(&self) -> Self {
+        Self {
+            mutation: self.mutation,
+            regex: self.regex.clone(),
+        }
+    }
+}
+
+impl<B: Builder> std::fmt::Debug for Mutant<B>
+where
+    Regex<B>: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Mutant")
+            .field("mutation", &self.mutation)
+            .field("regex", &self.regex)
+            .finish()
+    }
+}
+
+impl<B: Builder> Regex<B> {
+    /// Returns the regular expressions that differ from this one by a single mutation at any of
+    /// its subterms, using symbols from the given alphabet for changed symbols.
+    ///
+    /// Mutants are built with the builder of this regular expression, and are returned in a
+    /// deterministic order, from the outermost operators inwards. Mutants that are
+    /// structurally equal to this regular expression or to an earlier mutant are left out. A
+    /// mutant may still be equivalent to this regular expression.
+    pub fn mutants(&self, alphabet: &[B::Symbol]) -> Vec<Mutant<B>> {
+        let mut seen = HashSet::from([self.clone()]);
+        self.mutate(alphabet, true)
+            .into_iter()
+            .filter(|mutant| seen.insert(mutant.regex.clone()))
+            .collect()
+    }
+
+    /// Returns the mutants of this regular expression, as [`Regex::mutants`] does, that match
+    /// exactly the same of the given words as this regular expression. If any mutant survives,
+    /// the words do not distinguish this regular expression from it.
+    pub fn surviving_mutants<W: AsRef<[B::Symbol]>>(
+        &self,
+        alphabet: &[B::Symbol],
+        words: &[W],
+    ) -> Vec<Mutant<B>> {
+        let expected = words
+            .iter()
+            .map(|word| self.is_match(word.as_ref()))
+            .collect::<Vec<_>>();
+        self.mutants(alphabet)
+            .into_iter()
+            .filter(|mutant| {
+                words
+                    .iter()
+                    .zip(&expected)
+                    .all(|(word, expected)| mutant.regex.is_match(word.as_ref()) == *expected)
+            })
+            .collect()
+    }
+
+    /// Returns all mutants of this regular expression, possibly with duplicates. Widening
+    /// this regular expression itself is only included if `widen` is set.
+    fn mutate(&self, alphabet: &[B::Symbol], widen: bool) -> Vec<Mutant<B>> {
+        let mut mutants = Vec::new();
+        let mut push = |mutation, regex| mutants.push(Mutant { mutation, regex });
+        match self {
+            Regex::EmptySet | Regex::EmptyString => {}
+            Regex::Symbol(value) => {
+                for symbol in alphabet.iter().filter(|symbol| *symbol != value) {
+                    push(Mutation::ChangeSymbol, B::symbol(symbol.clone()));
+                }
+            }
+            Regex::Concat(left, right) => {
+                push(
+                    Mutation::SwapOperands,
+                    B::concat(right.as_ref().clone(), left.as_ref().clone()),
+                );
+                Self::mutate_operands(left, right, alphabet, B::concat, &mut push);
+            }
+            Regex::Closure(inner) => {
+                for mutant in inner.mutate(alphabet, false) {
+                    push(mutant.mutation, B::closure(mutant.regex));
+                }
+            }
+            Regex::Or(left, right) => {
+                Self::mutate_operands(left, right, alphabet, B::or, &mut push);
+            }
+            Regex::And(left, right) => {
+                Self::mutate_operands(left, right, alphabet, B::and, &mut push);
+            }
+            Regex::Complement(inner) => {
+                for mutant in inner.mutate(alphabet, true) {
+                    push(mutant.mutation, B::complement(mutant.regex));
+                }
+            }
+        }
+        if widen
+            && !matches!(
+                self,
+                Regex::EmptySet | Regex::EmptyString | Regex::Closure(_)
+            )
+        {
+            push(Mutation::WidenClosure, B::closure(self.clone()));
+        }
+        mutants
+    }
+
+    /// Pushes the mutants that drop either operand of a binary operator, followed by the
+    /// mutants of either operand combined with the other operand.
+    fn mutate_operands(
+        left: &Self,
+        right: &Self,
+        alphabet: &[B::Symbol],
+        operator: fn(Self, Self) -> Self,
+        push: &mut impl FnMut(Mutation, Self),
+    ) {
+        push(Mutation::DropOperand, left.clone());
+        push(Mutation::DropOperand, right.clone());
+        for mutant in left.mutate(alphabet, true) {
+            push(mutant.mutation, operator(mutant.regex, right.clone()));
+        }
+        for mutant in right.mutate(alphabet, true) {
+            push(mutant.mutation, operator(left.clone(), mutant.regex));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Pure;
+    use crate::ops::*;
+
+    use super::*;
+
+    #[test]
+    fn test_mutants() {
+        let regex: Regex<Pure<usize>> = [1.s(), 2.s().c()].r();
+        let expected: Vec<(Mutation, Regex<Pure<usize>>)> = vec![
+            (Mutation::SwapOperands, [2.s().c(), 1.s()].r()),
+            (Mutation::DropOperand, 1.s()),
+            (Mutation::DropOperand, 2.s().c()),
+            (Mutation::ChangeSymbol, [2.s(), 2.s().c()].r()),
+            (Mutation::WidenClosure, [1.s().c(), 2.s().c()].r()),
+            (Mutation::ChangeSymbol, [1.s(), 1.s().c()].r()),
+            (Mutation::WidenClosure, [1.s(), 2.s().c()].r().c()),
+        ];
+        let actual = regex
+            .mutants(&[1, 2])
+            .into_iter()
+            .map(|mutant| (mutant.mutation, mutant.regex))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_mutants_dedup() {
+        let tests: Vec<(Regex<ApproximatelySimilarCanonical<usize>>, usize)> = vec![
+            (().r(), 0),
+            ([].r(), 0),
+            (1.s(), 2),
+            (1.s().c(), 1),
+            (1.s() | 2.s(), 5),
+        ];
+        for (regex, expected) in tests {
+            let mutants = regex.mutants(&[1, 2]);
+            assert_eq!(expected, mutants.len(), "{}", regex);
+            for mutant in &mutants {
+                assert_ne!(regex, mutant.regex, "{}", regex);
+            }
+        }
+    }
+
+    #[test]
+    fn test_surviving_mutants() {
+        let regex: Regex<Pure<usize>> = [1.s(), 2.s().c()].r();
+        let survivors = regex.surviving_mutants(&[1, 2], &[vec![1], vec![1, 2]]);
+        let expected = vec![
+            Mutant {
+                mutation: Mutation::WidenClosure,
+                regex: [1.s().c(), 2.s().c()].r(),
+            },
+            Mutant {
+                mutation: Mutation::WidenClosure,
+                regex: [1.s(), 2.s().c()].r().c(),
+            },
+        ];
+        assert_eq!(expected, survivors);
+        assert!(regex
+            .surviving_mutants(&[1, 2], &[vec![], vec![1], vec![1, 2]])
+            .is_empty());
+    }
+}
@@ -32,6 +32,7 @@ impl<B: Builder> Regex<B> {
 mod tests {
     use crate::builder::ApproximatelySimilarCanonical;
     use crate::builder::Pure;
+    use crate::builder::StronglyCanonical;
     use crate::ops::*;
 
     use super::*;
@@ -46,6 +47,11 @@ mod tests {
         test_is_nullable::<ApproximatelySimilarCanonical<_>>();
     }
 
+    #[test]
+    fn test_is_nullable_strong() {
+        test_is_nullable::<StronglyCanonical<_>>();
+    }
+
     fn test_is_nullable<B: Builder<Symbol = usize> + Clone>() {
         let tests: Vec<(Regex<B>, bool)> = vec![
             (!().r(), true),
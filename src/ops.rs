@@ -25,7 +25,7 @@ impl<B: Builder> IntoRegex<B> for () {
 // empty string is a special case of concat
 
 pub fn sym<B: Builder>(value: B::Symbol) -> Regex<B> {
-    B::symbol(value.into())
+    B::symbol(value)
 }
 
 impl<B: Builder> IntoSymbol<B> for B::Symbol {
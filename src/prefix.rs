@@ -0,0 +1,115 @@
+//! Literal prefixes of the strings in the language of regular expressions.
+
+use std::collections::BTreeSet;
+use std::collections::HashSet;
+
+use crate::builder::ApproximatelySimilarCanonical;
+use crate::builder::Builder;
+use crate::builder::Regex;
+use crate::derivation::Symbols;
+use crate::Alphabet;
+
+impl<B: Builder> Regex<B> {
+    /// Returns a set of strings of at most the given length, such that every string in the
+    /// language of this regular expression starts with one of them.
+    ///
+    /// Prefixes are extended symbol by symbol until they reach the maximum length, the
+    /// remaining language contains the empty string, or the next symbol is not one of the
+    /// symbols that occur in the regular expression. The result is empty if the language is
+    /// empty, and contains the empty string if no literal prefix is known.
+    pub fn literal_prefixes(&self, max: usize) -> BTreeSet<Vec<B::Symbol>> {
+        let regex = self.rebuild::<ApproximatelySimilarCanonical<B::Symbol>>();
+
+        let mut symbols = HashSet::new();
+        regex.collect_symbols(&mut symbols);
+        let mut classes = Symbols::classes(symbols);
+        let default_symbols = classes.pop().expect("default class");
+
+        let mut prefixes = BTreeSet::new();
+        let mut prefix = Vec::new();
+        regex.collect_literal_prefixes(&classes, &default_symbols, max, &mut prefix, &mut prefixes);
+        prefixes
+    }
+}
+
+impl<S: Alphabet> Regex<ApproximatelySimilarCanonical<S>> {
+    fn collect_literal_prefixes(
+        &self,
+        classes: &[Symbols<S>],
+        default_symbols: &Symbols<S>,
+        max: usize,
+        prefix: &mut Vec<S>,
+        prefixes: &mut BTreeSet<Vec<S>>,
+    ) {
+        if matches!(self, Regex::EmptySet) {
+            return;
+        }
+        if prefix.len() == max
+            || self.is_nullable()
+            || !matches!(self.derive_symbols(default_symbols), Regex::EmptySet)
+        {
+            prefixes.insert(prefix.clone());
+            return;
+        }
+        for class in classes {
+            let Symbols::Include(symbols) = class else {
+                unreachable!("singleton class");
+            };
+            let symbol = symbols.iter().next().expect("singleton class");
+            prefix.push(symbol.clone());
+            self.derive_symbols(class).collect_literal_prefixes(
+                classes,
+                default_symbols,
+                max,
+                prefix,
+                prefixes,
+            );
+            prefix.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::Pure;
+    use crate::ops::*;
+
+    use super::*;
+
+    type PureRegex = Regex<Pure<usize>>;
+
+    #[test]
+    fn test_literal_prefixes() {
+        let tests: Vec<(PureRegex, usize, Vec<Vec<usize>>)> = vec![
+            (().r(), 3, vec![]),
+            ([].r(), 3, vec![vec![]]),
+            ([1.s(), 2.s(), 3.s()].r(), 2, vec![vec![1, 2]]),
+            ([1.s(), 2.s(), 3.s()].r(), 5, vec![vec![1, 2, 3]]),
+            (
+                [1.s(), 2.s()].r() | [1.s(), 3.s()].r(),
+                5,
+                vec![vec![1, 2], vec![1, 3]],
+            ),
+            (
+                [1.s(), 2.s().c(), 3.s()].r(),
+                3,
+                vec![vec![1, 2, 2], vec![1, 2, 3], vec![1, 3]],
+            ),
+            (
+                [1.s(), 2.s().c(), 3.s()].r(),
+                2,
+                vec![vec![1, 2], vec![1, 3]],
+            ),
+            ([1.s(), !2.s()].r(), 5, vec![vec![1]]),
+            ([1.s(), 2.s()].r() & [1.s(), 3.s()].r(), 5, vec![]),
+        ];
+        for (regex, max, expected) in tests {
+            assert_eq!(
+                expected.into_iter().collect::<BTreeSet<_>>(),
+                regex.literal_prefixes(max),
+                "{}",
+                regex
+            );
+        }
+    }
+}
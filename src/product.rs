@@ -0,0 +1,296 @@
+//! Regular expressions over product alphabets.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use crate::automaton::Prehashed;
+use crate::builder::ApproximatelySimilarCanonical;
+use crate::builder::Builder;
+use crate::builder::Regex;
+use crate::Alphabet;
+use crate::FiniteAlphabet;
+
+impl<A: FiniteAlphabet, B: FiniteAlphabet> FiniteAlphabet for (A, B) {
+    fn universe() -> impl Iterator<Item = Self> {
+        A::universe().flat_map(|a| B::universe().map(move |b| (a.clone(), b)))
+    }
+}
+
+impl<L, R, B> Regex<B>
+where
+    L: FiniteAlphabet,
+    R: FiniteAlphabet,
+    B: Builder<Symbol = (L, R)>,
+{
+    /// Returns a regular expression for the left components of the strings in the language
+    /// of this regular expression.
+    pub fn project_left<X: Builder<Symbol = L>>(&self) -> Regex<X> {
+        self.project(|(left, _)| left.clone())
+    }
+
+    /// Returns a regular expression for the right components of the strings in the language
+    /// of this regular expression.
+    pub fn project_right<X: Builder<Symbol = R>>(&self) -> Regex<X> {
+        self.project(|(_, right)| right.clone())
+    }
+}
+
+impl<B: Builder> Regex<B> {
+    /// Returns a regular expression over a product alphabet, that constrains the left
+    /// components of its strings by this regular expression, and leaves the right
+    /// components unconstrained.
+    pub fn cylindrify_left<R, X>(&self) -> Regex<X>
+    where
+        R: FiniteAlphabet,
+        X: Builder<Symbol = (B::Symbol, R)>,
+    {
+        self.cylindrify(&|left: &B::Symbol| {
+            R::universe().map(|right| (left.clone(), right)).collect()
+        })
+    }
+
+    /// Returns a regular expression over a product alphabet, that constrains the right
+    /// components of its strings by this regular expression, and leaves the left
+    /// components unconstrained.
+    pub fn cylindrify_right<L, X>(&self) -> Regex<X>
+    where
+        L: FiniteAlphabet,
+        X: Builder<Symbol = (L, B::Symbol)>,
+    {
+        self.cylindrify(&|right: &B::Symbol| {
+            L::universe().map(|left| (left, right.clone())).collect()
+        })
+    }
+
+    /// Rebuild this regular expression, replacing every symbol with the alternation of
+    /// the symbols it is mapped to. This is the inverse image of the symbol mapping,
+    /// which (unlike the image) commutes with all operators.
+    fn cylindrify<X: Builder>(&self, f: &impl Fn(&B::Symbol) -> Vec<X::Symbol>) -> Regex<X> {
+        match self {
+            Regex::EmptySet => X::empty_set(),
+            Regex::EmptyString => X::empty_string(),
+            Regex::Symbol(value) => f(value)
+                .into_iter()
+                .map(X::symbol)
+                .reduce(X::or)
+                .unwrap_or_else(X::empty_set),
+            Regex::Concat(left, right) => X::concat(left.cylindrify(f), right.cylindrify(f)),
+            Regex::Closure(inner) => X::closure(inner.cylindrify(f)),
+            Regex::Or(left, right) => X::or(left.cylindrify(f), right.cylindrify(f)),
+            Regex::And(left, right) => X::and(left.cylindrify(f), right.cylindrify(f)),
+            Regex::Complement(inner) => X::complement(inner.cylindrify(f)),
+        }
+    }
+}
+
+impl<B: Builder> Regex<B>
+where
+    B::Symbol: FiniteAlphabet,
+{
+    /// Returns a regular expression for the image of the language of this regular expression
+    /// under the given symbol mapping.
+    ///
+    /// The image does not commute with intersection and complement, so it cannot be computed
+    /// structurally. Instead, the derivative of the image w.r.t. a symbol `t` is the image of
+    /// the union of the derivatives w.r.t. all symbols mapped to `t`. The resulting automaton
+    /// is converted back into a regular expression by state elimination.
+    fn project<T: Alphabet, X: Builder<Symbol = T>>(
+        &self,
+        f: impl Fn(&B::Symbol) -> T,
+    ) -> Regex<X> {
+        let mut classes: HashMap<T, Vec<B::Symbol>> = HashMap::new();
+        for symbol in B::Symbol::universe() {
+            classes.entry(f(&symbol)).or_default().push(symbol);
+        }
+
+        let mut regexes: HashMap<
+            Prehashed<Regex<ApproximatelySimilarCanonical<B::Symbol>>>,
+            usize,
+        > = HashMap::new();
+        let mut accepting = Vec::new();
+        let mut edges: HashMap<(usize, usize), Regex<X>> = HashMap::new();
+
+        let mut queue = VecDeque::new();
+        let start = self.rebuild();
+        regexes.insert(Prehashed::new(start.clone()), 0);
+        queue.push_back((start, 0));
+        while let Some((regex, idx)) = queue.pop_front() {
+            accepting.push(regex.is_nullable());
+            for (target, sources) in &classes {
+                let next = sources
+                    .iter()
+                    .map(|source| regex.derive(source))
+                    .reduce(Regex::or)
+                    .expect("at least one source symbol");
+                if matches!(next, Regex::EmptySet) {
+                    continue;
+                }
+                let next = Prehashed::new(next);
+                let next_idx = if let Some(next_idx) = regexes.get(&next) {
+                    *next_idx
+                } else {
+                    let next_idx = regexes.len();
+                    queue.push_back((next.value.clone(), next_idx));
+                    regexes.insert(next, next_idx);
+                    next_idx
+                };
+                let edge = edges.remove(&(idx, next_idx)).unwrap_or_else(X::empty_set);
+                edges.insert((idx, next_idx), X::or(edge, X::symbol(target.clone())));
+            }
+        }
+
+        eliminate_states(0, accepting, edges)
+    }
+}
+
+/// Convert an automaton, given by the accepting flags of its states and regular expression
+/// labeled edges, into a regular expression for the language accepted from the given state.
+pub(crate) fn eliminate_states<X: Builder>(
+    start: usize,
+    accepting: Vec<bool>,
+    mut edges: HashMap<(usize, usize), Regex<X>>,
+) -> Regex<X> {
+    let initial = accepting.len();
+    let final_ = initial + 1;
+    edges.insert((initial, start), X::empty_string());
+    for (idx, accepting) in accepting.iter().enumerate() {
+        if *accepting {
+            edges.insert((idx, final_), X::empty_string());
+        }
+    }
+
+    for eliminated in 0..initial {
+        let closure = edges
+            .remove(&(eliminated, eliminated))
+            .map(X::closure)
+            .unwrap_or_else(X::empty_string);
+        let (incoming, outgoing): (Vec<_>, Vec<_>) = edges
+            .keys()
+            .filter(|(from, to)| *from == eliminated || *to == eliminated)
+            .cloned()
+            .partition(|(_, to)| *to == eliminated);
+        let incoming = incoming
+            .into_iter()
+            .map(|key| (key.0, edges.remove(&key).expect("edge exists")))
+            .collect::<Vec<_>>();
+        let outgoing = outgoing
+            .into_iter()
+            .map(|key| (key.1, edges.remove(&key).expect("edge exists")))
+            .collect::<Vec<_>>();
+        for (from, in_regex) in &incoming {
+            for (to, out_regex) in &outgoing {
+                let path = X::concat(
+                    X::concat(in_regex.clone(), closure.clone()),
+                    out_regex.clone(),
+                );
+                let edge = edges.remove(&(*from, *to)).unwrap_or_else(X::empty_set);
+                edges.insert((*from, *to), X::or(edge, path));
+            }
+        }
+    }
+
+    edges
+        .remove(&(initial, final_))
+        .unwrap_or_else(X::empty_set)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Pure;
+    use crate::ops::*;
+
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    enum Bit {
+        Zero,
+        One,
+    }
+
+    impl FiniteAlphabet for Bit {
+        fn universe() -> impl Iterator<Item = Self> {
+            [Bit::Zero, Bit::One].into_iter()
+        }
+    }
+
+    use Bit::*;
+
+    type PairRegex = Regex<ApproximatelySimilarCanonical<(Bit, Bit)>>;
+    type BitRegex = Regex<ApproximatelySimilarCanonical<Bit>>;
+    type Word = Vec<(Bit, Bit)>;
+
+    #[test]
+    fn test_project() {
+        let tests: Vec<(PairRegex, Vec<Bit>, Vec<Bit>, bool)> = vec![
+            (
+                (Zero, One).s() + (One, One).s(),
+                vec![Zero, One],
+                vec![One, One],
+                true,
+            ),
+            (
+                (Zero, One).s() + (One, One).s(),
+                vec![Zero, Zero],
+                vec![Zero, One],
+                false,
+            ),
+            (
+                ((Zero, Zero).s() | (Zero, One).s()) & !(Zero, Zero).s(),
+                vec![Zero],
+                vec![One],
+                true,
+            ),
+            (
+                ((Zero, Zero).s() | (Zero, One).s()) & !(Zero, Zero).s(),
+                vec![One],
+                vec![Zero],
+                false,
+            ),
+            (!().r(), vec![One, Zero], vec![Zero, Zero], true),
+            (
+                ((Zero, One).s() | (One, Zero).s()).c(),
+                vec![Zero, One],
+                vec![One, Zero],
+                true,
+            ),
+        ];
+        for (regex, left, right, expected) in tests {
+            let left_regex: Regex<Pure<Bit>> = regex.project_left();
+            let right_regex: BitRegex = regex.project_right();
+            assert_eq!(
+                expected,
+                left_regex.is_match(&left),
+                "left projection {:?} of {:?}",
+                left_regex,
+                regex
+            );
+            assert_eq!(
+                expected,
+                right_regex.is_match(&right),
+                "right projection {:?} of {:?}",
+                right_regex,
+                regex
+            );
+        }
+    }
+
+    #[test]
+    fn test_cylindrify() {
+        let tests: Vec<(BitRegex, Word, bool)> = vec![
+            (Zero.s(), vec![(Zero, One)], true),
+            (Zero.s(), vec![(One, Zero)], false),
+            (!Zero.s(), vec![(One, Zero)], true),
+            (!Zero.s(), vec![(Zero, Zero)], false),
+            (!Zero.s(), vec![], true),
+            (Zero.s().c(), vec![(Zero, One), (Zero, Zero)], true),
+        ];
+        for (regex, word, expected) in tests {
+            let cylindrified: PairRegex = regex.cylindrify_left();
+            assert_eq!(expected, cylindrified.is_match(&word));
+            let swapped = word.iter().map(|(l, r)| (*r, *l)).collect::<Vec<_>>();
+            let cylindrified: PairRegex = regex.cylindrify_right();
+            assert_eq!(expected, cylindrified.is_match(&swapped));
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Raw access to the tables of compiled automata.
+//!
+//! The views in this module expose the memory layout of automata, so that embedders can
+//! implement their own matching loops, for example using SIMD instructions or on a GPU. The
+//! layout is part of the public API, and is documented on the views.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::automaton::StateId;
+use crate::Alphabet;
+
+/// A borrowed view of the tables of a [`DenseAutomaton`](crate::DenseAutomaton), returned by
+/// [`DenseAutomaton::raw`](crate::DenseAutomaton::raw).
+///
+/// States are numbered `0..state_count()`, and matching starts in [`Self::start_state`].
+/// Symbols are mapped to classes `0..class_count()` by [`Self::class_of`], where symbols that
+/// do not occur in the regular expression belong to class `0`. The transition from `state`
+/// on a symbol of class `class` is `transitions()[state * class_count() + class]`.
+#[derive(Clone, Copy, Debug)]
+pub struct RawDenseAutomaton<'a, S: Alphabet, I: StateId = usize> {
+    classes: &'a HashMap<S, usize>,
+    class_count: usize,
+    accepting: &'a [u64],
+    transitions: &'a [I],
+}
+
+impl<'a, S: Alphabet, I: StateId> RawDenseAutomaton<'a, S, I> {
+    pub(crate) fn new(
+        classes: &'a HashMap<S, usize>,
+        class_count: usize,
+        accepting: &'a [u64],
+        transitions: &'a [I],
+    ) -> Self {
+        Self {
+            classes,
+            class_count,
+            accepting,
+            transitions,
+        }
+    }
+
+    /// Returns the state in which matching starts, which is always `0`.
+    pub fn start_state(&self) -> usize {
+        0
+    }
+
+    /// Returns the number of states, which is the number of rows of the transition table.
+    pub fn state_count(&self) -> usize {
+        self.transitions.len() / self.class_count
+    }
+
+    /// Returns the number of symbol classes, which is the number of columns of the transition
+    /// table.
+    pub fn class_count(&self) -> usize {
+        self.class_count
+    }
+
+    /// Returns the class of the given symbol.
+    pub fn class_of<Q>(&self, symbol: &Q) -> usize
+    where
+        S: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.classes.get(symbol).copied().unwrap_or(0)
+    }
+
+    /// Returns the symbols that do not belong to class `0`, with their classes, in no
+    /// particular order.
+    pub fn classes(&self) -> impl Iterator<Item = (&'a S, usize)> {
+        self.classes.iter().map(|(symbol, class)| (symbol, *class))
+    }
+
+    /// Returns the transition table, with a row of `class_count()` target states for every
+    /// state, in order.
+    pub fn transitions(&self) -> &'a [I] {
+        self.transitions
+    }
+
+    /// Returns the accepting states as a bitset, where `state` is accepting if bit
+    /// `state % 64` of word `state / 64` is set. Bits beyond the last state are not set.
+    pub fn accepting(&self) -> &'a [u64] {
+        self.accepting
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+
+    #[test]
+    fn test_raw_dense_automaton() {
+        let regex: AscRegex = [1.s(), (2.s() | 3.s()).c()].r();
+        let dense = regex.to_automaton_with_state_ids::<u8>().to_dense();
+        let raw = dense.raw();
+        assert_eq!(3, raw.state_count());
+        assert_eq!(3, raw.class_count());
+        assert_eq!(raw.class_of(&2), raw.class_of(&3));
+        assert_eq!(0, raw.class_of(&4));
+        assert_eq!(3, raw.classes().count());
+
+        let tests = vec![
+            (vec![], false),
+            (vec![1], true),
+            (vec![1, 3, 2], true),
+            (vec![1, 4], false),
+            (vec![2], false),
+        ];
+        for (word, expected) in tests {
+            let state = word.iter().fold(raw.start_state(), |state, symbol| {
+                raw.transitions()[state * raw.class_count() + raw.class_of(symbol)] as usize
+            });
+            let accepting = raw.accepting()[state / 64] & (1 << (state % 64)) != 0;
+            assert_eq!(expected, accepting, "{:?}", word);
+            assert_eq!(expected, dense.to_matcher().next_iter(&word), "{:?}", word);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Search for strings in the language of regular expressions.
+
+use std::collections::HashSet;
+
+use crate::builder::ApproximatelySimilarCanonical;
+use crate::builder::Builder;
+use crate::builder::Regex;
+use crate::derivation::Symbols;
+
+impl<B: Builder> Regex<B> {
+    /// Returns whether the language of this regular expression contains a string of at most
+    /// the given length.
+    pub fn has_word_of_len_at_most(&self, len: usize) -> bool {
+        self.search_bounded(len, true)
+    }
+
+    /// Returns whether the language of this regular expression contains a string of exactly
+    /// the given length.
+    pub fn has_word_of_len_exactly(&self, len: usize) -> bool {
+        self.search_bounded(len, false)
+    }
+
+    /// Breadth-first search over the derivatives of this regular expression, up to the given
+    /// depth. Derivatives are deduplicated per level, so the cost is bounded by the number of
+    /// distinct derivatives times the depth.
+    fn search_bounded(&self, len: usize, accept_shorter: bool) -> bool {
+        let regex = self.rebuild::<ApproximatelySimilarCanonical<B::Symbol>>();
+
+        let mut symbols = HashSet::new();
+        regex.collect_symbols(&mut symbols);
+        let classes = Symbols::classes(symbols);
+
+        let mut level = HashSet::from([regex]);
+        for depth in 0..=len {
+            if (accept_shorter || depth == len) && level.iter().any(Regex::is_nullable) {
+                return true;
+            }
+            if depth == len {
+                break;
+            }
+            level = level
+                .iter()
+                .flat_map(|regex| classes.iter().map(|class| regex.derive_symbols(class)))
+                .filter(|regex| !matches!(regex, Regex::EmptySet))
+                .collect();
+            if level.is_empty() {
+                break;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::Pure;
+    use crate::ops::*;
+
+    use super::*;
+
+    type PureRegex = Regex<Pure<usize>>;
+
+    #[test]
+    fn test_has_word_of_len() {
+        let tests: Vec<(PureRegex, usize, bool, bool)> = vec![
+            (().r(), 3, false, false),
+            ([].r(), 0, true, true),
+            ([].r(), 1, true, false),
+            (1.s(), 0, false, false),
+            (1.s(), 1, true, true),
+            (1.s(), 2, true, false),
+            ([1.s(), 2.s(), 3.s()].r(), 2, false, false),
+            ([1.s(), 2.s()].r().c(), 3, true, false),
+            ([1.s(), 2.s()].r().c(), 4, true, true),
+            (!1.s(), 1, true, true),
+            (!().r() & ![].r() & !1.s(), 1, true, true),
+            (1.s().c() & !(1.s() | [].r()), 1, false, false),
+            (1.s().c() & !(1.s() | [].r()), 2, true, true),
+        ];
+        for (regex, len, at_most, exactly) in tests {
+            assert_eq!(
+                at_most,
+                regex.has_word_of_len_at_most(len),
+                "{} ≤ {}",
+                regex,
+                len
+            );
+            assert_eq!(
+                exactly,
+                regex.has_word_of_len_exactly(len),
+                "{} = {}",
+                regex,
+                len
+            );
+        }
+    }
+}
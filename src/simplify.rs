@@ -0,0 +1,267 @@
+//! Simplify regular expressions within a budget.
+
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::builder::Builder;
+use crate::builder::Regex;
+use crate::builder::StronglyCanonical;
+
+/// Limits on the work done by [`Regex::simplify_within`](crate::builder::Regex::simplify_within)
+/// and by budgeted matching, such as [`Regex::is_match_with_budget`].
+///
+/// The node limit counts the nodes that are visited across all simplification passes, and
+/// the step limit counts the symbols that are consumed when matching. The time limit starts
+/// when simplification or matching starts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Budget {
+    nodes: Option<usize>,
+    steps: Option<usize>,
+    time: Option<Duration>,
+}
+
+impl Budget {
+    /// A budget without limits, which simplifies until a fixpoint is reached.
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Limit the number of nodes that are visited.
+    pub fn with_nodes(self, nodes: usize) -> Self {
+        Self {
+            nodes: Some(nodes),
+            ..self
+        }
+    }
+
+    /// Limit the number of symbols that are consumed when matching.
+    pub fn with_steps(self, steps: usize) -> Self {
+        Self {
+            steps: Some(steps),
+            ..self
+        }
+    }
+
+    /// Limit the time that is spent.
+    pub fn with_time(self, time: Duration) -> Self {
+        Self {
+            time: Some(time),
+            ..self
+        }
+    }
+}
+
+/// Tracks the remaining budget during simplification or matching.
+pub(crate) struct Meter {
+    remaining: Option<usize>,
+    deadline: Option<Instant>,
+    exhausted: bool,
+}
+
+impl Meter {
+    /// Returns a meter for simplification, which counts nodes.
+    fn for_nodes(budget: Budget) -> Self {
+        Self::new(budget.nodes, budget.time)
+    }
+
+    /// Returns a meter for matching, which counts steps.
+    pub(crate) fn for_steps(budget: Budget) -> Self {
+        Self::new(budget.steps, budget.time)
+    }
+
+    fn new(remaining: Option<usize>, time: Option<Duration>) -> Self {
+        Self {
+            remaining,
+            deadline: time.map(|time| Instant::now() + time),
+            exhausted: false,
+        }
+    }
+
+    /// Consume budget for one node or step, and return whether it may be taken.
+    pub(crate) fn tick(&mut self) -> bool {
+        if self.exhausted {
+            return false;
+        }
+        match &mut self.remaining {
+            Some(0) => self.exhausted = true,
+            Some(remaining) => *remaining -= 1,
+            None => {}
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.exhausted = true;
+        }
+        !self.exhausted
+    }
+}
+
+impl<B: Builder> Regex<B> {
+    /// Simplify this regular expression using the rules of the [`StronglyCanonical`] builder,
+    /// without spending more than the given budget.
+    ///
+    /// Simplification rebuilds the regular expression bottom-up, repeatedly, until it no
+    /// longer changes. If the budget runs out, the nodes that were not completely rebuilt are
+    /// kept as-is, and the smallest form found so far is returned. The result is never larger than this
+    /// regular expression, and always has the same language.
+    pub fn simplify_within(&self, budget: Budget) -> Self {
+        let mut meter = Meter::for_nodes(budget);
+        let mut current: Regex<StronglyCanonical<B::Symbol>> = self.verbatim();
+        let mut best: Option<Regex<StronglyCanonical<B::Symbol>>> = None;
+        let mut best_size = self.size();
+        loop {
+            let next = current.simplify_pass(&mut meter);
+            let next_size = next.size();
+            if next_size < best_size {
+                best = Some(next.verbatim());
+                best_size = next_size;
+            }
+            if meter.exhausted || next == current {
+                break;
+            }
+            current = next;
+        }
+        match best {
+            Some(best) => best.rebuild(),
+            None => self.verbatim(),
+        }
+    }
+
+    /// Returns the number of nodes in the regular expression.
+    pub(crate) fn size(&self) -> usize {
+        match self {
+            Regex::EmptySet | Regex::EmptyString | Regex::Symbol(_) => 1,
+            Regex::Closure(inner) | Regex::Complement(inner) => 1 + inner.size(),
+            Regex::Concat(left, right) | Regex::Or(left, right) | Regex::And(left, right) => {
+                1 + left.size() + right.size()
+            }
+        }
+    }
+
+    /// Copy this regular expression into a different builder, without applying its rules.
+    fn verbatim<X: Builder<Symbol = B::Symbol>>(&self) -> Regex<X> {
+        match self {
+            Regex::EmptySet => Regex::EmptySet,
+            Regex::EmptyString => Regex::EmptyString,
+            Regex::Symbol(value) => Regex::Symbol(value.clone()),
+            Regex::Concat(left, right) => {
+                Regex::Concat(left.verbatim().into(), right.verbatim().into())
+            }
+            Regex::Closure(inner) => Regex::Closure(inner.verbatim().into()),
+            Regex::Or(left, right) => Regex::Or(left.verbatim().into(), right.verbatim().into()),
+            Regex::And(left, right) => Regex::And(left.verbatim().into(), right.verbatim().into()),
+            Regex::Complement(inner) => Regex::Complement(inner.verbatim().into()),
+        }
+    }
+
+    /// Rebuild this regular expression bottom-up, for as long as the budget allows.
+    fn simplify_pass(&self, meter: &mut Meter) -> Self {
+        if !meter.tick() {
+            return self.verbatim();
+        }
+        match self {
+            Regex::EmptySet => B::empty_set(),
+            Regex::EmptyString => B::empty_string(),
+            Regex::Symbol(value) => B::symbol(value.clone()),
+            Regex::Concat(left, right) => {
+                let (left, right) = (left.simplify_pass(meter), right.simplify_pass(meter));
+                self.unless_exhausted(meter, || B::concat(left, right))
+            }
+            Regex::Closure(inner) => {
+                let inner = inner.simplify_pass(meter);
+                self.unless_exhausted(meter, || B::closure(inner))
+            }
+            Regex::Or(left, right) => {
+                let (left, right) = (left.simplify_pass(meter), right.simplify_pass(meter));
+                self.unless_exhausted(meter, || B::or(left, right))
+            }
+            Regex::And(left, right) => {
+                let (left, right) = (left.simplify_pass(meter), right.simplify_pass(meter));
+                self.unless_exhausted(meter, || B::and(left, right))
+            }
+            Regex::Complement(inner) => {
+                let inner = inner.simplify_pass(meter);
+                self.unless_exhausted(meter, || B::complement(inner))
+            }
+        }
+    }
+
+    /// Returns the node built from the rebuilt operands, or this node as-is if the budget ran
+    /// out while rebuilding them. Operands that were not rebuilt may not satisfy the
+    /// invariants that the rules of the builder rely on.
+    fn unless_exhausted(&self, meter: &Meter, build: impl FnOnce() -> Self) -> Self {
+        if meter.exhausted {
+            self.verbatim()
+        } else {
+            build()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::Pure;
+    use crate::ops::*;
+
+    use super::*;
+
+    type PureRegex = Regex<Pure<usize>>;
+    type StrongRegex = Regex<StronglyCanonical<usize>>;
+
+    #[test]
+    fn test_simplify_unlimited() {
+        let tests: Vec<PureRegex> = vec![
+            1.s(),
+            ([].r() | 1.s()).c(),
+            [].r() | [1.s(), 1.s().c()].r(),
+            [1.s(), 2.s()].r() | [1.s(), 3.s()].r(),
+            (1.s() | !1.s()) & 2.s(),
+            [].r() & (1.s() | 2.s().c()),
+        ];
+        for regex in tests {
+            let simplified = regex.simplify_within(Budget::unlimited());
+            let expected: StrongRegex = regex.rebuild();
+            assert_eq!(
+                expected.rebuild::<Pure<_>>(),
+                simplified,
+                "simplifying {}",
+                regex
+            );
+        }
+    }
+
+    #[test]
+    fn test_simplify_budget() {
+        let regex: PureRegex = ([].r() | [1.s(), 1.s().c()].r()) | ([].r() | 2.s()).c();
+        let unlimited = regex.simplify_within(Budget::unlimited());
+        let tests: Vec<(Budget, usize)> = vec![
+            (Budget::unlimited().with_nodes(0), regex.size()),
+            (Budget::unlimited().with_nodes(6), regex.size()),
+            (Budget::unlimited().with_nodes(regex.size()), 5),
+            (Budget::unlimited(), unlimited.size()),
+            (Budget::unlimited().with_time(Duration::ZERO), regex.size()),
+        ];
+        for (budget, expected) in tests {
+            let simplified = regex.simplify_within(budget);
+            assert_eq!(expected, simplified.size(), "simplifying with {:?}", budget);
+            for word in [vec![], vec![1], vec![1, 1], vec![2, 2], vec![1, 2], vec![3]] {
+                assert_eq!(regex.is_match(&word), simplified.is_match(&word));
+            }
+        }
+
+        // budgets that run out in operands that are not sorted
+        let regex: PureRegex = (1.s() | 2.s()) | (4.s() | 3.s());
+        for nodes in 0..=regex.size() {
+            let simplified = regex.simplify_within(Budget::unlimited().with_nodes(nodes));
+            assert!(
+                simplified.size() <= regex.size(),
+                "simplifying with {}",
+                nodes
+            );
+            for symbol in 0..6 {
+                assert_eq!(regex.is_match([symbol]), simplified.is_match([symbol]));
+            }
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! Finite automata backed by static byte tables.
+
+use itertools::Itertools;
+
+use crate::automaton::FiniteAutomaton;
+use crate::automaton::StateId;
+use crate::Alphabet;
+
+const MAGIC: [u8; 4] = *b"BRZ1";
+const HEADER_LEN: usize = 12;
+
+impl<S: Alphabet + Copy + Into<u32>, L, I: StateId> FiniteAutomaton<S, L, I> {
+    /// Serialize this automaton into a table that can be used by a [`StaticAutomaton`].
+    ///
+    /// The table consists of a header (magic bytes, state count, symbol count), the sorted
+    /// symbols, the accepting flags of all states, and a dense transition table with a row
+    /// per state, and a column per symbol plus one for the default transition. All numbers
+    /// are encoded as little-endian `u32` values.
+    ///
+    /// Returns [`StaticAutomatonError::TooLarge`] if the number of states or symbols does not
+    /// fit in a `u32`.
+    pub fn to_static_bytes(&self) -> Result<Vec<u8>, StaticAutomatonError> {
+        let encode = |value: usize| {
+            u32::try_from(value)
+                .map(u32::to_le_bytes)
+                .map_err(|_| StaticAutomatonError::TooLarge)
+        };
+        let symbols = (0..self.state_count())
+            .flat_map(|state| self.symbol_transitions(state).map(|(symbol, _)| *symbol))
+            .sorted()
+            .dedup()
+            .collect_vec();
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&encode(self.state_count())?);
+        bytes.extend_from_slice(&encode(symbols.len())?);
+        for symbol in &symbols {
+            bytes.extend_from_slice(&(*symbol).into().to_le_bytes());
+        }
+        for state in 0..self.state_count() {
+            bytes.push(self.is_accepting(state) as u8);
+        }
+        for state in 0..self.state_count() {
+            for symbol in &symbols {
+                bytes.extend_from_slice(&encode(self.next(state, symbol))?);
+            }
+            bytes.extend_from_slice(&encode(self.default_transition(state))?);
+        }
+        Ok(bytes)
+    }
+}
+
+/// A finite automaton that reads its transitions directly from a byte table, such as one
+/// included using `include_bytes!`. Matching does not allocate.
+#[derive(Clone, Copy, Debug)]
+pub struct StaticAutomaton<'a> {
+    bytes: &'a [u8],
+    state_count: usize,
+    symbol_count: usize,
+}
+
+/// Errors that can occur when reading a static automaton table.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StaticAutomatonError {
+    /// The table does not start with the expected header.
+    InvalidHeader,
+    /// The length of the table does not match the sizes in its header.
+    InvalidLength,
+    /// The symbols in the table are not strictly increasing.
+    UnsortedSymbols,
+    /// A transition in the table refers to a state that does not exist.
+    InvalidTransition,
+    /// The automaton has more states or symbols than fit in a table.
+    TooLarge,
+}
+
+impl std::fmt::Display for StaticAutomatonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "invalid header"),
+            Self::InvalidLength => write!(f, "invalid length"),
+            Self::UnsortedSymbols => write!(f, "unsorted symbols"),
+            Self::InvalidTransition => write!(f, "invalid transition"),
+            Self::TooLarge => write!(f, "too many states or symbols"),
+        }
+    }
+}
+
+impl std::error::Error for StaticAutomatonError {}
+
+const fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+/// Returns the length of a table with the given numbers of states and symbols, or `None` if it
+/// does not fit in a `usize`.
+const fn table_len(state_count: usize, symbol_count: usize) -> Option<usize> {
+    let Some(row_len) = symbol_count.checked_add(1) else {
+        return None;
+    };
+    let Some(transitions) = state_count.checked_mul(row_len) else {
+        return None;
+    };
+    let Some(transitions_len) = transitions.checked_mul(4) else {
+        return None;
+    };
+    let Some(symbols_len) = symbol_count.checked_mul(4) else {
+        return None;
+    };
+    let Some(len) = HEADER_LEN.checked_add(state_count) else {
+        return None;
+    };
+    let Some(len) = len.checked_add(symbols_len) else {
+        return None;
+    };
+    len.checked_add(transitions_len)
+}
+
+impl<'a> StaticAutomaton<'a> {
+    /// Read an automaton from a table produced by [`FiniteAutomaton::to_static_bytes`].
+    pub const fn from_bytes(bytes: &'a [u8]) -> Result<Self, StaticAutomatonError> {
+        if bytes.len() < HEADER_LEN
+            || bytes[0] != MAGIC[0]
+            || bytes[1] != MAGIC[1]
+            || bytes[2] != MAGIC[2]
+            || bytes[3] != MAGIC[3]
+        {
+            return Err(StaticAutomatonError::InvalidHeader);
+        }
+        let state_count = read_u32(bytes, 4) as usize;
+        let symbol_count = read_u32(bytes, 8) as usize;
+        if state_count == 0 {
+            return Err(StaticAutomatonError::InvalidHeader);
+        }
+        match table_len(state_count, symbol_count) {
+            Some(expected_len) if bytes.len() == expected_len => {}
+            _ => return Err(StaticAutomatonError::InvalidLength),
+        }
+        let fa = Self {
+            bytes,
+            state_count,
+            symbol_count,
+        };
+        let mut idx = 1;
+        while idx < symbol_count {
+            if fa.symbol(idx - 1) >= fa.symbol(idx) {
+                return Err(StaticAutomatonError::UnsortedSymbols);
+            }
+            idx += 1;
+        }
+        let mut idx = 0;
+        while idx < state_count * (symbol_count + 1) {
+            if read_u32(bytes, fa.transitions_offset() + 4 * idx) as usize >= state_count {
+                return Err(StaticAutomatonError::InvalidTransition);
+            }
+            idx += 1;
+        }
+        Ok(fa)
+    }
+
+    pub fn to_matcher(&self) -> StaticMatcher<'a> {
+        StaticMatcher {
+            fa: *self,
+            state: 0,
+        }
+    }
+
+    const fn symbol(&self, idx: usize) -> u32 {
+        read_u32(self.bytes, HEADER_LEN + 4 * idx)
+    }
+
+    const fn accepting_offset(&self) -> usize {
+        HEADER_LEN + 4 * self.symbol_count
+    }
+
+    const fn transitions_offset(&self) -> usize {
+        self.accepting_offset() + self.state_count
+    }
+
+    fn symbol_index(&self, symbol: u32) -> usize {
+        let (mut low, mut high) = (0, self.symbol_count);
+        while low < high {
+            let mid = (low + high) / 2;
+            match self.symbol(mid).cmp(&symbol) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => return mid,
+            }
+        }
+        self.symbol_count
+    }
+
+    fn next(&self, current: usize, symbol: u32) -> usize {
+        let column = self.symbol_index(symbol);
+        let offset = self.transitions_offset() + 4 * (current * (self.symbol_count + 1) + column);
+        read_u32(self.bytes, offset) as usize
+    }
+
+    fn is_accepting(&self, current: usize) -> bool {
+        self.bytes[self.accepting_offset() + current] != 0
+    }
+}
+
+/// A matcher for a [`StaticAutomaton`].
+#[derive(Clone, Copy, Debug)]
+pub struct StaticMatcher<'a> {
+    fa: StaticAutomaton<'a>,
+    state: usize,
+}
+
+impl StaticMatcher<'_> {
+    pub fn next(&mut self, symbol: impl Into<u32>) -> bool {
+        self.state = self.fa.next(self.state, symbol.into());
+        self.fa.is_accepting(self.state)
+    }
+
+    pub fn next_iter(&mut self, symbols: impl IntoIterator<Item = impl Into<u32>>) -> bool {
+        for symbol in symbols {
+            self.next(symbol);
+        }
+        self.fa.is_accepting(self.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::builder::Regex;
+    use crate::ops::*;
+
+    use super::*;
+
+    #[test]
+    fn test_static_matcher() {
+        let tests: Vec<(Regex<ApproximatelySimilarCanonical<u8>>, Vec<u8>, bool)> = vec![
+            ((().r()), vec![], false),
+            (([].r()), vec![], true),
+            (42.s(), vec![42], true),
+            (42.s(), vec![11], false),
+            (([42.s(), (11.s() | 7.s())].r()), vec![42, 7], true),
+            ((42.s().c()), vec![42, 42, 42], true),
+            ((42.s().c()), vec![42, 11], false),
+            ((42.s() & 42.s().c()), vec![42, 42], false),
+            ((!11.s()), vec![42], true),
+            ((!11.s()), vec![11], false),
+        ];
+        for test in tests {
+            let bytes = test.0.to_automaton().to_static_bytes().expect("fits");
+            let fa = StaticAutomaton::from_bytes(&bytes).expect("valid table");
+            assert_eq!(test.2, fa.to_matcher().next_iter(test.1));
+        }
+    }
+
+    #[test]
+    fn test_invalid_tables() {
+        let r: Regex<ApproximatelySimilarCanonical<u8>> = [1.s(), 2.s()].r();
+        let bytes = r.to_automaton().to_static_bytes().expect("fits");
+        assert_eq!(
+            Some(StaticAutomatonError::InvalidHeader),
+            StaticAutomaton::from_bytes(&bytes[1..]).err()
+        );
+        assert_eq!(
+            Some(StaticAutomatonError::InvalidLength),
+            StaticAutomaton::from_bytes(&bytes[..bytes.len() - 1]).err()
+        );
+        let mut huge = bytes[..HEADER_LEN].to_vec();
+        huge[4..HEADER_LEN].fill(0xff);
+        assert_eq!(
+            Some(StaticAutomatonError::InvalidLength),
+            StaticAutomaton::from_bytes(&huge).err()
+        );
+        let mut corrupted = bytes.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] = 0xff;
+        assert_eq!(
+            Some(StaticAutomatonError::InvalidTransition),
+            StaticAutomaton::from_bytes(&corrupted).err()
+        );
+    }
+}
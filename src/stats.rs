@@ -0,0 +1,133 @@
+//! Statistics about the size and structure of regular expressions.
+
+use std::collections::HashMap;
+
+use crate::builder::Builder;
+use crate::builder::Regex;
+
+/// Statistics about the nodes of a regular expression, returned by [`Regex::stats`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RegexStats {
+    /// The total number of nodes.
+    pub nodes: usize,
+    /// The number of structurally distinct subterms.
+    pub distinct_nodes: usize,
+    /// The number of nodes on the longest path from the root to a leaf.
+    pub depth: usize,
+}
+
+impl RegexStats {
+    /// Returns the ratio of distinct subterms to nodes. This is the fraction of the nodes that
+    /// would remain if equal subterms were shared, so lower values mean that interning would
+    /// save more memory.
+    pub fn dedup_ratio(&self) -> f64 {
+        self.distinct_nodes as f64 / self.nodes as f64
+    }
+}
+
+/// A node with its operands replaced by the identifiers of their distinct subterms.
+#[derive(Hash, PartialEq, Eq)]
+enum Subterm<'a, S> {
+    EmptySet,
+    EmptyString,
+    Symbol(&'a S),
+    Concat(usize, usize),
+    Closure(usize),
+    Or(usize, usize),
+    And(usize, usize),
+    Complement(usize),
+}
+
+impl<B: Builder> Regex<B> {
+    /// Returns statistics about the nodes of this regular expression.
+    ///
+    /// Regular expressions are trees, so equal subterms are stored separately. The number of
+    /// distinct subterms shows how much sharing them would save, and helps to find generators
+    /// that produce pathologically large regular expressions.
+    pub fn stats(&self) -> RegexStats {
+        let mut subterms = HashMap::new();
+        let mut nodes = 0;
+        let (_, depth) = self.collect_subterms(&mut subterms, &mut nodes);
+        RegexStats {
+            nodes,
+            distinct_nodes: subterms.len(),
+            depth,
+        }
+    }
+
+    /// Returns the identifier of the distinct subterm for this regular expression, and its
+    /// depth, adding it and its operands to the given subterms.
+    fn collect_subterms<'a>(
+        &'a self,
+        subterms: &mut HashMap<Subterm<'a, B::Symbol>, usize>,
+        nodes: &mut usize,
+    ) -> (usize, usize) {
+        *nodes += 1;
+        let (subterm, depth) = match self {
+            Regex::EmptySet => (Subterm::EmptySet, 0),
+            Regex::EmptyString => (Subterm::EmptyString, 0),
+            Regex::Symbol(value) => (Subterm::Symbol(value), 0),
+            Regex::Concat(left, right) => {
+                let (left, left_depth) = left.collect_subterms(subterms, nodes);
+                let (right, right_depth) = right.collect_subterms(subterms, nodes);
+                (Subterm::Concat(left, right), left_depth.max(right_depth))
+            }
+            Regex::Closure(inner) => {
+                let (inner, depth) = inner.collect_subterms(subterms, nodes);
+                (Subterm::Closure(inner), depth)
+            }
+            Regex::Or(left, right) => {
+                let (left, left_depth) = left.collect_subterms(subterms, nodes);
+                let (right, right_depth) = right.collect_subterms(subterms, nodes);
+                (Subterm::Or(left, right), left_depth.max(right_depth))
+            }
+            Regex::And(left, right) => {
+                let (left, left_depth) = left.collect_subterms(subterms, nodes);
+                let (right, right_depth) = right.collect_subterms(subterms, nodes);
+                (Subterm::And(left, right), left_depth.max(right_depth))
+            }
+            Regex::Complement(inner) => {
+                let (inner, depth) = inner.collect_subterms(subterms, nodes);
+                (Subterm::Complement(inner), depth)
+            }
+        };
+        let next_id = subterms.len();
+        (*subterms.entry(subterm).or_insert(next_id), depth + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::Pure;
+    use crate::ops::*;
+
+    use super::*;
+
+    type PureRegex = Regex<Pure<usize>>;
+
+    #[test]
+    fn test_stats() {
+        let tests: Vec<(PureRegex, usize, usize, usize)> = vec![
+            (().r(), 1, 1, 1),
+            (1.s(), 1, 1, 1),
+            (1.s() | 1.s(), 3, 2, 2),
+            (1.s() | 2.s(), 3, 3, 2),
+            ([1.s(), 2.s()].r() | [1.s(), 2.s()].r(), 7, 4, 3),
+            (!(1.s().c() & 1.s().c()), 6, 4, 4),
+        ];
+        for (regex, nodes, distinct_nodes, depth) in tests {
+            let expected = RegexStats {
+                nodes,
+                distinct_nodes,
+                depth,
+            };
+            assert_eq!(expected, regex.stats(), "{}", regex);
+        }
+    }
+
+    #[test]
+    fn test_dedup_ratio() {
+        let regex: PureRegex = [1.s(), 2.s()].r() | [1.s(), 2.s()].r();
+        assert_eq!(4.0 / 7.0, regex.stats().dedup_ratio());
+    }
+}
@@ -0,0 +1,98 @@
+//! Temporal operators over finite traces, as in linear temporal logic on finite traces.
+
+use crate::builder::Builder;
+use crate::builder::Regex;
+
+impl<B: Builder> Regex<B> {
+    /// Returns a regular expression matching the traces in which some step matches the given
+    /// regular expression.
+    ///
+    /// The operand describes single steps, and only its strings of length one are used. The
+    /// result describes whole traces, and can be combined with the other operators.
+    pub fn eventually(step: Self) -> Self {
+        B::concat_all([
+            B::complement(B::empty_set()),
+            Self::step(step),
+            B::complement(B::empty_set()),
+        ])
+    }
+
+    /// Returns a regular expression matching the traces in which every step matches the given
+    /// regular expression, including the empty trace.
+    ///
+    /// The operand describes single steps, and only its strings of length one are used.
+    pub fn always(step: Self) -> Self {
+        B::complement(Self::eventually(B::complement(step)))
+    }
+
+    /// Returns a regular expression matching the traces in which some step matches `release`,
+    /// and every step before it matches `hold`.
+    ///
+    /// The operands describe single steps, and only their strings of length one are used.
+    pub fn until(hold: Self, release: Self) -> Self {
+        B::concat_all([
+            B::closure(Self::step(hold)),
+            Self::step(release),
+            B::complement(B::empty_set()),
+        ])
+    }
+
+    /// Returns the strings of length one of the given regular expression.
+    fn step(inner: Self) -> Self {
+        B::and(Self::single_symbol(), inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::builder::ApproximatelySimilarCanonical;
+    use crate::ops::*;
+
+    use super::*;
+
+    type AscRegex = Regex<ApproximatelySimilarCanonical<usize>>;
+    type Step = (AscRegex, fn(usize) -> bool);
+
+    #[test]
+    fn test_temporal_operators() {
+        let steps: Vec<Step> = vec![
+            (1.s(), |s| s == 1),
+            (1.s() | 2.s(), |s| s != 3),
+            (!2.s(), |s| s != 2),
+            ([1.s(), 1.s()].r() | 3.s(), |s| s == 3),
+            (().r(), |_| false),
+        ];
+        let words = (1..=4)
+            .flat_map(|len| std::iter::repeat_n([1, 2, 3], len).multi_cartesian_product())
+            .chain([vec![]])
+            .collect_vec();
+        for ((p, p_holds), (q, q_holds)) in steps.iter().cartesian_product(&steps) {
+            let eventually = Regex::eventually(p.clone());
+            let always = Regex::always(p.clone());
+            let until = Regex::until(p.clone(), q.clone());
+            for word in &words {
+                assert_eq!(
+                    word.iter().any(|s| p_holds(*s)),
+                    eventually.is_match(word),
+                    "{} on {:?}",
+                    eventually,
+                    word
+                );
+                assert_eq!(
+                    word.iter().all(|s| p_holds(*s)),
+                    always.is_match(word),
+                    "{} on {:?}",
+                    always,
+                    word
+                );
+                let expected = word
+                    .iter()
+                    .position(|s| q_holds(*s))
+                    .is_some_and(|idx| word[..idx].iter().all(|s| p_holds(*s)));
+                assert_eq!(expected, until.is_match(word), "{} on {:?}", until, word);
+            }
+        }
+    }
+}
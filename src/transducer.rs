@@ -0,0 +1,177 @@
+//! Build a finite state transducer from regular expressions annotated with outputs.
+
+use std::borrow::Borrow;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+use crate::builder::ApproximatelySimilarCanonical;
+use crate::builder::Regex;
+use crate::derivation::Symbols;
+use crate::Alphabet;
+
+/// A deterministic finite state transducer (Mealy machine) that emits outputs on transitions.
+///
+/// The transducer is built from rules, which are regular expressions annotated with an
+/// output. Whenever a transition results in a prefix of the input that matches the regular
+/// expression of a rule, the output of the rule is emitted. Outputs are emitted in the
+/// order of the rules.
+#[derive(Clone)]
+pub struct Transducer<S: Alphabet, O: Clone> {
+    states: Vec<State<S, O>>,
+}
+
+#[derive(Clone)]
+struct State<S: Alphabet, O: Clone> {
+    transitions: HashMap<S, Transition<O>>,
+    default_transition: Transition<O>,
+}
+
+#[derive(Clone)]
+struct Transition<O: Clone> {
+    target: usize,
+    outputs: Vec<O>,
+}
+
+impl<S: Alphabet, O: Clone> Transducer<S, O> {
+    /// Build a transducer from the given rules.
+    ///
+    /// Rules that match the empty string are not triggered at the start, because no
+    /// transition has been taken yet.
+    pub fn from_rules(
+        rules: impl IntoIterator<Item = (Regex<ApproximatelySimilarCanonical<S>>, O)>,
+    ) -> Self {
+        let (regexes, outputs): (Vec<_>, Vec<_>) = rules.into_iter().unzip();
+
+        let mut symbols = HashSet::new();
+        for regex in &regexes {
+            regex.collect_symbols(&mut symbols);
+        }
+        let default_symbols = Symbols::Exclude(symbols.iter().cloned().collect());
+
+        let mut indices: HashMap<Vec<Regex<ApproximatelySimilarCanonical<S>>>, usize> =
+            HashMap::new();
+        let mut states = Vec::new();
+
+        let mut queue = VecDeque::new();
+        let mut get_or_insert = |residuals: Vec<Regex<ApproximatelySimilarCanonical<S>>>,
+                                 queue: &mut VecDeque<_>| {
+            let emitted = residuals
+                .iter()
+                .zip(&outputs)
+                .filter(|(residual, _)| residual.is_nullable())
+                .map(|(_, output)| output.clone())
+                .collect();
+            let target = if let Some(idx) = indices.get(&residuals) {
+                *idx
+            } else {
+                let idx = indices.len();
+                indices.insert(residuals.clone(), idx);
+                queue.push_back(residuals);
+                idx
+            };
+            Transition {
+                target,
+                outputs: emitted,
+            }
+        };
+
+        get_or_insert(regexes, &mut queue);
+        while let Some(residuals) = queue.pop_front() {
+            let mut transitions = HashMap::default();
+            for symbol in &symbols {
+                let class = Symbols::include([symbol.clone()]);
+                let next = residuals.iter().map(|r| r.derive_symbols(&class)).collect();
+                transitions.insert(symbol.clone(), get_or_insert(next, &mut queue));
+            }
+            let default_transition = {
+                let next = residuals
+                    .iter()
+                    .map(|r| r.derive_symbols(&default_symbols))
+                    .collect();
+                get_or_insert(next, &mut queue)
+            };
+            states.push(State {
+                transitions,
+                default_transition,
+            });
+        }
+
+        Transducer { states }
+    }
+
+    pub fn to_translator(&self) -> Translator<'_, S, O> {
+        Translator {
+            fst: Cow::Borrowed(self),
+            state: 0,
+        }
+    }
+
+    pub fn into_translator(self) -> Translator<'static, S, O> {
+        Translator {
+            fst: Cow::Owned(self),
+            state: 0,
+        }
+    }
+
+    fn next(&self, current: usize, symbol: &S) -> &Transition<O> {
+        self.states[current]
+            .transitions
+            .get(symbol)
+            .unwrap_or(&self.states[current].default_transition)
+    }
+}
+
+/// Runs a transducer over a sequence of symbols.
+pub struct Translator<'a, S: Alphabet, O: Clone> {
+    fst: Cow<'a, Transducer<S, O>>,
+    state: usize,
+}
+
+impl<S: Alphabet, O: Clone> Translator<'_, S, O> {
+    /// Consume the given symbol and return the outputs emitted by the transition.
+    pub fn next(&mut self, symbol: &S) -> &[O] {
+        let transition = self.fst.next(self.state, symbol);
+        self.state = transition.target;
+        &transition.outputs
+    }
+
+    /// Consume the given symbols and return all outputs emitted along the way.
+    pub fn next_iter<I>(&mut self, symbols: impl IntoIterator<Item = I>) -> Vec<O>
+    where
+        I: Borrow<S>,
+    {
+        let mut outputs = Vec::new();
+        for symbol in symbols {
+            outputs.extend_from_slice(self.next(symbol.borrow()));
+        }
+        outputs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ops::*;
+
+    use super::*;
+
+    #[test]
+    fn test_translator() {
+        let fst = Transducer::from_rules([
+            ([1.s(), 2.s()].r(), 'a'),
+            ([1.s(), 2.s(), 3.s()].r(), 'b'),
+            (!().r() + 3.s(), 'c'),
+        ]);
+        let tests: Vec<(Vec<usize>, Vec<char>)> = vec![
+            (vec![], vec![]),
+            (vec![1, 2], vec!['a']),
+            (vec![1, 2, 3], vec!['a', 'b', 'c']),
+            (vec![4, 3, 3], vec!['c', 'c']),
+            (vec![1, 3], vec!['c']),
+        ];
+        for (input, expected) in tests {
+            assert_eq!(expected, fst.to_translator().next_iter(&input));
+        }
+    }
+}
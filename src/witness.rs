@@ -0,0 +1,133 @@
+//! Witness words for relations between regular expressions.
+
+use std::collections::HashSet;
+
+use crate::builder::ApproximatelySimilarCanonical;
+use crate::builder::Builder;
+use crate::builder::Regex;
+use crate::derivation::Symbols;
+
+impl<B: Builder> Regex<B> {
+    /// Returns a shortest word in the languages of both this regular expression and the given
+    /// regular expression, or `None` if the languages are disjoint.
+    ///
+    /// Pairs of derivatives of both regular expressions w.r.t. the same words are explored
+    /// breadth first. Every step of the word is a set of symbols: either a single symbol that
+    /// occurs in either regular expression, or the set of all other symbols, any of which can
+    /// be used at that step. Among words of the same length, one is chosen deterministically,
+    /// preferring symbols that occur in the regular expressions.
+    pub fn intersection_witness<X: Builder<Symbol = B::Symbol>>(
+        &self,
+        other: &Regex<X>,
+    ) -> Option<Vec<Symbols<B::Symbol>>> {
+        let left = self.rebuild::<ApproximatelySimilarCanonical<B::Symbol>>();
+        let right = other.rebuild::<ApproximatelySimilarCanonical<B::Symbol>>();
+
+        let mut symbols = HashSet::new();
+        left.collect_symbols(&mut symbols);
+        right.collect_symbols(&mut symbols);
+        let classes = Symbols::classes(symbols);
+
+        let mut visited = HashSet::from([(left.clone(), right.clone())]);
+        let mut pairs = vec![(left, right, None)];
+        let mut current = 0;
+        while let Some((left, right, _)) = pairs.get(current) {
+            if left.is_nullable() && right.is_nullable() {
+                let mut word = Vec::new();
+                let mut idx = current;
+                while let Some((previous, class)) = pairs[idx].2 {
+                    word.push(class);
+                    idx = previous;
+                }
+                word.reverse();
+                return Some(word.into_iter().cloned().collect());
+            }
+            if !matches!(left, Regex::EmptySet) && !matches!(right, Regex::EmptySet) {
+                let next = classes
+                    .iter()
+                    .map(|class| {
+                        (
+                            left.derive_symbols(class),
+                            right.derive_symbols(class),
+                            class,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                for (left, right, class) in next {
+                    if visited.insert((left.clone(), right.clone())) {
+                        pairs.push((left, right, Some((current, class))));
+                    }
+                }
+            }
+            current += 1;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::Pure;
+    use crate::ops::*;
+
+    use super::*;
+
+    type PureRegex = Regex<Pure<usize>>;
+    type Word = Option<Vec<Symbols<usize>>>;
+
+    fn word<const N: usize>(symbols: [usize; N]) -> Word {
+        Some(symbols.map(|symbol| Symbols::include([symbol])).to_vec())
+    }
+
+    #[test]
+    fn test_intersection_witness() {
+        let tests: Vec<(PureRegex, PureRegex, Word)> = vec![
+            (().r(), !().r(), None),
+            ([].r(), 1.s().c(), word([])),
+            (1.s(), 2.s(), None),
+            (1.s() | 2.s(), 2.s() | 3.s(), word([2])),
+            (
+                [1.s().c(), 2.s()].r(),
+                [1.s(), 1.s(), (1.s() | 2.s()).c()].r(),
+                word([1, 1, 2]),
+            ),
+            (!1.s(), 1.s(), None),
+            (
+                !1.s() & !().r(),
+                !([].r()),
+                Some(vec![Symbols::exclude([1])]),
+            ),
+            (
+                [1.s(), 1.s()].r().c(),
+                [1.s(), 1.s(), 1.s()].r().c() & !([].r()),
+                word([1; 6]),
+            ),
+            (
+                !(1.s().c()),
+                !(1.s().c()),
+                Some(vec![Symbols::exclude([1])]),
+            ),
+            (
+                [1.s(), !(1.s().c())].r(),
+                !().r(),
+                Some(vec![Symbols::include([1]), Symbols::exclude([1])]),
+            ),
+        ];
+        for (left, right, expected) in tests {
+            let witness = left.intersection_witness(&right);
+            assert_eq!(expected, witness, "{} ∩ {}", left, right);
+            if let Some(word) = witness {
+                let word = word
+                    .iter()
+                    .map(|symbols| match symbols {
+                        Symbols::Include(included) => *included.iter().next().unwrap(),
+                        Symbols::Exclude(excluded) => {
+                            (0..).find(|symbol| !excluded.contains(symbol)).unwrap()
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                assert!(left.is_match(&word) && right.is_match(&word));
+            }
+        }
+    }
+}